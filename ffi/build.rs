@@ -0,0 +1,150 @@
+// Writes include/chess.h from the exported items in src/lib.rs: opaque
+// structs, C enums and extern "C" functions, each with the comment above it.
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    let source = fs::read_to_string("src/lib.rs").expect("src/lib.rs");
+    let header = header(&source);
+    // Only a changed header is written, so that builds leave the file alone.
+    if fs::read_to_string("include/chess.h").ok().as_deref() != Some(header.as_str()) {
+        fs::create_dir_all("include").expect("include directory");
+        fs::write("include/chess.h", header).expect("include/chess.h");
+    }
+}
+
+fn header(source: &str) -> String {
+    let mut types = String::new();
+    let mut functions = String::new();
+    let mut comment: Vec<&str> = vec![];
+    let mut lines = source.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(text) = line.strip_prefix("//") {
+            comment.push(text);
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+
+        let mut doc = String::new();
+        for text in comment.drain(..) {
+            doc.push_str(&format!("//{}\n", text));
+        }
+        if let Some(rest) = line.strip_prefix("pub struct ") {
+            let name: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+            types.push_str(&format!("{}typedef struct {1} {1};\n\n", doc, name));
+        } else if let Some(rest) = line.strip_prefix("pub enum ") {
+            let name = rest.trim_end_matches(" {");
+            let mut variants = vec![];
+            for line in lines.by_ref() {
+                let line = line.trim();
+                if line == "}" {
+                    break;
+                }
+                let (variant, value) = line.trim_end_matches(',').split_once(" = ").unwrap();
+                variants.push(format!("    CHESS_{} = {},\n", snake(variant), value));
+            }
+            types.push_str(&format!(
+                "{}typedef enum {{\n{}}} {};\n\n",
+                doc,
+                variants.concat(),
+                name
+            ));
+        } else if line.starts_with("pub extern \"C\" fn ")
+            || line.starts_with("pub unsafe extern \"C\" fn ")
+        {
+            let mut signature = line.to_string();
+            while !signature.trim_end().ends_with('{') {
+                signature.push(' ');
+                signature.push_str(lines.next().unwrap().trim());
+            }
+            functions.push_str(&format!("{}{}\n\n", doc, function(&signature)));
+        }
+    }
+
+    format!(
+        "/* Generated by build.rs from src/lib.rs, so edit that instead. */\n\
+         #ifndef CHESS_H\n\
+         #define CHESS_H\n\
+         \n\
+         #include <stdbool.h>\n\
+         #include <stddef.h>\n\
+         \n\
+         #ifdef __cplusplus\n\
+         extern \"C\" {{\n\
+         #endif\n\
+         \n\
+         {}{}\
+         #ifdef __cplusplus\n\
+         }}\n\
+         #endif\n\
+         \n\
+         #endif\n",
+        types, functions
+    )
+}
+
+// "pub unsafe extern "C" fn name(a: T, b: U) -> R {" as "R name(T a, U b);".
+fn function(signature: &str) -> String {
+    let signature = &signature[signature.find(" fn ").unwrap() + 4..];
+    let open = signature.find('(').unwrap();
+    let close = signature.rfind(')').unwrap();
+    let name = &signature[..open];
+    let parameters: Vec<String> = signature[open + 1..close]
+        .split(',')
+        .map(str::trim)
+        .filter(|parameter| !parameter.is_empty())
+        .map(|parameter| {
+            let (name, kind) = parameter.split_once(": ").unwrap();
+            declaration(&c_type(kind), name)
+        })
+        .collect();
+    let result = match signature[close + 1..].trim().trim_end_matches('{').trim() {
+        "" => "void".to_string(),
+        result => c_type(result.trim_start_matches("->").trim()),
+    };
+    let parameters = match parameters.is_empty() {
+        true => "void".to_string(),
+        false => parameters.join(", "),
+    };
+    format!(
+        "{};",
+        declaration(&result, &format!("{}({})", name, parameters))
+    )
+}
+
+fn declaration(kind: &str, name: &str) -> String {
+    match kind.ends_with('*') {
+        true => format!("{}{}", kind, name),
+        false => format!("{} {}", kind, name),
+    }
+}
+
+fn c_type(kind: &str) -> String {
+    if let Some(pointee) = kind.strip_prefix("*mut ") {
+        return format!("{} *", c_type(pointee));
+    }
+    if let Some(pointee) = kind.strip_prefix("*const ") {
+        return format!("const {} *", c_type(pointee));
+    }
+    match kind {
+        "c_char" => "char",
+        "usize" => "size_t",
+        kind => kind,
+    }
+    .to_string()
+}
+
+// GameOver as GAME_OVER.
+fn snake(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_uppercase());
+    }
+    snake
+}
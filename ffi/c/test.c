@@ -0,0 +1,93 @@
+/* Plays through the C interface and checks what comes back. Exits with the
+ * number of failed checks. */
+#include <stdio.h>
+#include <string.h>
+
+#include "chess.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static int has_move(const ChessMoves *moves, const char *notation) {
+    for (size_t i = 0; i < chess_moves_len(moves); i++) {
+        if (strcmp(chess_moves_get(moves, i), notation) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static void test_start_position(void) {
+    ChessGame *game = chess_game_new();
+    ChessMoves *moves = chess_game_legal_moves(game);
+    CHECK(chess_moves_len(moves) == 20);
+    CHECK(has_move(moves, "g1f3"));
+    CHECK(chess_moves_get(moves, 20) == NULL);
+    chess_moves_free(moves);
+
+    char *fen = chess_game_fen(game);
+    CHECK(strcmp(fen, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1") == 0);
+    chess_string_free(fen);
+    CHECK(chess_game_turn(game) == CHESS_WHITE);
+    chess_game_free(game);
+}
+
+static void test_fools_mate(void) {
+    ChessGame *game = chess_game_new();
+    CHECK(chess_game_play(game, "f3") == CHESS_OK);
+    CHECK(chess_game_play(game, "e7e5") == CHESS_OK);
+    CHECK(chess_game_play(game, "e2e5") == CHESS_ILLEGAL);
+    CHECK(chess_game_play(game, "g4") == CHESS_OK);
+    CHECK(chess_game_turn(game) == CHESS_BLACK);
+
+    ChessGame *copy = chess_game_clone(game);
+    CHECK(chess_game_play(game, "Qh4#") == CHESS_OK);
+    CHECK(chess_game_is_check(game));
+    CHECK(chess_game_result(game) == CHESS_BLACK_WINS);
+    CHECK(chess_game_play(game, "a2a3") == CHESS_GAME_OVER);
+    CHECK(chess_game_result(copy) == CHESS_ONGOING);
+
+    CHECK(chess_game_undo(game));
+    CHECK(chess_game_result(game) == CHESS_ONGOING);
+    CHECK(!chess_game_is_check(game));
+    chess_game_free(copy);
+    chess_game_free(game);
+}
+
+static void test_fen_and_null(void) {
+    ChessGame *game = chess_game_from_fen("7k/P7/6K1/8/8/8/8/8 w - - 0 1");
+    CHECK(game != NULL);
+    ChessMoves *moves = chess_game_legal_moves(game);
+    CHECK(has_move(moves, "a7a8q"));
+    CHECK(has_move(moves, "a7a8n"));
+    chess_moves_free(moves);
+    CHECK(chess_game_play(game, "a8=Q") == CHESS_OK);
+    CHECK(chess_game_result(game) == CHESS_WHITE_WINS);
+    CHECK(!chess_game_undo(NULL));
+    chess_game_free(game);
+
+    CHECK(chess_game_from_fen("not a position") == NULL);
+    CHECK(chess_game_from_fen(NULL) == NULL);
+    CHECK(chess_game_play(NULL, "e4") == CHESS_INVALID_ARGUMENT);
+    CHECK(chess_moves_len(NULL) == 0);
+    chess_game_free(NULL);
+    chess_moves_free(NULL);
+    chess_string_free(NULL);
+}
+
+int main(void) {
+    test_start_position();
+    test_fools_mate();
+    test_fen_and_null();
+    if (failures == 0) {
+        printf("All checks passed.\n");
+    }
+    return failures;
+}
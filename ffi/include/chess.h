@@ -0,0 +1,78 @@
+/* Generated by build.rs from src/lib.rs, so edit that instead. */
+#ifndef CHESS_H
+#define CHESS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct ChessGame ChessGame;
+
+typedef struct ChessMoves ChessMoves;
+
+typedef enum {
+    CHESS_OK = 0,
+    CHESS_ILLEGAL = 1,
+    CHESS_GAME_OVER = 2,
+    CHESS_INVALID_ARGUMENT = 3,
+} ChessStatus;
+
+typedef enum {
+    CHESS_ONGOING = 0,
+    CHESS_WHITE_WINS = 1,
+    CHESS_BLACK_WINS = 2,
+    CHESS_DRAW = 3,
+} ChessResult;
+
+typedef enum {
+    CHESS_WHITE = 0,
+    CHESS_BLACK = 1,
+} ChessColor;
+
+// A new game from the standard start position.
+ChessGame *chess_game_new(void);
+
+// A new game from a FEN string, or NULL when it cannot be read.
+ChessGame *chess_game_from_fen(const char *fen);
+
+// A separate copy of the game, history included.
+ChessGame *chess_game_clone(const ChessGame *game);
+
+void chess_game_free(ChessGame *game);
+
+// The position as FEN, to be freed with chess_string_free.
+char *chess_game_fen(const ChessGame *game);
+
+void chess_string_free(char *text);
+
+// Every legal move in coordinate notation, as in "e2e4" or "e7e8q".
+ChessMoves *chess_game_legal_moves(const ChessGame *game);
+
+size_t chess_moves_len(const ChessMoves *moves);
+
+// The move at the index, owned by the list, or NULL past its end.
+const char *chess_moves_get(const ChessMoves *moves, size_t index);
+
+void chess_moves_free(ChessMoves *moves);
+
+// Plays a whole move, written in coordinates or in SAN.
+ChessStatus chess_game_play(ChessGame *game, const char *notation);
+
+// Takes back the last move, returning false when there is none.
+bool chess_game_undo(ChessGame *game);
+
+ChessColor chess_game_turn(const ChessGame *game);
+
+// Whether the side to move is in check.
+bool chess_game_is_check(const ChessGame *game);
+
+ChessResult chess_game_result(const ChessGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,195 @@
+// A C interface to the rules. Games and move lists are opaque handles that
+// the caller frees with the matching function, and strings handed out are
+// freed with chess_string_free. Every function accepts NULL handles and
+// answers them as it would an empty or failed call.
+//
+// build.rs writes include/chess.h from the declarations in this file, so
+// exported items keep to one pattern: a comment, #[no_mangle] and then the
+// function.
+#![allow(clippy::missing_safety_doc)]
+
+use chess::game::{EndResult, Game, Turn, TurnResult};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+#[cfg(test)]
+mod tests;
+
+pub struct ChessGame(Game);
+
+pub struct ChessMoves(Vec<CString>);
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChessStatus {
+    Ok = 0,
+    Illegal = 1,
+    GameOver = 2,
+    InvalidArgument = 3,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChessResult {
+    Ongoing = 0,
+    WhiteWins = 1,
+    BlackWins = 2,
+    Draw = 3,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChessColor {
+    White = 0,
+    Black = 1,
+}
+
+fn handle(game: Game) -> *mut ChessGame {
+    Box::into_raw(Box::new(ChessGame(game)))
+}
+
+unsafe fn text<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+// A new game from the standard start position.
+#[no_mangle]
+pub extern "C" fn chess_game_new() -> *mut ChessGame {
+    handle(Game::new())
+}
+
+// A new game from a FEN string, or NULL when it cannot be read.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_from_fen(fen: *const c_char) -> *mut ChessGame {
+    match text(fen).and_then(Game::from_fen) {
+        Some(game) => handle(game),
+        None => ptr::null_mut(),
+    }
+}
+
+// A separate copy of the game, history included.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_clone(game: *const ChessGame) -> *mut ChessGame {
+    match game.as_ref() {
+        Some(game) => handle(game.0.clone()),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_free(game: *mut ChessGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+// The position as FEN, to be freed with chess_string_free.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_fen(game: *const ChessGame) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) => CString::new(game.0.to_fen()).map_or(ptr::null_mut(), CString::into_raw),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+// Every legal move in coordinate notation, as in "e2e4" or "e7e8q".
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_legal_moves(game: *const ChessGame) -> *mut ChessMoves {
+    let game = match game.as_ref() {
+        Some(game) => game,
+        None => return ptr::null_mut(),
+    };
+    let moves = game
+        .0
+        .clone()
+        .legal_turns()
+        .iter()
+        .filter_map(|turn| CString::new(turn.to_string()).ok())
+        .collect();
+    Box::into_raw(Box::new(ChessMoves(moves)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_moves_len(moves: *const ChessMoves) -> usize {
+    moves.as_ref().map_or(0, |moves| moves.0.len())
+}
+
+// The move at the index, owned by the list, or NULL past its end.
+#[no_mangle]
+pub unsafe extern "C" fn chess_moves_get(moves: *const ChessMoves, index: usize) -> *const c_char {
+    match moves.as_ref().and_then(|moves| moves.0.get(index)) {
+        Some(notation) => notation.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_moves_free(moves: *mut ChessMoves) {
+    if !moves.is_null() {
+        drop(Box::from_raw(moves));
+    }
+}
+
+// Plays a whole move, written in coordinates or in SAN.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_play(
+    game: *mut ChessGame,
+    notation: *const c_char,
+) -> ChessStatus {
+    let (game, notation) = match (game.as_mut(), text(notation)) {
+        (Some(game), Some(notation)) => (&mut game.0, notation),
+        _ => return ChessStatus::InvalidArgument,
+    };
+    if game.get_result().is_some() {
+        return ChessStatus::GameOver;
+    }
+
+    let turn = Turn::parse(notation)
+        .filter(|turn| game.legal_turns().contains(turn))
+        .or_else(|| game.parse_san(notation));
+    match turn.map(|turn| game.play(&turn)) {
+        Some(TurnResult::Failed) | None => ChessStatus::Illegal,
+        Some(_) => ChessStatus::Ok,
+    }
+}
+
+// Takes back the last move, returning false when there is none.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_undo(game: *mut ChessGame) -> bool {
+    game.as_mut().is_some_and(|game| game.0.undo())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_turn(game: *const ChessGame) -> ChessColor {
+    match game.as_ref().map(|game| game.0.color) {
+        Some(chess::Color::Black) => ChessColor::Black,
+        _ => ChessColor::White,
+    }
+}
+
+// Whether the side to move is in check.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_is_check(game: *const ChessGame) -> bool {
+    game.as_ref().is_some_and(|game| game.0.is_checked())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_result(game: *const ChessGame) -> ChessResult {
+    match game.as_ref().and_then(|game| game.0.get_result()) {
+        Some(EndResult::Win(chess::Color::White)) => ChessResult::WhiteWins,
+        Some(EndResult::Win(chess::Color::Black)) => ChessResult::BlackWins,
+        Some(EndResult::Tie) => ChessResult::Draw,
+        None => ChessResult::Ongoing,
+    }
+}
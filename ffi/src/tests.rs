@@ -0,0 +1,88 @@
+use super::*;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+fn c(text: &str) -> CString {
+    CString::new(text).unwrap()
+}
+
+#[test]
+fn test_playing_through_handles() {
+    unsafe {
+        let game = chess_game_from_fen(c("7k/P7/6K1/8/8/8/8/8 w - - 0 1").as_ptr());
+        let moves = chess_game_legal_moves(game);
+        assert_eq!(chess_moves_len(moves), 10);
+        let first = CStr::from_ptr(chess_moves_get(moves, 0)).to_str().unwrap();
+        assert_eq!(first, "a7a8q");
+        assert!(chess_moves_get(moves, 10).is_null());
+        chess_moves_free(moves);
+
+        assert_eq!(
+            chess_game_play(game, c("a8").as_ptr()),
+            ChessStatus::Illegal
+        );
+        assert_eq!(chess_game_play(game, c("a7a8r").as_ptr()), ChessStatus::Ok);
+        assert_eq!(chess_game_result(game), ChessResult::WhiteWins);
+        assert!(chess_game_is_check(game));
+        assert!(chess_game_undo(game));
+        assert_eq!(chess_game_turn(game), ChessColor::White);
+
+        let fen = chess_game_fen(game);
+        assert_eq!(
+            CStr::from_ptr(fen).to_str(),
+            Ok("7k/P7/6K1/8/8/8/8/8 w - - 0 1")
+        );
+        chess_string_free(fen);
+        chess_game_free(game);
+
+        let game = chess_game_new();
+        let invalid = [0xffu8, 0];
+        assert_eq!(
+            chess_game_play(game, invalid.as_ptr() as *const c_char),
+            ChessStatus::InvalidArgument
+        );
+        chess_game_free(game);
+    }
+}
+
+// Builds c/test.c against the static library from `cargo build` and runs it,
+// skipping when that library has not been built yet.
+#[test]
+fn test_c_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let exe = env::current_exe().unwrap();
+    let library = exe
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("libchess_ffi.a");
+    if !library.exists() {
+        eprintln!("Skipping the C test without {}", library.display());
+        return;
+    }
+    let program = exe.with_file_name("chess_ffi_c_test");
+    let compiled = Command::new("cc")
+        .arg(root.join("c/test.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status();
+    match compiled {
+        Ok(status) => assert!(status.success()),
+        Err(error) => {
+            eprintln!("Skipping the C test without a C compiler: {}", error);
+            return;
+        }
+    }
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
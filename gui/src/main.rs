@@ -1,10 +1,26 @@
+use chess::book;
+use chess::engine;
 use chess::game;
+use chess::game::bughouse;
+use chess::game::clock;
+use chess::game::variant;
+use chess::net;
 use chess::pieces;
+use chess::pieces::custom;
+use chess::store;
+use chess::tablebase;
 use ggez;
 use ggez::event;
 use ggez::graphics;
 use ggez::nalgebra as na;
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::net::TcpListener;
 use std::path;
+use std::sync::mpsc;
+use std::thread;
+use std::time;
 mod screen;
 pub const WINDOW_SIZE: (f32, f32) = (1200.0, 900.0);
 #[derive(Debug, Clone)]
@@ -15,6 +31,10 @@ pub enum Piece {
     Pawn(Color),
     Knight(Color),
     Bishop(Color),
+    Archbishop(Color),
+    Chancellor(Color),
+    Custom(char, Color),
+    Duck,
     None,
 }
 pub enum State {
@@ -30,6 +50,7 @@ pub enum Overlay {
         selected: Position,
         to: Vec<Position>,
     },
+    Drops(Vec<Position>),
     None,
 }
 #[derive(Debug, Clone, Copy)]
@@ -42,7 +63,98 @@ impl Position {
         Position((pos.0 - 1) as usize, (pos.1 - 1) as usize)
     }
 }
+// Squares are scaled so that every board fits where an 8x8 one would.
+#[derive(Debug, Clone, Copy)]
+pub struct Grid {
+    pub size: f32,
+    pub height: usize,
+}
+impl Grid {
+    pub const PANEL: Grid = Grid {
+        size: 100.0,
+        height: 8,
+    };
+    pub fn of(board: &chess::board::Board) -> Self {
+        let files = board.width.clone().count();
+        let ranks = board.height.clone().count();
+        Grid {
+            size: 800.0 / files.max(ranks) as f32,
+            height: ranks,
+        }
+    }
+    pub fn origin(&self, position: &Position) -> (f32, f32) {
+        (
+            50.0 + self.size * position.0 as f32,
+            50.0 + self.size * (self.height - 1 - position.1) as f32,
+        )
+    }
+}
 pub struct Board(Vec<(Piece, Position)>);
+pub fn piece_of(piece: &pieces::Piece) -> Piece {
+    let color = match piece.color {
+        chess::Color::White => Color::White,
+        chess::Color::Black => Color::Black,
+    };
+    match piece.kind {
+        pieces::Kind::King => Piece::King(color),
+        pieces::Kind::Knight => Piece::Knight(color),
+        pieces::Kind::Rook => Piece::Rook(color),
+        pieces::Kind::Pawn => Piece::Pawn(color),
+        pieces::Kind::Queen => Piece::Queen(color),
+        pieces::Kind::Bishop => Piece::Bishop(color),
+        custom::ARCHBISHOP => Piece::Archbishop(color),
+        custom::CHANCELLOR => Piece::Chancellor(color),
+        pieces::Kind::Custom(_) => Piece::Custom(piece.symbol(), color),
+    }
+}
+// In fog of war the side to move only sees what its own pieces reach.
+pub fn fogged(game: &game::Game) -> bool {
+    game.get_variant()
+        .is_some_and(|variant| variant.name() == "fogofwar")
+}
+// Only the pieces the variant allows are offered, in panel coordinates.
+pub fn promotion_buttons(game: &game::Game) -> Vec<(Position, pieces::Kind)> {
+    let buttons = [
+        (Position(9, 5), pieces::Kind::Bishop),
+        (Position(10, 5), pieces::Kind::Knight),
+        (Position(9, 4), pieces::Kind::Queen),
+        (Position(10, 4), pieces::Kind::Rook),
+        (Position(9, 3), custom::ARCHBISHOP),
+        (Position(10, 3), custom::CHANCELLOR),
+        // Only antichess promotes to king. Its button sits above the others,
+        // clear of the text to the left and of the pocket below.
+        (Position(10, 6), pieces::Kind::King),
+    ];
+    buttons
+        .iter()
+        .filter(|(_, kind)| game.get_board().promotions.contains(kind))
+        .cloned()
+        .collect()
+}
+// The crazyhouse pocket of the side to move, with how many of each kind it
+// holds, below the promotion buttons.
+pub fn pocket_buttons(game: &game::Game) -> Vec<(Position, pieces::Kind, usize)> {
+    let board = game.get_board();
+    if !board.crazyhouse {
+        return vec![];
+    }
+    let pocket = board.pockets.get(&game.color).cloned().unwrap_or_default();
+    let buttons = [
+        (Position(9, 2), pieces::Kind::Queen),
+        (Position(10, 2), pieces::Kind::Rook),
+        (Position(9, 1), pieces::Kind::Bishop),
+        (Position(10, 1), pieces::Kind::Knight),
+        (Position(9, 0), pieces::Kind::Pawn),
+    ];
+    buttons
+        .iter()
+        .map(|(position, kind)| {
+            let count = pocket.iter().filter(|pocketed| *pocketed == kind).count();
+            (*position, *kind, count)
+        })
+        .filter(|(_, _, count)| *count > 0)
+        .collect()
+}
 #[derive(PartialEq, Clone, Debug)]
 pub enum Color {
     Black,
@@ -56,16 +168,76 @@ pub struct MainState {
     pub state: State,
     pub help: Overlay,
     pub selected: Selected,
+    pub computer: Option<(chess::Color, engine::Engine)>,
+    pub bughouse: Option<Bughouse>,
+    pub network: Option<Network>,
+    pub correspondence: Option<Correspondence>,
+}
+// A game against another GUI over the network. Lines typed in the terminal
+// are sent as chat.
+pub struct Network {
+    pub peer: net::Peer,
+    pub chat: mpsc::Receiver<String>,
+}
+// A correspondence game kept in a store on disk. Each finished turn is saved
+// straight away, and the panel lists the games awaiting the player's move.
+pub struct Correspondence {
+    pub store: store::Store,
+    pub player: String,
+    pub saved: store::SavedGame,
+    pub awaiting: Vec<String>,
+}
+impl Correspondence {
+    fn save(&mut self) {
+        if let Err(error) = self.store.save(&self.saved) {
+            eprintln!("Could not save game {}: {}", self.saved.id, error);
+        }
+        self.awaiting = awaiting(&self.store, &self.player);
+    }
+}
+// Both bughouse boards are played on one screen, showing one at a time. Each
+// board keeps the time its last move was made to charge the clocks.
+pub struct Bughouse {
+    pub coordinator: bughouse::Bughouse,
+    pub active: usize,
+    pub last_move: [time::Instant; 2],
 }
 #[derive(Clone)]
 pub enum Selected {
     Position(Position),
+    Drop(pieces::Kind),
     None,
 }
 impl MainState {
-    fn new() -> ggez::GameResult<MainState> {
+    fn new(
+        game: game::Game,
+        computer: Option<(chess::Color, engine::Engine)>,
+        bughouse: Option<bughouse::Bughouse>,
+        peer: Option<net::Peer>,
+        correspondence: Option<Correspondence>,
+    ) -> ggez::GameResult<MainState> {
+        let network = peer.map(|peer| {
+            let (sender, chat) = mpsc::channel();
+            thread::spawn(move || {
+                for line in io::stdin().lock().lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+            Network { peer, chat }
+        });
+        let bughouse = bughouse.map(|coordinator| Bughouse {
+            coordinator,
+            active: 0,
+            last_move: [time::Instant::now(); 2],
+        });
         let mut s = MainState {
-            game: game::Game::new(),
+            game: match (&bughouse, &correspondence) {
+                (Some(bughouse), _) => bughouse.coordinator.game(0).clone(),
+                (_, Some(correspondence)) => correspondence.saved.get_game().clone(),
+                _ => game,
+            },
             board: Board(vec![]),
             turn: Color::White,
             state: State::Playing {
@@ -74,33 +246,195 @@ impl MainState {
             },
             help: Overlay::None,
             selected: Selected::None,
+            computer,
+            bughouse,
+            network,
+            correspondence,
         };
         s.parse();
+        s.computer_turn();
         Ok(s)
     }
+    fn update_state(&mut self, result: game::TurnResult) {
+        match result {
+            game::TurnResult::Promotion => {
+                self.state = State::Playing {
+                    promotion: true,
+                    check: false,
+                }
+            }
+            game::TurnResult::Checked => {
+                self.state = State::Playing {
+                    promotion: false,
+                    check: true,
+                }
+            }
+            game::TurnResult::Moved | game::TurnResult::Duck => {
+                self.state = State::Playing {
+                    promotion: false,
+                    check: false,
+                }
+            }
+            game::TurnResult::GameEnd(_) => self.state = State::Checkmate,
+            _ => (),
+        };
+        self.parse();
+    }
+    // Moves go through the bughouse coordinator when there is one, so that
+    // captures reach the other board, and the shown game is copied back.
+    fn act<F>(&mut self, action: F) -> game::TurnResult
+    where
+        F: FnOnce(&mut bughouse::Bughouse, usize, time::Duration) -> game::TurnResult,
+    {
+        let bughouse = match &mut self.bughouse {
+            Some(bughouse) => bughouse,
+            None => return game::TurnResult::Failed,
+        };
+        let board = bughouse.active;
+        let elapsed = bughouse.last_move[board].elapsed();
+        let result = action(&mut bughouse.coordinator, board, elapsed);
+        if result != game::TurnResult::Failed && result != game::TurnResult::Promotion {
+            bughouse.last_move[board] = time::Instant::now();
+        }
+        self.game = bughouse.coordinator.game(board).clone();
+        result
+    }
+    fn turn(&mut self, source: chess::Point, target: chess::Point) -> game::TurnResult {
+        if self.bughouse.is_none() {
+            return self.game.turn(source, target);
+        }
+        self.act(|bughouse, board, elapsed| bughouse.turn(board, source, target, elapsed))
+    }
+    fn promote(&mut self, kind: pieces::Kind) -> game::TurnResult {
+        if self.bughouse.is_none() {
+            return self.game.promote(kind);
+        }
+        self.act(|bughouse, board, _| bughouse.promote(board, kind))
+    }
+    fn drop_piece(&mut self, kind: pieces::Kind, target: chess::Point) -> game::TurnResult {
+        if self.bughouse.is_none() {
+            return self.game.drop_piece(kind, target);
+        }
+        self.act(|bughouse, board, elapsed| bughouse.drop_piece(board, kind, target, elapsed))
+    }
+    fn switch_board(&mut self) {
+        if let Some(bughouse) = &mut self.bughouse {
+            bughouse.active = 1 - bughouse.active;
+            self.game = bughouse.coordinator.game(bughouse.active).clone();
+            if bughouse.coordinator.get_result().is_none() {
+                self.state = State::Playing {
+                    promotion: false,
+                    check: self.game.is_checked(),
+                };
+            }
+            self.selected = Selected::None;
+            self.help = Overlay::None;
+            self.parse();
+        }
+    }
+    // Passes a finished local turn on to the other player, over the network
+    // or through the store.
+    fn send_turn(&mut self, result: &game::TurnResult) {
+        match result {
+            game::TurnResult::Failed | game::TurnResult::Promotion | game::TurnResult::Duck => {
+                return
+            }
+            _ => (),
+        }
+        if let Some(network) = &mut self.network {
+            if let Err(error) = network.peer.send_turn(&self.game) {
+                eprintln!("Could not send the move: {}", error);
+            }
+        }
+        if let Some(correspondence) = &mut self.correspondence {
+            let turn = match self.game.get_history().last() {
+                Some(turn) => *turn,
+                None => return,
+            };
+            let player = correspondence.player.clone();
+            correspondence
+                .saved
+                .play(&player, &turn, time::SystemTime::now());
+            correspondence.save();
+            self.game = correspondence.saved.get_game().clone();
+        }
+    }
+    fn poll_network(&mut self) {
+        let network = match &mut self.network {
+            Some(network) => network,
+            None => return,
+        };
+        while let Ok(line) = network.chat.try_recv() {
+            if let Err(error) = network.peer.chat(&line) {
+                eprintln!("Could not send the message: {}", error);
+            }
+        }
+        let event = match network.peer.poll(&mut self.game) {
+            Some(event) => event,
+            None => return,
+        };
+        match event {
+            net::Event::Moved(_, result) => self.update_state(result),
+            net::Event::Illegal(turn) => {
+                eprintln!("The other player sent an illegal move {}", turn)
+            }
+            net::Event::OutOfTime(_) => {
+                println!("The other player ran out of time.");
+                self.state = State::Checkmate;
+            }
+            net::Event::Resigned => {
+                println!("The other player resigned.");
+                self.state = State::Checkmate;
+            }
+            net::Event::DrawOffered => println!("Draw offered: press D to accept or N to decline."),
+            net::Event::DrawAgreed => {
+                println!("Draw agreed.");
+                self.state = State::Remi;
+            }
+            net::Event::DrawDeclined => println!("The draw offer was declined."),
+            net::Event::Chat(text) => println!("> {}", text),
+            net::Event::Disconnected => {
+                println!("The other player left.");
+                self.network = None;
+            }
+        }
+    }
+    fn computer_turn(&mut self) {
+        if self.bughouse.is_some() {
+            return;
+        }
+        let engine = match &self.computer {
+            Some((color, engine)) if *color == self.game.color => engine.clone(),
+            _ => return,
+        };
+        let limits = engine::Limits {
+            depth: Some(3),
+            ..engine::Limits::default()
+        };
+        if let Some(turn) = engine.search(&self.game, &limits, |_| ()) {
+            let result = self.game.play(&turn);
+            self.update_state(result);
+        }
+    }
     fn parse(&mut self) {
         self.board = Board(vec![]);
-        for i in 1..=8 {
-            for j in 1..=8 {
+        let board = if fogged(&self.game) {
+            self.game.view(&self.game.color)
+        } else {
+            self.game.get_board().clone()
+        };
+        for i in board.width.clone() {
+            for j in board.height.clone() {
                 let map_point = chess::Point(i, j);
                 let position = Position::new(&map_point);
-                if let Some(cur) = self.game.get_board().at_point(&map_point) {
-                    let color = match cur.color {
-                        chess::Color::White => Color::White,
-                        chess::Color::Black => Color::Black,
-                    };
-                    let piece = match cur.kind {
-                        chess::pieces::Kind::King => Piece::King(color),
-                        chess::pieces::Kind::Knight => Piece::Knight(color),
-                        chess::pieces::Kind::Rook => Piece::Rook(color),
-                        chess::pieces::Kind::Pawn => Piece::Pawn(color),
-                        chess::pieces::Kind::Queen => Piece::Queen(color),
-                        chess::pieces::Kind::Bishop => Piece::Bishop(color),
-                    };
-                    self.board.0.push((piece, position));
+                if let Some(cur) = board.at_point(&map_point) {
+                    self.board.0.push((piece_of(&cur), position));
                 }
             }
         }
+        if let Some(duck) = board.duck {
+            self.board.0.push((Piece::Duck, Position::new(&duck)));
+        }
         self.turn = match self.game.color {
             chess::Color::Black => Color::Black,
             chess::Color::White => Color::White,
@@ -109,7 +443,9 @@ impl MainState {
 }
 #[derive(Debug)]
 pub enum ButtonType {
-    Promotion(Piece),
+    Promotion(pieces::Kind),
+    Drop(pieces::Kind),
+    SwitchBoard,
     LCastling,
     SCastling,
 }
@@ -131,34 +467,71 @@ impl Box {
         }
     }
 }
-fn get_element(point: &mut (f32, f32)) -> Element {
-    let mut board = Box(50.0, 850.0, 50.0, 850.0);
+pub const SWITCH_BUTTON: Position = Position(10, 0);
+fn get_element(
+    point: &mut (f32, f32),
+    grid: &Grid,
+    files: usize,
+    buttons: &[(Position, pieces::Kind)],
+    pockets: &[(Position, pieces::Kind, usize)],
+    bughouse: bool,
+) -> Element {
+    let mut board = Box(
+        50.0,
+        50.0 + grid.size * files as f32,
+        50.0,
+        50.0 + grid.size * grid.height as f32,
+    );
     if board.selected(point) {
         return Element::Tile(Position(
-            (point.0 / 100.0) as usize,
-            7 - (point.1 / 100.0) as usize,
+            (point.0 / grid.size) as usize,
+            grid.height - 1 - (point.1 / grid.size) as usize,
         ));
     }
-    let mut promotions = Box(950.0, 1150.0, 250.0, 450.0);
-    if promotions.selected(point) {
-        return match point {
-            (x, y) if *x < 100.0 && *y < 100.0 => {
-                Element::Button(ButtonType::Promotion(Piece::Bishop(Color::None)))
-            }
-            (x, y) if *x < 100.0 => {
-                Element::Button(ButtonType::Promotion(Piece::Queen(Color::None)))
-            }
-            (x, y) if *y < 100.0 => {
-                Element::Button(ButtonType::Promotion(Piece::Knight(Color::None)))
-            }
-            _ => Element::Button(ButtonType::Promotion(Piece::Rook(Color::None))),
-        };
+    for (position, kind) in buttons.iter() {
+        let (x, y) = Grid::PANEL.origin(position);
+        if Box(x, x + 100.0, y, y + 100.0).selected(point) {
+            return Element::Button(ButtonType::Promotion(*kind));
+        }
+    }
+    for (position, kind, _) in pockets.iter() {
+        let (x, y) = Grid::PANEL.origin(position);
+        if Box(x, x + 100.0, y, y + 100.0).selected(point) {
+            return Element::Button(ButtonType::Drop(*kind));
+        }
+    }
+    let (x, y) = Grid::PANEL.origin(&SWITCH_BUTTON);
+    if bughouse && Box(x, x + 100.0, y, y + 100.0).selected(point) {
+        return Element::Button(ButtonType::SwitchBoard);
     }
     Element::None
 }
 
 impl event::EventHandler for MainState {
     fn update(&mut self, _ctx: &mut ggez::Context) -> ggez::GameResult {
+        self.poll_network();
+        if let Some(correspondence) = &mut self.correspondence {
+            if correspondence
+                .saved
+                .check_deadline(time::SystemTime::now())
+                .is_some()
+            {
+                correspondence.save();
+                self.game = correspondence.saved.get_game().clone();
+                self.state = State::Checkmate;
+            }
+        }
+        if self.game.check_time().is_some() {
+            self.state = State::Checkmate;
+        }
+        if let Some(bughouse) = &mut self.bughouse {
+            for board in 0..2 {
+                let elapsed = bughouse.last_move[board].elapsed();
+                if bughouse.coordinator.flag(board, elapsed).is_some() {
+                    self.state = State::Checkmate;
+                }
+            }
+        }
         Ok(())
     }
     fn mouse_button_down_event(
@@ -168,7 +541,30 @@ impl event::EventHandler for MainState {
         x: f32,
         y: f32,
     ) {
-        match get_element(&mut (x, y)) {
+        let waiting = self
+            .network
+            .as_ref()
+            .is_some_and(|network| network.peer.color() != self.game.color);
+        let elsewhere = self.correspondence.as_ref().is_some_and(|correspondence| {
+            correspondence.saved.to_move() != Some(correspondence.player.as_str())
+        });
+        if waiting || elsewhere {
+            return;
+        }
+        let grid = Grid::of(self.game.get_board());
+        let files = self.game.get_board().width.clone().count();
+        let buttons = promotion_buttons(&self.game);
+        let pockets = pocket_buttons(&self.game);
+        let bughouse = self.bughouse.is_some();
+        match get_element(&mut (x, y), &grid, files, &buttons, &pockets, bughouse) {
+            // The second half of a duck chess turn is a click on any empty tile.
+            Element::Tile(mut pos) if self.game.is_awaiting_duck() => {
+                let state = self.game.place_duck(pos.translate());
+
+                self.send_turn(&state);
+                self.update_state(state);
+                self.computer_turn();
+            }
             Element::Tile(mut pos) => match self.selected.clone() {
                 Selected::None => {
                     self.selected = Selected::Position(pos);
@@ -181,50 +577,36 @@ impl event::EventHandler for MainState {
                     }
                 }
                 Selected::Position(position) => {
-                    let state = self
-                        .game
-                        .turn(position.clone().translate(), pos.translate());
-
-                    match state {
-                        game::TurnResult::Promotion => {
-                            self.state = State::Playing {
-                                promotion: true,
-                                check: false,
-                            }
-                        }
-                        game::TurnResult::Checked => {
-                            self.state = State::Playing {
-                                promotion: false,
-                                check: true,
-                            }
-                        }
-                        game::TurnResult::Moved => {
-                            self.state = State::Playing {
-                                promotion: false,
-                                check: false,
-                            }
-                        }
-                        game::TurnResult::GameEnd(_) => self.state = State::Checkmate,
-                        _ => (),
-                    };
-                    self.parse();
+                    let state = self.turn(position.clone().translate(), pos.translate());
+
+                    self.send_turn(&state);
+                    self.update_state(state);
+                    self.selected = Selected::None;
+                    self.help = Overlay::None;
+                    self.computer_turn();
+                }
+                Selected::Drop(kind) => {
+                    let state = self.drop_piece(kind, pos.translate());
+
+                    self.send_turn(&state);
+                    self.update_state(state);
                     self.selected = Selected::None;
                     self.help = Overlay::None;
+                    self.computer_turn();
                 }
             },
-            Element::Button(ButtonType::Promotion(piece)) => {
-                self.game.promote(match piece {
-                    Piece::Queen(_) => pieces::Kind::Queen,
-                    Piece::Rook(_) => pieces::Kind::Rook,
-                    Piece::Knight(_) => pieces::Kind::Knight,
-                    Piece::Bishop(_) => pieces::Kind::Bishop,
-                    _ => pieces::Kind::King,
-                });
+            Element::Button(ButtonType::Drop(kind)) => {
+                self.selected = Selected::Drop(kind);
+                let to = self.game.get_drops(kind).unwrap_or_default();
+                self.help = Overlay::Drops(to.iter().map(Position::new).collect());
+            }
+            Element::Button(ButtonType::SwitchBoard) => self.switch_board(),
+            Element::Button(ButtonType::Promotion(kind)) => {
+                let state = self.promote(kind);
 
-                self.state = State::Playing {
-                    promotion: false,
-                    check: false,
-                };
+                self.send_turn(&state);
+                self.update_state(state);
+                self.computer_turn();
             }
             _ => {
                 self.selected = Selected::None;
@@ -232,6 +614,46 @@ impl event::EventHandler for MainState {
             }
         }
     }
+    // Over the network R resigns, D offers or accepts a draw and N declines
+    // one. In a correspondence game R resigns.
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut ggez::Context,
+        keycode: event::KeyCode,
+        _keymods: event::KeyMods,
+        _repeat: bool,
+    ) {
+        if let Some(correspondence) = &mut self.correspondence {
+            let player = correspondence.player.clone();
+            if keycode == event::KeyCode::R
+                && correspondence.saved.resign(&player) != game::TurnResult::Failed
+            {
+                correspondence.save();
+                self.game = correspondence.saved.get_game().clone();
+                self.state = State::Checkmate;
+            }
+            return;
+        }
+        let network = match &mut self.network {
+            Some(network) => network,
+            None => return,
+        };
+        let action = match keycode {
+            event::KeyCode::R => network.peer.resign(&mut self.game),
+            event::KeyCode::D => network.peer.offer_draw(&mut self.game),
+            event::KeyCode::N => network
+                .peer
+                .decline_draw()
+                .map(|_| game::TurnResult::Failed),
+            _ => return,
+        };
+        match action {
+            Ok(game::TurnResult::GameEnd(game::EndResult::Tie)) => self.state = State::Remi,
+            Ok(game::TurnResult::GameEnd(_)) => self.state = State::Checkmate,
+            Ok(_) => (),
+            Err(error) => eprintln!("Could not reach the other player: {}", error),
+        }
+    }
     fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
         graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
         screen::playing::playing(self, ctx);
@@ -240,6 +662,249 @@ impl event::EventHandler for MainState {
     }
 }
 
+// Usage: gui [--computer white|black] [--book <polyglot.bin>] [--book-best]
+//            [--syzygy <path>] [--chess960 [index] | --capablanca | --crazyhouse]
+//            [--bughouse [minutes]] [--king-of-the-hill | --three-check | --racing-kings]
+//            [--atomic | --antichess | --horde | --fog-of-war | --duck]
+//            [--clock <control>], with controls like 5+3, 15d5 or 40/90+30:30+30
+//            [--host <port> [white|black] | --join <address>]
+//            [--correspondence <directory> <player> [<id> | new <opponent> [days]]]
+fn game_from_args() -> game::Game {
+    let args: Vec<String> = env::args().collect();
+    let variants: [(&str, fn() -> game::Game); 8] = [
+        ("--king-of-the-hill", game::Game::king_of_the_hill),
+        ("--three-check", game::Game::three_check),
+        ("--racing-kings", game::Game::racing_kings),
+        ("--atomic", game::Game::atomic),
+        ("--antichess", game::Game::antichess),
+        ("--horde", game::Game::horde),
+        ("--fog-of-war", game::Game::fog_of_war),
+        ("--duck", game::Game::duck_chess),
+    ];
+    for (flag, variant) in variants.iter() {
+        if args.iter().any(|arg| arg == flag) {
+            return variant();
+        }
+    }
+    if args.iter().any(|arg| arg == "--crazyhouse") {
+        return game::Game::crazyhouse();
+    }
+    if args.iter().any(|arg| arg == "--capablanca") {
+        return game::Game::capablanca();
+    }
+    let position = match args.iter().position(|arg| arg == "--chess960") {
+        Some(position) => position,
+        None => return game::Game::new(),
+    };
+
+    // Without an index a start position is picked from the clock.
+    let index = match args.get(position + 1).and_then(|index| index.parse().ok()) {
+        Some(index) => index,
+        None => time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map_or(518, |elapsed| (elapsed.subsec_nanos() % 960) as u16),
+    };
+
+    game::Game::chess960(index).unwrap_or_else(|| {
+        eprintln!("Chess960 positions are numbered 0 to 959, not {}", index);
+        game::Game::new()
+    })
+}
+
+fn bughouse_from_args() -> Option<bughouse::Bughouse> {
+    let args: Vec<String> = env::args().collect();
+    let position = args.iter().position(|arg| arg == "--bughouse")?;
+    let minutes = args
+        .get(position + 1)
+        .and_then(|minutes| minutes.parse().ok())
+        .unwrap_or(5);
+    Some(bughouse::Bughouse::new(time::Duration::from_secs(
+        minutes * 60,
+    )))
+}
+
+fn clock_from_args() -> Option<clock::Clock> {
+    let args: Vec<String> = env::args().collect();
+    let position = args.iter().position(|arg| arg == "--clock")?;
+    let control = args.get(position + 1)?;
+    clock::TimeControl::parse(control).map(clock::Clock::new)
+}
+
+// The host waits for the other player before the window opens. The guest
+// then sets up the position the host sent, keeping its variant and clock.
+fn peer_from_args(game: &mut game::Game) -> Option<net::Peer> {
+    let args: Vec<String> = env::args().collect();
+    let position = |flag: &str| args.iter().position(|arg| arg == flag);
+
+    let peer = if let Some(index) = position("--host") {
+        let port = args.get(index + 1)?;
+        let color = match args.get(index + 2).map(|color| color.as_str()) {
+            Some("black") => chess::Color::Black,
+            _ => chess::Color::White,
+        };
+        println!("Waiting for the other player on port {}", port);
+        TcpListener::bind(("0.0.0.0", port.parse().ok()?))
+            .and_then(|listener| net::Peer::host(&listener, color, game))
+    } else {
+        let peer = net::Peer::join(args.get(position("--join")? + 1)?.as_str());
+        if let Ok(peer) = &peer {
+            let variant = game
+                .get_variant()
+                .and_then(|variant| variant::by_name(variant.name()));
+            match game::Game::from_fen_with_variant(peer.start(), variant) {
+                Some(mut start) => {
+                    start.set_clock(game.get_clock().cloned());
+                    *game = start;
+                }
+                None => {
+                    eprintln!("Could not set up the position {}", peer.start());
+                    return None;
+                }
+            }
+        }
+        peer
+    };
+    match peer {
+        Ok(peer) => Some(peer),
+        Err(error) => {
+            eprintln!("Could not connect: {}", error);
+            None
+        }
+    }
+}
+
+// One line per game awaiting the player, most urgent first.
+fn awaiting(store: &store::Store, player: &str) -> Vec<String> {
+    let now = time::SystemTime::now();
+    let listing = match store.awaiting(player, now) {
+        Ok(listing) => listing,
+        Err(error) => {
+            eprintln!("Could not read the games: {}", error);
+            return vec![];
+        }
+    };
+    // A broken file only hides its own game.
+    for (id, error) in listing.unreadable.iter() {
+        eprintln!("Skipping game {}: {}", id, error);
+    }
+    listing
+        .games
+        .iter()
+        .map(|saved| {
+            let opponent = saved.player(saved.get_game().color.inverse());
+            match saved
+                .deadline()
+                .map(|deadline| deadline.duration_since(now))
+            {
+                Some(Ok(left)) => {
+                    format!(
+                        "{} vs {}, {}h left",
+                        saved.id,
+                        opponent,
+                        left.as_secs() / 3600
+                    )
+                }
+                _ => format!("{} vs {}", saved.id, opponent),
+            }
+        })
+        .collect()
+}
+
+// Opens the named game, starts a new one as White, or picks the most urgent
+// game awaiting the player's move.
+fn correspondence_from_args(game: game::Game) -> Option<Correspondence> {
+    let args: Vec<String> = env::args().collect();
+    let index = args.iter().position(|arg| arg == "--correspondence")?;
+    let (directory, player) = (args.get(index + 1)?, args.get(index + 2)?.clone());
+    let store = match store::Store::open(directory) {
+        Ok(store) => store,
+        Err(error) => {
+            eprintln!("Could not open {}: {}", directory, error);
+            return None;
+        }
+    };
+
+    let now = time::SystemTime::now();
+    let saved = match args.get(index + 3).map(|arg| arg.as_str()) {
+        Some("new") => {
+            let opponent = args.get(index + 4)?;
+            let days = args.get(index + 5).and_then(|days| days.parse().ok());
+            store.create(&player, opponent, game, days, now)
+        }
+        Some(id) if !id.starts_with("--") => store.load(id),
+        _ => match store.awaiting(&player, now) {
+            Ok(listing) => match listing.games.into_iter().next() {
+                Some(saved) => Ok(saved),
+                None => {
+                    println!("No games are waiting for {}.", player);
+                    return None;
+                }
+            },
+            Err(error) => Err(error),
+        },
+    };
+    let mut saved = match saved {
+        Ok(saved) => saved,
+        Err(error) => {
+            eprintln!("Could not open the game: {}", error);
+            return None;
+        }
+    };
+    if saved.check_deadline(now).is_some() {
+        if let Err(error) = store.save(&saved) {
+            eprintln!("Could not save game {}: {}", saved.id, error);
+        }
+    }
+    println!(
+        "Playing game {}: {} against {}",
+        saved.id, saved.white, saved.black
+    );
+
+    let awaiting = awaiting(&store, &player);
+    Some(Correspondence {
+        store,
+        player,
+        saved,
+        awaiting,
+    })
+}
+
+fn computer_from_args() -> Option<(chess::Color, engine::Engine)> {
+    let args: Vec<String> = env::args().collect();
+    let value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    let color = match value("--computer").map(|color| color.as_str()) {
+        Some("white") => chess::Color::White,
+        Some("black") => chess::Color::Black,
+        _ => return None,
+    };
+
+    let mut engine = engine::Engine::new();
+    if let Some(path) = value("--book") {
+        let selection = if args.iter().any(|arg| arg == "--book-best") {
+            book::Selection::Best
+        } else {
+            book::Selection::Weighted
+        };
+        match book::Book::open(path) {
+            Ok(book) => engine.set_book(Some(book), selection),
+            Err(error) => eprintln!("Could not open book {}: {}", path, error),
+        }
+    }
+    if let Some(path) = value("--syzygy") {
+        match tablebase::Tablebase::open(path) {
+            Ok(tablebase) => engine.set_tablebase(Some(tablebase)),
+            Err(error) => eprintln!("Could not open tablebases {}: {}", path, error),
+        }
+    }
+
+    Some((color, engine))
+}
+
 pub fn main() -> ggez::GameResult {
     let resource_dir = path::PathBuf::from("./gui/src/resources");
     let cb = ggez::ContextBuilder::new("super_simple", "ggez")
@@ -247,6 +912,19 @@ pub fn main() -> ggez::GameResult {
         .window_mode(ggez::conf::WindowMode::default().dimensions(WINDOW_SIZE.0, WINDOW_SIZE.1))
         .add_resource_path(resource_dir);
     let (ctx, event_loop) = &mut cb.build()?;
-    let state = &mut MainState::new()?;
+    let mut game = game_from_args();
+    game.set_clock(clock_from_args());
+    let correspondence = correspondence_from_args(game.clone());
+    if correspondence.is_none() && env::args().any(|arg| arg == "--correspondence") {
+        return Ok(());
+    }
+    let peer = peer_from_args(&mut game);
+    let state = &mut MainState::new(
+        game,
+        computer_from_args(),
+        bughouse_from_args(),
+        peer,
+        correspondence,
+    )?;
     event::run(ctx, event_loop, state)
 }
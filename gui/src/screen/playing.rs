@@ -1,6 +1,11 @@
-use super::super::{Color, MainState, Overlay, Piece, Position, State};
+use super::super::{
+    fogged, piece_of, pocket_buttons, promotion_buttons, Color, Grid, MainState, Overlay, Position,
+    Selected, State, SWITCH_BUTTON,
+};
 use super::tools;
 use chess::game;
+use chess::game::bughouse;
+use chess::pieces;
 use ggez;
 use ggez::event;
 use ggez::graphics;
@@ -19,23 +24,60 @@ fn clighttile() -> PColor {
 fn cselect() -> PColor {
     PColor::from_rgba(100, 100, 100, 150)
 }
+fn cfog() -> PColor {
+    PColor::from_rgb(40, 40, 40)
+}
 fn cpossible() -> PColor {
     PColor::from_rgba(0, 200, 0, 150)
 }
 pub fn playing(mainstate: &mut MainState, ctx: &mut ggez::Context) {
     super::tools::background(ctx, cbackground());
-    tools::draw_tile(ctx, &Position(9, 4), cdarktile());
-    tools::draw_tile(ctx, &Position(10, 5), cdarktile());
-    tools::draw_tile(ctx, &Position(9, 5), clighttile());
-    tools::draw_tile(ctx, &Position(10, 4), clighttile());
-    tools::draw_piece(ctx, Position(9, 4), Piece::Queen(mainstate.turn.clone()));
-    tools::draw_piece(ctx, Position(10, 5), Piece::Knight(mainstate.turn.clone()));
-    tools::draw_piece(ctx, Position(9, 5), Piece::Bishop(mainstate.turn.clone()));
-    tools::draw_piece(ctx, Position(10, 4), Piece::Rook(mainstate.turn.clone()));
-    for i in 0..8 {
-        for j in 0..8 {
+    let grid = Grid::of(mainstate.game.get_board());
+    let files = mainstate.game.get_board().width.clone().count();
+    let buttons = promotion_buttons(&mainstate.game);
+    for (position, kind) in buttons.iter() {
+        tools::draw_tile(
+            ctx,
+            &Grid::PANEL,
+            position,
+            if (position.0 + position.1) % 2 == 1 {
+                cdarktile()
+            } else {
+                clighttile()
+            },
+        );
+        let piece = pieces::Piece::new(mainstate.game.color, *kind);
+        tools::draw_piece(ctx, &Grid::PANEL, *position, piece_of(&piece));
+    }
+    for (position, kind, count) in pocket_buttons(&mainstate.game).iter() {
+        tools::draw_tile(
+            ctx,
+            &Grid::PANEL,
+            position,
+            if (position.0 + position.1) % 2 == 1 {
+                cdarktile()
+            } else {
+                clighttile()
+            },
+        );
+        let piece = pieces::Piece::new(mainstate.game.color, *kind);
+        tools::draw_piece(ctx, &Grid::PANEL, *position, piece_of(&piece));
+        if *count > 1 {
+            let (x, y) = Grid::PANEL.origin(position);
+            tools::text(ctx, x + 70.0, y + 50.0, &count.to_string());
+        }
+        match mainstate.selected {
+            Selected::Drop(selected) if selected == *kind => {
+                tools::draw_tile(ctx, &Grid::PANEL, position, cselect())
+            }
+            _ => (),
+        }
+    }
+    for i in 0..files {
+        for j in 0..grid.height {
             tools::draw_tile(
                 ctx,
+                &grid,
                 &Position(i, j),
                 if (i + j) % 2 == 1 {
                     cdarktile()
@@ -45,17 +87,27 @@ pub fn playing(mainstate: &mut MainState, ctx: &mut ggez::Context) {
             );
         }
     }
+    if fogged(&mainstate.game) {
+        let visible = mainstate.game.visible(&mainstate.game.color);
+        for i in 0..files {
+            for j in 0..grid.height {
+                let position = Position(i, j);
+                if !visible.contains(&position.clone().translate()) {
+                    tools::draw_tile(ctx, &grid, &position, cfog());
+                }
+            }
+        }
+    }
     match mainstate.state {
         State::Playing { promotion, check } => {
             if !promotion {
-                tools::draw_tile(ctx, &Position(9, 4), cselect());
-                tools::draw_tile(ctx, &Position(10, 5), cselect());
-                tools::draw_tile(ctx, &Position(9, 5), cselect());
-                tools::draw_tile(ctx, &Position(10, 4), cselect());
+                for (position, _) in buttons.iter() {
+                    tools::draw_tile(ctx, &Grid::PANEL, position, cselect());
+                }
             } else {
-                for i in 0..8 {
-                    for j in 0..8 {
-                        tools::draw_tile(ctx, &Position(i, j), cselect());
+                for i in 0..files {
+                    for j in 0..grid.height {
+                        tools::draw_tile(ctx, &grid, &Position(i, j), cselect());
                     }
                 }
             };
@@ -66,22 +118,58 @@ pub fn playing(mainstate: &mut MainState, ctx: &mut ggez::Context) {
         }
         _ => (),
     };
+    if let Some(bughouse) = &mainstate.bughouse {
+        tools::draw_tile(ctx, &Grid::PANEL, &SWITCH_BUTTON, cselect());
+        let (x, y) = Grid::PANEL.origin(&SWITCH_BUTTON);
+        let label = format!("B{}", bughouse.active + 1);
+        tools::text(ctx, x + 10.0, y + 25.0, &label);
+
+        // The clock of the side to move keeps running until it moves.
+        let board = bughouse.active;
+        for (color, y) in [(chess::Color::White, 200.0), (chess::Color::Black, 250.0)].iter() {
+            let mut left = bughouse.coordinator.time_left(board, *color);
+            if mainstate.game.color == *color {
+                left = left.saturating_sub(bughouse.last_move[board].elapsed());
+            }
+            let seconds = left.as_secs();
+            let clock = format!("{}:{:02}", seconds / 60, seconds % 60);
+            tools::text(ctx, 875.0, *y, &clock);
+        }
+    }
+    if let Some(clock) = mainstate.game.get_clock() {
+        for (color, y) in [(chess::Color::White, 200.0), (chess::Color::Black, 250.0)].iter() {
+            let seconds = clock.remaining(color).as_secs();
+            let clock = format!("{}:{:02}", seconds / 60, seconds % 60);
+            tools::text(ctx, 875.0, *y, &clock);
+        }
+    }
+    if let Some(correspondence) = &mainstate.correspondence {
+        tools::text(ctx, 875.0, 350.0, "Awaiting your move:");
+        for (index, line) in correspondence.awaiting.iter().enumerate() {
+            tools::text(ctx, 875.0, 400.0 + 50.0 * index as f32, line);
+        }
+    }
     match mainstate.turn {
         Color::White => tools::text(ctx, 875.0, 100.0, "White's turn."),
         Color::Black => tools::text(ctx, 875.0, 100.0, "Black's turn."),
         _ => (),
     }
     for i in mainstate.board.0.iter() {
-        tools::draw_piece(ctx, i.1.clone(), i.0.clone());
+        tools::draw_piece(ctx, &grid, i.1.clone(), i.0.clone());
     }
 
     match mainstate.help.clone() {
         Overlay::Moves { selected, to } => {
             {
-                tools::draw_tile(ctx, &selected, cselect());
+                tools::draw_tile(ctx, &grid, &selected, cselect());
             }
             for i in to.iter() {
-                tools::draw_tile(ctx, i, cpossible());
+                tools::draw_tile(ctx, &grid, i, cpossible());
+            }
+        }
+        Overlay::Drops(to) => {
+            for i in to.iter() {
+                tools::draw_tile(ctx, &grid, i, cpossible());
             }
         }
         Overlay::None => (),
@@ -89,10 +177,28 @@ pub fn playing(mainstate: &mut MainState, ctx: &mut ggez::Context) {
     match mainstate.state {
         State::Checkmate => {
             super::tools::background(ctx, cselect());
-            match mainstate.turn {
-                Color::White => tools::text(ctx, 300.0, 100.0, "White Wins"),
-                Color::Black => tools::text(ctx, 300.0, 100.0, "Black Wins"),
-                _ => (),
+            if let Some(bughouse) = &mainstate.bughouse {
+                match bughouse.coordinator.get_result() {
+                    Some(bughouse::TeamResult::Win(bughouse::Team::A)) => {
+                        tools::text(ctx, 300.0, 100.0, "Team A Wins")
+                    }
+                    Some(bughouse::TeamResult::Win(bughouse::Team::B)) => {
+                        tools::text(ctx, 300.0, 100.0, "Team B Wins")
+                    }
+                    Some(bughouse::TeamResult::Tie) => tools::text(ctx, 300.0, 100.0, "Remi"),
+                    None => (),
+                }
+                return;
+            }
+            match mainstate.game.get_result() {
+                Some(game::EndResult::Win(chess::Color::White)) => {
+                    tools::text(ctx, 300.0, 100.0, "White Wins")
+                }
+                Some(game::EndResult::Win(chess::Color::Black)) => {
+                    tools::text(ctx, 300.0, 100.0, "Black Wins")
+                }
+                Some(game::EndResult::Tie) => tools::text(ctx, 300.0, 100.0, "Remi"),
+                None => (),
             }
         }
         _ => (),
@@ -1,11 +1,12 @@
-use super::super::{Color, Piece, Position};
+use super::super::{Color, Grid, Piece, Position};
 use chess::game;
 use ggez;
 use ggez::event;
 use ggez::graphics;
 use ggez::nalgebra as na;
 use std::path;
-pub fn draw_piece(ctx: &mut ggez::Context, position: Position, piece: Piece) -> () {
+pub fn draw_piece(ctx: &mut ggez::Context, grid: &Grid, position: Position, piece: Piece) -> () {
+    let (x, y) = grid.origin(&position);
     let mut path;
     match piece {
         Piece::King(color) => {
@@ -50,6 +51,39 @@ pub fn draw_piece(ctx: &mut ggez::Context, position: Position, piece: Piece) ->
                 "/pieces/Chess_ndt60.png"
             }
         }
+        Piece::Archbishop(color) => {
+            path = if color == Color::White {
+                "/pieces/Chess_alt60.png"
+            } else {
+                "/pieces/Chess_adt60.png"
+            }
+        }
+        Piece::Chancellor(color) => {
+            path = if color == Color::White {
+                "/pieces/Chess_clt60.png"
+            } else {
+                "/pieces/Chess_cdt60.png"
+            }
+        }
+        // Fairy pieces have no images, so they are drawn as their letter.
+        Piece::Custom(symbol, _) => {
+            text(
+                ctx,
+                x + (grid.size - 30.0) / 2.0,
+                y + (grid.size - 50.0) / 2.0,
+                &symbol.to_string(),
+            );
+            return;
+        }
+        Piece::Duck => {
+            text(
+                ctx,
+                x + (grid.size - 30.0) / 2.0,
+                y + (grid.size - 50.0) / 2.0,
+                "D",
+            );
+            return;
+        }
         Piece::None => return,
     }
     let image = graphics::Image::new(ctx, path).unwrap();
@@ -57,8 +91,8 @@ pub fn draw_piece(ctx: &mut ggez::Context, position: Position, piece: Piece) ->
         ctx,
         &image,
         (na::Point2::new(
-            70.0 + 100.0 * position.0 as f32,
-            70.0 + 100.0 * (7 - position.1) as f32,
+            x + (grid.size - 60.0) / 2.0,
+            y + (grid.size - 60.0) / 2.0,
         ),),
     );
 }
@@ -68,15 +102,21 @@ pub fn text(ctx: &mut ggez::Context, x: f32, y: f32, to_draw: &str) {
     let dest_point = na::Point2::new(x, y);
     graphics::draw(ctx, &text, (dest_point,));
 }
-pub fn draw_tile(ctx: &mut ggez::Context, position: &Position, color: graphics::Color) -> () {
+pub fn draw_tile(
+    ctx: &mut ggez::Context,
+    grid: &Grid,
+    position: &Position,
+    color: graphics::Color,
+) -> () {
+    let (x, y) = grid.origin(position);
     let mesh = graphics::Mesh::new_rectangle(
         ctx,
         graphics::DrawMode::fill(),
         graphics::Rect {
-            x: 50.0 + 100.0 * (position.0) as f32,
-            y: 50.0 + 100.0 * (7 - position.1) as f32,
-            w: 100.0,
-            h: 100.0,
+            x,
+            y,
+            w: grid.size,
+            h: grid.size,
         },
         color,
     )
@@ -0,0 +1,285 @@
+use super::json::Json;
+use chess::engine::{Engine, Limits};
+use chess::game::{variant, EndResult, Game, Turn, TurnResult};
+use chess::Color;
+use std::collections::HashMap;
+
+// Searches deeper than this would hold up every other request.
+const MAX_DEPTH: u64 = 6;
+
+pub struct Response {
+    pub status: u16,
+    pub body: Option<Json>,
+}
+
+impl Response {
+    fn ok(body: Json) -> Self {
+        Response {
+            status: 200,
+            body: Some(body),
+        }
+    }
+
+    // Errors carry a code to check against and a message to show:
+    //
+    //     {"error": {"code": "illegal_move", "message": "e5 is not a legal move here"}}
+    pub fn error(status: u16, code: &str, message: &str) -> Self {
+        Response {
+            status,
+            body: Some(Json::object(vec![(
+                "error",
+                Json::object(vec![
+                    ("code", Json::string(code)),
+                    ("message", Json::string(message)),
+                ]),
+            )])),
+        }
+    }
+}
+
+// The games being played through the API, answering requests like
+//
+//     POST   /games               {"fen": ..., "variant": ...}, both optional
+//     GET    /games               the ids of every game
+//     GET    /games/<id>          the game's state
+//     DELETE /games/<id>
+//     POST   /games/<id>/moves    {"move": "Nf3"} in SAN or {"move": "g1f3"}
+//     POST   /games/<id>/undo
+//     POST   /games/<id>/engine   {"depth": 3}, optional
+//
+// Everything that changes a game answers with its new state, which holds the
+// position as FEN, the moves so far and the legal moves, in both notations.
+pub struct Api {
+    games: HashMap<u64, Game>,
+    next_id: u64,
+    engine: Engine,
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Api {
+    pub fn new() -> Self {
+        Api {
+            games: HashMap::new(),
+            next_id: 1,
+            engine: Engine::new(),
+        }
+    }
+
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> Response {
+        let body = match body.trim() {
+            "" => Json::Object(vec![]),
+            body => match Json::parse(body) {
+                Some(body @ Json::Object(_)) => body,
+                _ => return Response::error(400, "bad_request", "the body is not a JSON object"),
+            },
+        };
+        let segments: Vec<&str> = path
+            .trim_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        let (id, action) = match segments.as_slice() {
+            ["games"] => {
+                return match method {
+                    "GET" => self.list(),
+                    "POST" => self.create(&body),
+                    _ => not_allowed(),
+                }
+            }
+            ["games", id] => (*id, None),
+            ["games", id, action] => (*id, Some(*action)),
+            _ => return Response::error(404, "not_found", "no such endpoint"),
+        };
+        let id = match id.parse::<u64>() {
+            Ok(id) if self.games.contains_key(&id) => id,
+            _ => return Response::error(404, "not_found", "no such game"),
+        };
+
+        match (method, action) {
+            ("GET", None) => Response::ok(state(id, &self.games[&id])),
+            ("DELETE", None) => {
+                self.games.remove(&id);
+                Response {
+                    status: 204,
+                    body: None,
+                }
+            }
+            ("POST", Some("moves")) => self.play(id, &body),
+            ("POST", Some("undo")) => {
+                let game = self.games.get_mut(&id).unwrap();
+                match game.undo() {
+                    true => Response::ok(state(id, game)),
+                    false => Response::error(409, "nothing_to_undo", "no moves have been made"),
+                }
+            }
+            ("POST", Some("engine")) => self.engine_move(id, &body),
+            (_, Some("moves")) | (_, Some("undo")) | (_, Some("engine")) | (_, None) => {
+                not_allowed()
+            }
+            _ => Response::error(404, "not_found", "no such endpoint"),
+        }
+    }
+
+    fn list(&self) -> Response {
+        let mut ids: Vec<u64> = self.games.keys().copied().collect();
+        ids.sort_unstable();
+        let ids = ids.into_iter().map(|id| Json::Number(id as f64)).collect();
+        Response::ok(Json::object(vec![("games", Json::Array(ids))]))
+    }
+
+    fn create(&mut self, body: &Json) -> Response {
+        let variant = match body.get("variant") {
+            None | Some(Json::Null) => None,
+            Some(Json::String(name)) if name == "standard" => None,
+            Some(name) => match name.as_str().and_then(variant::by_name) {
+                Some(variant) => Some(variant),
+                None => return Response::error(400, "unknown_variant", "no such variant"),
+            },
+        };
+        let game = match body.get("fen") {
+            None | Some(Json::Null) => match variant {
+                Some(variant) => Game::with_variant(variant),
+                None => Game::new(),
+            },
+            Some(fen) => match fen
+                .as_str()
+                .and_then(|fen| Game::from_fen_with_variant(fen, variant))
+            {
+                Some(game) => game,
+                None => return Response::error(400, "bad_fen", "the FEN could not be read"),
+            },
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let response = state(id, &game);
+        self.games.insert(id, game);
+        Response {
+            status: 201,
+            body: Some(response),
+        }
+    }
+
+    fn play(&mut self, id: u64, body: &Json) -> Response {
+        let notation = match body.get("move").and_then(Json::as_str) {
+            Some(notation) => notation,
+            None => return Response::error(400, "bad_request", "the move is missing"),
+        };
+        let game = self.games.get_mut(&id).unwrap();
+        if game.get_result().is_some() {
+            return Response::error(409, "game_over", "the game is over");
+        }
+
+        // Coordinates are tried first, since SAN never looks like them.
+        let turn = Turn::parse(notation)
+            .filter(|turn| game.legal_turns().contains(turn))
+            .or_else(|| game.parse_san(notation));
+        match turn.map(|turn| game.play(&turn)) {
+            Some(TurnResult::Failed) | None => Response::error(
+                422,
+                "illegal_move",
+                &format!("{} is not a legal move here", notation),
+            ),
+            Some(_) => Response::ok(state(id, game)),
+        }
+    }
+
+    fn engine_move(&mut self, id: u64, body: &Json) -> Response {
+        let depth = match body.get("depth") {
+            None => 3,
+            Some(depth) => match depth.as_u64() {
+                Some(depth) if (1..=MAX_DEPTH).contains(&depth) => depth,
+                _ => {
+                    let message = format!("the depth goes from 1 to {}", MAX_DEPTH);
+                    return Response::error(400, "bad_request", &message);
+                }
+            },
+        };
+        let game = self.games.get_mut(&id).unwrap();
+        if game.get_result().is_some() {
+            return Response::error(409, "game_over", "the game is over");
+        }
+
+        let limits = Limits {
+            depth: Some(depth as u32),
+            ..Limits::default()
+        };
+        match self.engine.search(game, &limits, |_| ()) {
+            Some(turn) => {
+                game.play(&turn);
+                Response::ok(state(id, game))
+            }
+            None => Response::error(409, "game_over", "there is no move to make"),
+        }
+    }
+}
+
+fn not_allowed() -> Response {
+    Response::error(
+        405,
+        "method_not_allowed",
+        "the endpoint does not take that method",
+    )
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+// A move in both notations, as seen from the position before it.
+fn move_json(game: &Game, turn: &Turn) -> Json {
+    Json::object(vec![
+        ("uci", Json::String(turn.to_string())),
+        ("san", game.to_san(turn).map_or(Json::Null, Json::String)),
+    ])
+}
+
+fn state(id: u64, game: &Game) -> Json {
+    // The moves are written out again from the start of the game.
+    let turns = game.get_history();
+    let mut replay = game.clone();
+    replay.set_clock(None);
+    while replay.undo() {}
+    let mut history = vec![];
+    for turn in turns.iter() {
+        history.push(move_json(&replay, turn));
+        replay.play(turn);
+    }
+
+    let legal = game
+        .clone()
+        .legal_turns()
+        .iter()
+        .map(|turn| move_json(game, turn))
+        .collect();
+    let result = match game.get_result() {
+        Some(EndResult::Win(Color::White)) => Json::string("1-0"),
+        Some(EndResult::Win(Color::Black)) => Json::string("0-1"),
+        Some(EndResult::Tie) => Json::string("1/2-1/2"),
+        None => Json::Null,
+    };
+
+    Json::object(vec![
+        ("id", Json::Number(id as f64)),
+        ("fen", Json::String(game.to_fen())),
+        (
+            "variant",
+            game.get_variant()
+                .map_or(Json::Null, |variant| Json::string(variant.name())),
+        ),
+        ("turn", Json::string(color_name(game.color))),
+        ("check", Json::Bool(game.is_checked())),
+        ("result", result),
+        ("moves", Json::Array(history)),
+        ("legal_moves", Json::Array(legal)),
+    ])
+}
@@ -0,0 +1,134 @@
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+mod api;
+mod json;
+
+#[cfg(test)]
+mod tests;
+
+// Bodies larger than this are turned away unread.
+const MAX_BODY: usize = 1 << 20;
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+// Accepts connections until the listener fails, giving each its own thread.
+pub fn serve(listener: TcpListener, api: Arc<Mutex<api::Api>>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let api = api.clone();
+        thread::spawn(move || client(stream, api));
+    }
+}
+
+// Answers a single request and closes the connection.
+fn client(mut stream: TcpStream, api: Arc<Mutex<api::Api>>) {
+    let response = match stream.try_clone().and_then(read_request) {
+        Ok(Some(request)) => {
+            let path = request.path.split('?').next().unwrap_or_default();
+            api.lock()
+                .unwrap()
+                .handle(&request.method, path, &request.body)
+        }
+        Ok(None) => api::Response::error(400, "bad_request", "the request could not be read"),
+        Err(_) => return,
+    };
+    let _ = write_response(&mut stream, &response);
+}
+
+fn read_request(stream: TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), path.to_string())
+        }
+        _ => return Ok(None),
+    };
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = match value.trim().parse() {
+                    Ok(length) if length <= MAX_BODY => length,
+                    _ => return Ok(None),
+                };
+            }
+        }
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(String::from_utf8(body)
+        .ok()
+        .map(|body| Request { method, path, body }))
+}
+
+fn write_response(stream: &mut TcpStream, response: &api::Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        _ => "Unknown",
+    };
+    let body = response
+        .body
+        .as_ref()
+        .map(|body| body.to_string())
+        .unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// Usage: http [--port <port>], listening on localhost port 8080 by default.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let port = args
+        .iter()
+        .position(|arg| arg == "--port")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(8080u16);
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on port {}: {}", port, error);
+            return;
+        }
+    };
+    serve(listener, Arc::new(Mutex::new(api::Api::new())));
+}
@@ -0,0 +1,178 @@
+use super::api::Api;
+use super::json::Json;
+use super::serve;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn start() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener, Arc::new(Mutex::new(Api::new()))));
+    address
+}
+
+// Sends a request and returns the status with the parsed body.
+fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Json) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, Json::parse(body).unwrap_or(Json::Null))
+}
+
+fn text<'a>(json: &'a Json, key: &str) -> &'a str {
+    json.get(key).and_then(Json::as_str).unwrap()
+}
+
+fn list(json: &Json, key: &str) -> Vec<Json> {
+    match json.get(key) {
+        Some(Json::Array(items)) => items.clone(),
+        _ => panic!("{} is not a list", key),
+    }
+}
+
+fn error_code(json: &Json) -> &str {
+    text(json.get("error").unwrap(), "code")
+}
+
+#[test]
+fn test_json() {
+    let input = r#" {"move": "e4", "depth": 3, "list": [true, null, -1.5e1], "text": "a\"\u00e9\ud83d\ude00\n"} "#;
+    let json = Json::parse(input).unwrap();
+    assert_eq!(json.get("depth").and_then(Json::as_u64), Some(3));
+    assert_eq!(
+        json.get("list"),
+        Some(&Json::Array(vec![
+            Json::Bool(true),
+            Json::Null,
+            Json::Number(-15.0)
+        ]))
+    );
+    assert_eq!(text(&json, "text"), "a\"é😀\n");
+    assert_eq!(Json::parse(&json.to_string()), Some(json));
+
+    for bad in [
+        "",
+        "{",
+        "[1,]",
+        "{\"a\" 1}",
+        "tru",
+        "\"\\x\"",
+        "1 2",
+        "[[[[[",
+    ]
+    .iter()
+    {
+        assert_eq!(Json::parse(bad), None);
+    }
+    assert_eq!(Json::parse(&"[".repeat(100)), None);
+}
+
+#[test]
+fn test_playing_a_game() {
+    let address = start();
+    let (status, game) = request(address, "POST", "/games", "");
+    assert_eq!(status, 201);
+    assert_eq!(
+        text(&game, "fen"),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    );
+    assert_eq!(list(&game, "legal_moves").len(), 20);
+    let path = format!("/games/{}", game.get("id").and_then(Json::as_u64).unwrap());
+
+    // Moves may come in SAN or in coordinates.
+    for notation in ["f3", "e7e5", "g4"].iter() {
+        let body = format!("{{\"move\": \"{}\"}}", notation);
+        let (status, _) = request(address, "POST", &format!("{}/moves", path), &body);
+        assert_eq!(status, 200);
+    }
+    let (status, error) = request(
+        address,
+        "POST",
+        &format!("{}/moves", path),
+        r#"{"move": "Qh5"}"#,
+    );
+    assert_eq!(status, 422);
+    assert_eq!(error_code(&error), "illegal_move");
+
+    let (_, game) = request(
+        address,
+        "POST",
+        &format!("{}/moves", path),
+        r#"{"move": "Qh4"}"#,
+    );
+    assert_eq!(text(&game, "result"), "0-1");
+    assert_eq!(game.get("check"), Some(&Json::Bool(true)));
+    assert!(list(&game, "legal_moves").is_empty());
+    let moves = list(&game, "moves");
+    assert_eq!(text(&moves[1], "san"), "e5");
+    assert_eq!(text(&moves[3], "uci"), "d8h4");
+    assert_eq!(text(&moves[3], "san"), "Qh4#");
+
+    let (status, error) = request(address, "POST", &format!("{}/engine", path), "");
+    assert_eq!((status, error_code(&error)), (409, "game_over"));
+
+    let (status, game) = request(address, "POST", &format!("{}/undo", path), "");
+    assert_eq!(status, 200);
+    assert_eq!(game.get("result"), Some(&Json::Null));
+    assert_eq!(text(&game, "turn"), "black");
+
+    let (status, game) = request(
+        address,
+        "POST",
+        &format!("{}/engine", path),
+        r#"{"depth": 2}"#,
+    );
+    assert_eq!(status, 200);
+    assert_eq!(text(&game, "result"), "0-1");
+}
+
+#[test]
+fn test_setups_and_errors() {
+    let address = start();
+    let body = r#"{"fen": "7k/P7/6K1/8/8/8/8/8 w - - 0 1", "variant": "atomic"}"#;
+    let (status, game) = request(address, "POST", "/games", body);
+    assert_eq!(status, 201);
+    assert_eq!(text(&game, "variant"), "atomic");
+    assert!(list(&game, "legal_moves")
+        .iter()
+        .any(|turn| text(turn, "san") == "a8=N"));
+
+    let (status, error) = request(address, "POST", "/games", r#"{"variant": "chaturanga"}"#);
+    assert_eq!((status, error_code(&error)), (400, "unknown_variant"));
+    let (status, error) = request(address, "POST", "/games", r#"{"fen": "8/8 x"}"#);
+    assert_eq!((status, error_code(&error)), (400, "bad_fen"));
+    let (status, error) = request(address, "POST", "/games", "{\"fen\": ");
+    assert_eq!((status, error_code(&error)), (400, "bad_request"));
+
+    let (status, error) = request(address, "GET", "/games/9", "");
+    assert_eq!((status, error_code(&error)), (404, "not_found"));
+    let (status, error) = request(address, "PUT", "/games/1", "");
+    assert_eq!((status, error_code(&error)), (405, "method_not_allowed"));
+    let (status, error) = request(address, "POST", "/games/1/moves", "{}");
+    assert_eq!((status, error_code(&error)), (400, "bad_request"));
+    let (status, error) = request(address, "POST", "/games/1/undo", "");
+    assert_eq!((status, error_code(&error)), (409, "nothing_to_undo"));
+    let (status, error) = request(address, "POST", "/games/1/engine", r#"{"depth": 40}"#);
+    assert_eq!((status, error_code(&error)), (400, "bad_request"));
+
+    let (_, games) = request(address, "GET", "/games?all", "");
+    assert_eq!(list(&games, "games"), [Json::Number(1.0)]);
+    let (status, _) = request(address, "DELETE", "/games/1", "");
+    assert_eq!(status, 204);
+    let (status, _) = request(address, "GET", "/games/1", "");
+    assert_eq!(status, 404);
+}
@@ -7,18 +7,55 @@ mod test_move_piece;
 #[cfg(test)]
 mod test_get_moves;
 
-use crate::pieces::{Kind, Piece};
+#[cfg(test)]
+mod test_render;
+
+pub mod render;
+
+use crate::pieces::{custom, Kind, Piece};
 use crate::*;
 use std::collections::HashMap;
 
+// Loaded boards are checked before use, see UncheckedBoard.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedBoard"))]
 pub struct Board {
-    pub current: [Option<Piece>; 64],
+    pub current: Vec<Option<Piece>>,
     pub graveyard: HashMap<Color, Vec<Piece>>,
     pub height: std::ops::RangeInclusive<i8>,
     pub width: std::ops::RangeInclusive<i8>,
     pub enpassant: Option<[Point; 2]>,
+    pub chess960: bool,
+    pub promotions: Vec<Kind>,
+    pub crazyhouse: bool,
+    pub pockets: HashMap<Color, Vec<Kind>>,
+    // Captures explode, as in atomic chess.
+    pub atomic: bool,
+    // Kings are ordinary pieces that can be captured, so there is no check,
+    // as in antichess and fog of war.
+    pub capturable_kings: bool,
+    // The duck of duck chess, which blocks a square without belonging to
+    // either side.
+    pub duck: Option<Point>,
+    // Whether unmoved pawns may step two squares. Minichess boards are too
+    // short for it.
+    pub double_step: bool,
 }
 
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
 impl Default for Board {
     fn default() -> Self {
         let mut starting_positions: Vec<(Point, Piece)> = vec![
@@ -48,54 +85,279 @@ impl Default for Board {
             starting_positions.push((Point(i, 7), Piece::new(Color::Black, Kind::Pawn)));
         }
 
-        let mut starting_board: [Option<Piece>; 64] = [None; 64];
+        let mut board = Board::new(8, 8);
         for (pos, piece) in starting_positions {
-            starting_board[pos.index()] = Some(piece);
+            board.set(&pos, Some(piece));
         }
 
+        board
+    }
+}
+
+impl Board {
+    pub fn new(width: i8, height: i8) -> Self {
         Board {
-            current: starting_board,
+            current: vec![None; width as usize * height as usize],
             graveyard: vec![(Color::White, vec![]), (Color::Black, vec![])]
                 .into_iter()
                 .collect(),
-            height: (1..=8),
-            width: (1..=8),
+            height: (1..=height),
+            width: (1..=width),
             enpassant: None,
+            chess960: false,
+            promotions: vec![Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight],
+            crazyhouse: false,
+            pockets: vec![(Color::White, vec![]), (Color::Black, vec![])]
+                .into_iter()
+                .collect(),
+            atomic: false,
+            capturable_kings: false,
+            duck: None,
+            double_step: true,
         }
     }
-}
 
-impl Board {
+    pub fn empty() -> Self {
+        Board::new(8, 8)
+    }
+
+    pub fn index(&self, point: &Point) -> usize {
+        point.index(*self.width.end())
+    }
+
+    pub fn set(&mut self, point: &Point, piece: Option<Piece>) {
+        let index = self.index(point);
+        self.current[index] = piece;
+    }
+
+    fn take(&mut self, point: &Point) -> Option<Piece> {
+        let index = self.index(point);
+        self.current[index].take()
+    }
+
+    // Both sides get the same back row, with pawns in front of it.
+    pub fn with_back_row(row: &[Kind], height: i8) -> Self {
+        let mut board = Board::new(row.len() as i8, height);
+        for (x, kind) in (1..).zip(row.iter()) {
+            board.set(&Point(x, 1), Some(Piece::new(Color::White, *kind)));
+            board.set(&Point(x, 2), Some(Piece::new(Color::White, Kind::Pawn)));
+            board.set(
+                &Point(x, height - 1),
+                Some(Piece::new(Color::Black, Kind::Pawn)),
+            );
+            board.set(&Point(x, height), Some(Piece::new(Color::Black, *kind)));
+        }
+        board
+    }
+
+    // Gardner's 5x5 minichess, played without castling.
+    pub fn gardner() -> Self {
+        use Kind::*;
+        let mut board = Board::with_back_row(&[Rook, Knight, Bishop, Queen, King], 5);
+        board.forbid_castling();
+        board.double_step = false;
+        board
+    }
+
+    // Los Alamos 6x6 chess, played without bishops and castling.
+    pub fn los_alamos() -> Self {
+        use Kind::*;
+        let mut board = Board::with_back_row(&[Rook, Knight, Queen, King, Knight, Rook], 6);
+        board.forbid_castling();
+        board.double_step = false;
+        board
+    }
+
+    // Capablanca chess on 10x8, where the king castles three squares to the
+    // c or i file and pawns may also promote to the two compound pieces.
+    pub fn capablanca() -> Self {
+        use Kind::*;
+        let row = [
+            Rook,
+            Knight,
+            custom::ARCHBISHOP,
+            Bishop,
+            Queen,
+            King,
+            Bishop,
+            custom::CHANCELLOR,
+            Knight,
+            Rook,
+        ];
+        let mut board = Board::with_back_row(&row, 8);
+        board
+            .promotions
+            .extend([custom::ARCHBISHOP, custom::CHANCELLOR].iter());
+        board
+    }
+
+    pub fn crazyhouse() -> Self {
+        Board {
+            crazyhouse: true,
+            ..Board::default()
+        }
+    }
+
+    pub fn forbid_castling(&mut self) {
+        for piece in self.current.iter_mut().flatten() {
+            if piece.kind == Kind::King || piece.kind == Kind::Rook {
+                piece.has_moved = true;
+            }
+        }
+    }
+
+    // Start positions are numbered like Scharnagl, so index 518 is the
+    // standard setup.
+    pub fn chess960(index: u16) -> Option<Self> {
+        if index >= 960 {
+            return None;
+        }
+
+        let mut row: [Option<Kind>; 8] = [None; 8];
+        let place = |row: &mut [Option<Kind>; 8], nth: usize, kind: Kind| {
+            let x = (0..8).filter(|x| row[*x].is_none()).nth(nth).unwrap();
+            row[x] = Some(kind);
+        };
+
+        let mut index = index as usize;
+        row[2 * (index % 4) + 1] = Some(Kind::Bishop);
+        index /= 4;
+        row[2 * (index % 4)] = Some(Kind::Bishop);
+        index /= 4;
+        place(&mut row, index % 6, Kind::Queen);
+        let (first, second) = KNIGHTS[index / 6];
+        place(&mut row, second, Kind::Knight);
+        place(&mut row, first, Kind::Knight);
+        for kind in [Kind::Rook, Kind::King, Kind::Rook].iter() {
+            place(&mut row, 0, *kind);
+        }
+
+        let row: Vec<Kind> = row.iter().map(|kind| kind.unwrap()).collect();
+        let mut board = Board::with_back_row(&row, 8);
+        board.chess960 = true;
+
+        Some(board)
+    }
+
     pub fn is_in_bounds(&self, point: &Point) -> bool {
         self.width.contains(&point.0) && self.height.contains(&point.1)
     }
 
-    fn find_king(&self, color: &Color) -> Point {
+    // A side can be without a king, as White in horde or after an explosion
+    // in atomic chess.
+    pub fn find_king(&self, color: &Color) -> Option<Point> {
         for x in self.width.clone() {
             for y in self.height.clone() {
                 let current_point = Point(x, y);
-                if let Some(piece) = self.current[current_point.index()] {
+                if let Some(piece) = self.current[self.index(&current_point)] {
                     if piece.kind == Kind::King && &piece.color == color {
-                        return current_point;
+                        return Some(current_point);
                     }
                 }
             }
         }
-        panic!("Couldn't find king");
+        None
     }
 
     // pub fn at_index(&self, index: usize) -> Option<Piece> {
     //     self.current[index]
     // }
 
+    // Neither a piece nor the duck stands there.
+    pub fn is_empty(&self, point: &Point) -> bool {
+        self.at_point(point).is_none() && self.duck != Some(*point)
+    }
+
     pub fn at_point(&self, point: &Point) -> Option<Piece> {
-        self.current[point.index()]
+        self.current[self.index(point)]
+    }
+
+    pub fn can_castle(&self, color: &Color, corner: i8) -> bool {
+        let direction = if corner == *self.width.start() { -1 } else { 1 };
+        self.castling_rook(color, direction).is_some()
+    }
+
+    // The unmoved rook that the unmoved king castles with towards `direction`,
+    // the outermost one if there are several.
+    pub fn castling_rook(&self, color: &Color, direction: i8) -> Option<Point> {
+        let row = match color {
+            Color::White => *self.height.start(),
+            Color::Black => *self.height.end(),
+        };
+
+        let unmoved = |point: Point, kind: Kind| match self.at_point(&point) {
+            Some(piece) => piece.kind == kind && &piece.color == color && !piece.has_moved,
+            None => false,
+        };
+
+        let king = self
+            .width
+            .clone()
+            .map(|x| Point(x, row))
+            .find(|point| unmoved(*point, Kind::King))?;
+
+        let corner = if direction > 0 {
+            *self.width.end()
+        } else {
+            *self.width.start()
+        };
+        let mut x = corner;
+        while x != king.0 {
+            if unmoved(Point(x, row), Kind::Rook) {
+                return Some(Point(x, row));
+            }
+            x -= direction;
+        }
+
+        None
+    }
+
+    // Where the king and the rook end up, the g and f files when castling
+    // towards the h file and the c and d files towards the a file.
+    pub fn castling_targets(&self, row: i8, direction: i8) -> (Point, Point) {
+        if direction > 0 {
+            (
+                Point(*self.width.end() - 1, row),
+                Point(*self.width.end() - 2, row),
+            )
+        } else {
+            (
+                Point(*self.width.start() + 2, row),
+                Point(*self.width.start() + 3, row),
+            )
+        }
+    }
+
+    pub fn is_capture(&self, source: &Point, target: &Point) -> bool {
+        let piece = match self.at_point(source) {
+            Some(piece) => piece,
+            None => return false,
+        };
+
+        match self.at_point(target) {
+            Some(other) => other.color != piece.color,
+            None => {
+                piece.kind == Kind::Pawn
+                    && self.enpassant.is_some_and(|[passed, _]| &passed == target)
+            }
+        }
     }
 
     pub fn detect_check(&self, color: &Color) -> Option<Vec<Point>> {
-        let king = self.find_king(&color);
+        if self.capturable_kings {
+            return None;
+        }
+
+        let king = self.find_king(color)?;
+        if self.atomic {
+            // Taking a king next to its own would blow up both of them.
+            let opponent = self.find_king(&color.inverse())?;
+            if (king.0 - opponent.0).abs() <= 1 && (king.1 - opponent.1).abs() <= 1 {
+                return None;
+            }
+        }
 
-        let points: Vec<Point> = self.covered_by_opponent(&king, &color);
+        let points: Vec<Point> = self.covered_by_opponent(&king, color);
 
         if !points.is_empty() {
             Some(points)
@@ -113,9 +375,9 @@ impl Board {
             let mut current_point = source.add(&mv.0);
 
             while self.is_in_bounds(&current_point) {
-                if let Some(piece) = self.current[current_point.index()] {
+                if let Some(piece) = self.current[self.index(&current_point)] {
                     if piece.color == opponent
-                        && self.get_moves_for_piece(&current_point).contains(&source)
+                        && self.get_moves_for_piece(&current_point).contains(source)
                     {
                         covering_pieces.push(current_point);
                     }
@@ -129,28 +391,63 @@ impl Board {
             }
         }
 
+        // Fairy pieces can attack from squares the lines above never reach.
+        for x in self.width.clone() {
+            for y in self.height.clone() {
+                let point = Point(x, y);
+                let is_custom = self.at_point(&point).is_some_and(|piece| {
+                    piece.color == opponent && matches!(piece.kind, Kind::Custom(_))
+                });
+                if is_custom
+                    && !covering_pieces.contains(&point)
+                    && self.get_moves_for_piece(&point).contains(source)
+                {
+                    covering_pieces.push(point);
+                }
+            }
+        }
+
         covering_pieces
     }
 
     pub fn move_piece(&mut self, source: Point, target: Point) -> bool {
-        if !self.is_in_bounds(&target) || source == target {
+        if !self.is_in_bounds(&target) || source == target || self.duck == Some(target) {
             return false;
         }
 
-        let source_index = source.index();
-        let target_index = target.index();
+        let source_index = self.index(&source);
+        let target_index = self.index(&target);
 
         let source_piece = match self.current[source_index] {
             Some(piece) => piece,
             None => return false,
         };
+        let explodes = self.atomic && self.is_capture(&source, &target);
 
         if let Some(target_piece) = self.current[target_index] {
             if target_piece.color == source_piece.color {
-                return false;
+                let castles = self.chess960
+                    && source_piece.kind == Kind::King
+                    && target_piece.kind == Kind::Rook
+                    && !source_piece.has_moved
+                    && !target_piece.has_moved
+                    && source.1 == target.1;
+                return castles && self.castle(source, target);
             } else {
-                let graveyard = self.graveyard.entry(target_piece.color).or_default();
-                graveyard.push(target_piece);
+                self.capture(target_piece);
+            }
+        }
+
+        if !self.chess960
+            && source_piece.kind == Kind::King
+            && !source_piece.has_moved
+            && (target.0 - source.0).abs() >= 2
+        {
+            let direction = (target.0 - source.0).signum();
+            if let Some(rook) = self.castling_rook(&source_piece.color, direction) {
+                if self.castling_targets(source.1, direction).0 == target {
+                    return self.castle(source, rook);
+                }
             }
         }
 
@@ -160,31 +457,219 @@ impl Board {
         self.current[target_index] = Some(new_target_piece);
         self.current[source_index] = None;
 
+        if source_piece.kind == Kind::Pawn {
+            if let Some([passed, pawn]) = self.enpassant {
+                if target == passed && source.0 != target.0 {
+                    if let Some(captured) = self.take(&pawn) {
+                        self.capture(captured);
+                    }
+                }
+            }
+        }
+
+        // Pawns stepping off the first rank, as in horde, cannot be taken en
+        // passant.
+        let passed = Point(source.0, (source.1 + target.1) / 2);
+        let second_rank = [self.height.start() + 2, self.height.end() - 2].contains(&passed.1);
+        self.enpassant =
+            if source_piece.kind == Kind::Pawn && (target.1 - source.1).abs() == 2 && second_rank {
+                Some([passed, target])
+            } else {
+                None
+            };
+
+        if explodes {
+            self.explode(&target);
+        }
+
+        true
+    }
+
+    // The capturing piece is destroyed together with every piece but a pawn
+    // around the square.
+    fn explode(&mut self, target: &Point) {
+        if let Some(piece) = self.take(target) {
+            self.capture(piece);
+        }
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let point = target.add(&Point(x, y));
+                if !self.is_in_bounds(&point) {
+                    continue;
+                }
+                match self.at_point(&point) {
+                    Some(piece) if piece.kind != Kind::Pawn => {
+                        self.take(&point);
+                        self.capture(piece);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // The duck has to move to another empty square every turn.
+    pub fn place_duck(&mut self, target: Point) -> bool {
+        if !self.is_in_bounds(&target) || !self.is_empty(&target) {
+            return false;
+        }
+        self.duck = Some(target);
+        true
+    }
+
+    // In crazyhouse the capturing side gets the piece, promoted ones as pawns.
+    fn capture(&mut self, piece: Piece) {
+        self.graveyard.entry(piece.color).or_default().push(piece);
+
+        if self.crazyhouse {
+            let kind = if piece.promoted {
+                Kind::Pawn
+            } else {
+                piece.kind
+            };
+            self.pockets
+                .entry(piece.color.inverse())
+                .or_default()
+                .push(kind);
+        }
+    }
+
+    pub fn drop_piece(&mut self, color: &Color, kind: Kind, target: Point) -> bool {
+        if !self.can_drop(color, kind, &target) {
+            return false;
+        }
+
+        let pocket = self.pockets.entry(*color).or_default();
+        let index = pocket
+            .iter()
+            .position(|pocketed| *pocketed == kind)
+            .unwrap();
+        pocket.remove(index);
+
+        // Pawns dropped on their starting rank may still advance two squares,
+        // but dropped rooks can never castle.
+        let mut piece = Piece::new(*color, kind);
+        piece.has_moved = match color {
+            Color::White => kind != Kind::Pawn || target.1 != self.height.start() + 1,
+            Color::Black => kind != Kind::Pawn || target.1 != self.height.end() - 1,
+        };
+        self.set(&target, Some(piece));
+        self.enpassant = None;
+
+        true
+    }
+
+    fn can_drop(&self, color: &Color, kind: Kind, target: &Point) -> bool {
+        let in_pocket = self
+            .pockets
+            .get(color)
+            .is_some_and(|pocket| pocket.contains(&kind));
+        let pawn_on_back_row = kind == Kind::Pawn
+            && (target.1 == *self.height.start() || target.1 == *self.height.end());
+
+        in_pocket && self.is_in_bounds(target) && self.is_empty(target) && !pawn_on_back_row
+    }
+
+    // Squares a piece from the pocket can be dropped on without leaving the
+    // king in check, which includes drops that block a check.
+    pub fn get_drops(&mut self, color: &Color, kind: Kind) -> Option<Vec<Point>> {
+        let mut drops: Vec<Point> = vec![];
+
+        for x in self.width.clone() {
+            for y in self.height.clone() {
+                let target = Point(x, y);
+                if !self.can_drop(color, kind, &target) {
+                    continue;
+                }
+
+                self.set(&target, Some(Piece::new(*color, kind)));
+                if self.detect_check(color).is_none() {
+                    drops.push(target);
+                }
+                self.set(&target, None);
+            }
+        }
+
+        if drops.is_empty() {
+            None
+        } else {
+            Some(drops)
+        }
+    }
+
+    fn castle(&mut self, king_source: Point, rook_source: Point) -> bool {
+        let direction = if rook_source.0 > king_source.0 { 1 } else { -1 };
+        let (king_target, rook_target) = self.castling_targets(king_source.1, direction);
+
+        let (mut king, mut rook) = match (self.take(&king_source), self.take(&rook_source)) {
+            (Some(king), Some(rook)) => (king, rook),
+            (king, rook) => {
+                self.set(&king_source, king);
+                self.set(&rook_source, rook);
+                return false;
+            }
+        };
+
+        king.has_moved = true;
+        rook.has_moved = true;
+        self.set(&king_target, Some(king));
+        self.set(&rook_target, Some(rook));
+        self.enpassant = None;
+
         true
     }
 
     pub fn get_allowed_moves(&mut self, source: &Point) -> Option<Vec<Point>> {
-        let piece = match &self.current[source.index()] {
+        let piece = match &self.current[self.index(source)] {
             Some(p) => *p,
             None => return None,
         };
 
-        let mut moves: Vec<Point> = self.get_moves_for_piece(&source);
+        let mut moves: Vec<Point> = self.get_moves_for_piece(source);
+        if self.capturable_kings {
+            if piece.kind == Kind::King {
+                moves.append(&mut self.get_castling_moves(source));
+            }
+            return if moves.is_empty() { None } else { Some(moves) };
+        }
+        if self.atomic && piece.kind == Kind::King {
+            moves.retain(|target| !self.is_capture(source, target));
+        }
 
-        let original = self.current;
+        let original = (
+            self.current.clone(),
+            self.enpassant,
+            self.graveyard.clone(),
+            self.pockets.clone(),
+        );
 
         let mut allowed_moves: Vec<Point> = vec![];
 
         for mv in &moves {
             self.move_piece(*source, *mv);
 
-            if self.detect_check(&piece.color).is_none() {
+            // Blowing up the enemy king wins at once, even out of check.
+            let legal = if self.atomic {
+                self.find_king(&piece.color).is_some()
+                    && (self.find_king(&piece.color.inverse()).is_none()
+                        || self.detect_check(&piece.color).is_none())
+            } else {
+                self.detect_check(&piece.color).is_none()
+            };
+            if legal {
                 allowed_moves.push(*mv);
             };
-            self.current = original;
+            self.current = original.0.clone();
+            self.enpassant = original.1;
+            self.graveyard = original.2.clone();
+            self.pockets = original.3.clone();
         }
 
-        moves.retain(|point| allowed_moves.contains(&point));
+        moves.retain(|point| allowed_moves.contains(point));
+
+        if piece.kind == Kind::King {
+            moves.append(&mut self.get_castling_moves(source));
+        }
 
         if moves.is_empty() {
             None
@@ -193,8 +678,158 @@ impl Board {
         }
     }
 
+    // Squares the pieces of a color stand on or could move to, which is all
+    // that color gets to see in fog of war.
+    pub fn visible(&self, color: &Color) -> Vec<Point> {
+        let mut visible: Vec<Point> = vec![];
+        for x in self.width.clone() {
+            for y in self.height.clone() {
+                let source = Point(x, y);
+                let piece = match self.at_point(&source) {
+                    Some(piece) if &piece.color == color => piece,
+                    _ => continue,
+                };
+
+                let mut seen = self.get_moves_for_piece(&source);
+                seen.push(source);
+                // A pawn also sees whatever blocks it.
+                if piece.kind == Kind::Pawn {
+                    let ahead = match color {
+                        Color::White => Point(x, y + 1),
+                        Color::Black => Point(x, y - 1),
+                    };
+                    if self.is_in_bounds(&ahead) {
+                        seen.push(ahead);
+                    }
+                }
+                for point in seen {
+                    if !visible.contains(&point) {
+                        visible.push(point);
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    pub fn has_capture(&mut self, color: &Color) -> bool {
+        for x in self.width.clone() {
+            for y in self.height.clone() {
+                let source = Point(x, y);
+                if !self
+                    .at_point(&source)
+                    .is_some_and(|piece| &piece.color == color)
+                {
+                    continue;
+                }
+                let moves = self.get_allowed_moves(&source).unwrap_or_default();
+                if moves.iter().any(|target| self.is_capture(&source, target)) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // Whether any series of moves could end in mate. A lone king never can,
+    // and a king with one bishop or knight only when the other side has
+    // pieces to hem its own king in. Pieces in hand count as well.
+    pub fn has_mating_material(&self, color: &Color) -> bool {
+        let pieces = |color: &Color| -> Vec<Kind> {
+            let mut kinds: Vec<Kind> = self
+                .current
+                .iter()
+                .flatten()
+                .filter(|piece| &piece.color == color && piece.kind != Kind::King)
+                .map(|piece| piece.kind)
+                .collect();
+            kinds.extend(self.pockets.get(color).into_iter().flatten());
+            kinds
+        };
+
+        match pieces(color).as_slice() {
+            [] => false,
+            [Kind::Bishop] | [Kind::Knight] => !pieces(&color.inverse()).is_empty(),
+            _ => true,
+        }
+    }
+
+    fn get_castling_moves(&mut self, source: &Point) -> Vec<Point> {
+        let king = match self.at_point(source) {
+            Some(piece) if piece.kind == Kind::King && !piece.has_moved => piece,
+            _ => return vec![],
+        };
+
+        if self.detect_check(&king.color).is_some() {
+            return vec![];
+        }
+
+        let mut moves: Vec<Point> = vec![];
+
+        for direction in [1, -1].iter() {
+            let rook = match self.castling_rook(&king.color, *direction) {
+                Some(rook) => rook,
+                None => continue,
+            };
+            let (king_target, rook_target) = self.castling_targets(source.1, *direction);
+            // Outside Chess960 castling is played as a king move of at least
+            // two squares, which only works when the king starts far enough in.
+            if !self.chess960 && (king_target.0 - source.0) * direction < 2 {
+                continue;
+            }
+
+            let files = [source.0, rook.0, king_target.0, rook_target.0];
+            let low = *files.iter().min().unwrap();
+            let high = *files.iter().max().unwrap();
+            let path_is_empty = (low..=high)
+                .all(|x| x == source.0 || x == rook.0 || self.is_empty(&Point(x, source.1)));
+            if !path_is_empty {
+                continue;
+            }
+
+            let step = (king_target.0 - source.0).signum();
+            let path_is_safe = (1..(king_target.0 - source.0).abs())
+                .all(|i| self.is_safe_for_king(source, &Point(source.0 + i * step, source.1)));
+
+            let original = (self.current.clone(), self.enpassant);
+            self.castle(*source, rook);
+            let target_is_safe = self.detect_check(&king.color).is_none();
+            self.current = original.0;
+            self.enpassant = original.1;
+
+            if path_is_safe && target_is_safe {
+                moves.push(if self.chess960 { rook } else { king_target });
+            }
+        }
+
+        moves
+    }
+
+    fn is_safe_for_king(&mut self, source: &Point, target: &Point) -> bool {
+        let original = self.current.clone();
+        let king = self.take(source).unwrap();
+        self.set(target, Some(king));
+
+        let safe = self.detect_check(&king.color).is_none();
+        self.current = original;
+
+        safe
+    }
+
+    fn is_enpassant_target(&self, point: &Point, color: &Color) -> bool {
+        match self.enpassant {
+            Some([passed, pawn]) => {
+                &passed == point
+                    && self
+                        .at_point(&pawn)
+                        .is_some_and(|piece| &piece.color != color)
+            }
+            None => false,
+        }
+    }
+
     fn get_moves_for_pawn(&self, source: &Point) -> Vec<Point> {
-        let piece = self.current[source.index()].unwrap();
+        let piece = self.current[self.index(source)].unwrap();
         if piece.kind != Kind::Pawn {
             panic!("Piece is not of kind pawn");
         };
@@ -206,7 +841,7 @@ impl Board {
 
         let mut moves: Vec<Point> = vec![];
 
-        if self.at_point(&source.add(&direction)).is_none() {
+        if self.is_in_bounds(&source.add(&direction)) && self.is_empty(&source.add(&direction)) {
             moves.push(source.add(&direction));
         };
 
@@ -216,6 +851,8 @@ impl Board {
                 if target.color != piece.color {
                     moves.push(plus);
                 };
+            } else if self.is_empty(&plus) && self.is_enpassant_target(&plus, &piece.color) {
+                moves.push(plus);
             };
         }
 
@@ -225,6 +862,8 @@ impl Board {
                 if target.color != piece.color {
                     moves.push(minus);
                 };
+            } else if self.is_empty(&minus) && self.is_enpassant_target(&minus, &piece.color) {
+                moves.push(minus);
             };
         }
 
@@ -232,10 +871,11 @@ impl Board {
         let two_forward = one_forward.add(&direction);
 
         if !piece.has_moved
+            && self.double_step
             && self.is_in_bounds(&one_forward)
-            && self.at_point(&one_forward).is_none()
+            && self.is_empty(&one_forward)
             && self.is_in_bounds(&two_forward)
-            && self.at_point(&two_forward).is_none()
+            && self.is_empty(&two_forward)
         {
             moves.push(two_forward);
         };
@@ -244,9 +884,9 @@ impl Board {
     }
 
     fn get_moves_for_piece(&self, source: &Point) -> Vec<Point> {
-        let piece = self.current[source.index()].unwrap();
+        let piece = self.current[self.index(source)].unwrap();
         if piece.kind == Kind::Pawn {
-            return self.get_moves_for_pawn(&source);
+            return self.get_moves_for_pawn(source);
         };
 
         let mut moves: Vec<Point> = vec![];
@@ -255,6 +895,9 @@ impl Board {
             let mut current_point = source.add(&mv.0);
 
             while self.is_in_bounds(&current_point) {
+                if self.duck == Some(current_point) {
+                    break;
+                }
                 match self.at_point(&current_point) {
                     Some(target_piece) => {
                         if target_piece.color != piece.color {
@@ -276,3 +919,74 @@ impl Board {
         moves
     }
 }
+
+// A board as read from a save, before its squares have been checked against
+// its size. Everything else indexes the squares without checking again.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedBoard {
+    current: Vec<Option<Piece>>,
+    graveyard: HashMap<Color, Vec<Piece>>,
+    height: std::ops::RangeInclusive<i8>,
+    width: std::ops::RangeInclusive<i8>,
+    enpassant: Option<[Point; 2]>,
+    chess960: bool,
+    promotions: Vec<Kind>,
+    crazyhouse: bool,
+    pockets: HashMap<Color, Vec<Kind>>,
+    atomic: bool,
+    capturable_kings: bool,
+    duck: Option<Point>,
+    double_step: bool,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<UncheckedBoard> for Board {
+    type Error = String;
+
+    fn try_from(unchecked: UncheckedBoard) -> Result<Self, String> {
+        // Squares are numbered from 1, and files are lettered.
+        let (width, height) = (unchecked.width, unchecked.height);
+        if *width.start() != 1 || !(1..=26).contains(width.end()) {
+            return Err(format!("bad board width {:?}", width));
+        }
+        if *height.start() != 1 || *height.end() < 1 {
+            return Err(format!("bad board height {:?}", height));
+        }
+        let squares = *width.end() as usize * *height.end() as usize;
+        if unchecked.current.len() != squares {
+            return Err(format!(
+                "{} squares for a {}x{} board",
+                unchecked.current.len(),
+                width.end(),
+                height.end()
+            ));
+        }
+
+        let mut board = Board {
+            current: unchecked.current,
+            graveyard: unchecked.graveyard,
+            height,
+            width,
+            enpassant: unchecked.enpassant,
+            chess960: unchecked.chess960,
+            promotions: unchecked.promotions,
+            crazyhouse: unchecked.crazyhouse,
+            pockets: unchecked.pockets,
+            atomic: unchecked.atomic,
+            capturable_kings: unchecked.capturable_kings,
+            duck: unchecked.duck,
+            double_step: unchecked.double_step,
+        };
+        let mut marked = board.enpassant.iter().flatten().chain(board.duck.iter());
+        if let Some(point) = marked.find(|point| !board.is_in_bounds(point)) {
+            return Err(format!("square {:?} is off the board", point));
+        }
+        // Both sides always have a graveyard and a pocket, if only empty ones.
+        for color in [Color::White, Color::Black].iter() {
+            board.graveyard.entry(*color).or_default();
+            board.pockets.entry(*color).or_default();
+        }
+        Ok(board)
+    }
+}
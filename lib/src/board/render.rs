@@ -0,0 +1,121 @@
+use super::Board;
+use crate::pieces::{Kind, Piece};
+use crate::{Color, Point};
+use std::fmt;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Style {
+    // FEN letters, upper case for White, with dots for empty squares.
+    Ascii,
+    // Chess figurines. Fairy pieces keep their letters.
+    Unicode,
+}
+
+// Draws a board as a grid with rank and file labels, one line per rank.
+// Highlighted squares are drawn in brackets, as in "[q]".
+#[derive(Clone)]
+pub struct Render<'a> {
+    board: &'a Board,
+    style: Style,
+    highlights: Vec<Point>,
+    perspective: Color,
+}
+
+impl Board {
+    pub fn render(&self) -> Render<'_> {
+        Render {
+            board: self,
+            style: Style::Ascii,
+            highlights: vec![],
+            perspective: Color::White,
+        }
+    }
+}
+
+impl<'a> Render<'a> {
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn highlight(mut self, squares: &[Point]) -> Self {
+        self.highlights.extend_from_slice(squares);
+        self
+    }
+
+    // Draws the board as the given side sees it, with its pieces at the
+    // bottom.
+    pub fn perspective(mut self, color: Color) -> Self {
+        self.perspective = color;
+        self
+    }
+
+    fn square(&self, point: &Point) -> char {
+        match self.board.at_point(point) {
+            Some(piece) => match self.style {
+                Style::Ascii => piece.symbol(),
+                Style::Unicode => figurine(&piece),
+            },
+            None if self.board.duck == Some(*point) => '*',
+            None => match self.style {
+                Style::Ascii => '.',
+                Style::Unicode => '·',
+            },
+        }
+    }
+}
+
+fn figurine(piece: &Piece) -> char {
+    match (piece.color, piece.kind) {
+        (Color::White, Kind::King) => '♔',
+        (Color::White, Kind::Queen) => '♕',
+        (Color::White, Kind::Rook) => '♖',
+        (Color::White, Kind::Bishop) => '♗',
+        (Color::White, Kind::Knight) => '♘',
+        (Color::White, Kind::Pawn) => '♙',
+        (Color::Black, Kind::King) => '♚',
+        (Color::Black, Kind::Queen) => '♛',
+        (Color::Black, Kind::Rook) => '♜',
+        (Color::Black, Kind::Bishop) => '♝',
+        (Color::Black, Kind::Knight) => '♞',
+        (Color::Black, Kind::Pawn) => '♟',
+        (_, Kind::Custom(_)) => piece.symbol(),
+    }
+}
+
+impl<'a> fmt::Display for Render<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut files: Vec<i8> = self.board.width.clone().collect();
+        let mut ranks: Vec<i8> = self.board.height.clone().rev().collect();
+        if self.perspective == Color::Black {
+            files.reverse();
+            ranks.reverse();
+        }
+        let label = self.board.height.end().to_string().len();
+
+        // Lines are trimmed so that a plain square at the end of a rank
+        // leaves no trailing space.
+        for rank in ranks.iter() {
+            let mut line = format!("{:>1$} ", rank, label);
+            for file in files.iter() {
+                let point = Point(*file, *rank);
+                line.push_str(&match self.highlights.contains(&point) {
+                    true => format!("[{}]", self.square(&point)),
+                    false => format!(" {} ", self.square(&point)),
+                });
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        let mut line = " ".repeat(label + 1);
+        for file in files.iter() {
+            line.push_str(&format!(" {} ", (b'a' + (*file - 1) as u8) as char));
+        }
+        writeln!(f, "{}", line.trim_end())
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render().fmt(f)
+    }
+}
@@ -98,6 +98,7 @@ fn test_pawn_normal_movement() {
                 color: Color::White,
                 kind: Kind::Pawn,
                 has_moved: true,
+                promoted: false,
             },
         ),
         (
@@ -106,6 +107,7 @@ fn test_pawn_normal_movement() {
                 color: Color::Black,
                 kind: Kind::Pawn,
                 has_moved: true,
+                promoted: false,
             },
         ),
     ]);
@@ -131,6 +133,7 @@ fn test_pawn_capture() {
                 color: Color::White,
                 kind: Kind::Pawn,
                 has_moved: true,
+                promoted: false,
             },
         ),
         (
@@ -139,6 +142,7 @@ fn test_pawn_capture() {
                 color: Color::Black,
                 kind: Kind::Pawn,
                 has_moved: true,
+                promoted: false,
             },
         ),
         (
@@ -147,6 +151,7 @@ fn test_pawn_capture() {
                 color: Color::Black,
                 kind: Kind::Pawn,
                 has_moved: true,
+                promoted: false,
             },
         ),
     ]);
@@ -16,6 +16,7 @@ fn test_move_to_empty_and_sets_has_moved() {
             color: Color::White,
             kind: Kind::Rook,
             has_moved: true,
+            promoted: false,
         })
     );
 }
@@ -36,6 +37,7 @@ fn test_move_to_occupied_by_same_color() {
             color: Color::White,
             kind: Kind::Rook,
             has_moved: false,
+            promoted: false,
         })
     );
     assert_eq!(
@@ -44,6 +46,7 @@ fn test_move_to_occupied_by_same_color() {
             color: Color::White,
             kind: Kind::Pawn,
             has_moved: false,
+            promoted: false,
         })
     );
 }
@@ -64,6 +67,7 @@ fn test_move_to_occupied_by_other_color() {
             color: Color::White,
             kind: Kind::Rook,
             has_moved: true,
+            promoted: false,
         })
     );
 
@@ -75,6 +79,7 @@ fn test_move_to_occupied_by_other_color() {
             color: Color::Black,
             kind: Kind::Pawn,
             has_moved: false,
+            promoted: false,
         }));
 }
 
@@ -0,0 +1,54 @@
+use super::render::Style;
+use super::*;
+use crate::game::Game;
+
+#[test]
+fn test_ascii() {
+    let expected = "\
+8  r  n  b  q  k  b  n  r
+7  p  p  p  p  p  p  p  p
+6  .  .  .  .  .  .  .  .
+5  .  .  .  .  .  .  .  .
+4  .  .  .  .  .  .  .  .
+3  .  .  .  .  .  .  .  .
+2  P  P  P  P  P  P  P  P
+1  R  N  B  Q  K  B  N  R
+   a  b  c  d  e  f  g  h
+";
+    assert_eq!(Board::default().to_string(), expected);
+    assert_eq!(Game::new().to_string(), expected);
+}
+
+#[test]
+fn test_unicode_from_black_with_highlights() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let targets = game.get_moves(&Point(5, 2)).unwrap();
+    let rendered = game
+        .get_board()
+        .render()
+        .style(Style::Unicode)
+        .perspective(Color::Black)
+        .highlight(&targets)
+        .to_string();
+    let expected = "\
+1  ·  ·  ·  ♔  ·  ·  ·  ·
+2  ·  ·  ·  ♙  ·  ·  ·  ·
+3  ·  ·  · [·] ·  ·  ·  ·
+4  ·  ·  · [·] ·  ·  ·  ·
+5  ·  ·  ·  ·  ·  ·  ·  ·
+6  ·  ·  ·  ·  ·  ·  ·  ·
+7  ·  ·  ·  ·  ·  ·  ·  ·
+8  ·  ·  ·  ♚  ·  ·  ·  ·
+   h  g  f  e  d  c  b  a
+";
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn test_other_sizes() {
+    let board = Board::new(3, 10);
+    let lines: Vec<String> = board.to_string().lines().map(String::from).collect();
+    assert_eq!(lines[0], "10  .  .  .");
+    assert_eq!(lines[9], " 1  .  .  .");
+    assert_eq!(lines[10], "    a  b  c");
+}
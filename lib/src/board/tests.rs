@@ -1,9 +1,9 @@
 use super::*;
 
 pub fn create_test_board(positions: Vec<(Point, Piece)>) -> Board {
-    let mut current: [Option<Piece>; 64] = [None; 64];
+    let mut current: Vec<Option<Piece>> = vec![None; 64];
     for (point, piece) in positions {
-        current[point.index()] = Some(piece);
+        current[point.index(8)] = Some(piece);
     }
     Board {
         current,
@@ -13,6 +13,16 @@ pub fn create_test_board(positions: Vec<(Point, Piece)>) -> Board {
         height: (1..=8),
         width: (1..=8),
         enpassant: None,
+        chess960: false,
+        promotions: vec![Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight],
+        crazyhouse: false,
+        pockets: vec![(Color::White, vec![]), (Color::Black, vec![])]
+            .into_iter()
+            .collect(),
+        atomic: false,
+        capturable_kings: false,
+        duck: None,
+        double_step: true,
     }
 }
 
@@ -44,7 +54,11 @@ fn test_find_king() {
         (Point(8, 3), Piece::new(Color::Black, Kind::King)),
     ]);
 
-    assert_eq!(board.find_king(&Color::White), Point(3, 8));
+    assert_eq!(board.find_king(&Color::White), Some(Point(3, 8)));
+
+    assert_eq!(board.find_king(&Color::Black), Some(Point(8, 3)));
 
-    assert_eq!(board.find_king(&Color::Black), Point(8, 3));
+    let board = create_test_board(vec![(Point(8, 3), Piece::new(Color::Black, Kind::King))]);
+    assert_eq!(board.find_king(&Color::White), None);
+    assert_eq!(board.detect_check(&Color::White), None);
 }
@@ -0,0 +1,212 @@
+use crate::game::{Game, Turn};
+use crate::pieces::Kind;
+use crate::*;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::Path;
+
+mod keys;
+
+#[cfg(test)]
+mod tests;
+
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Selection {
+    Best,
+    Weighted,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BookMove {
+    pub turn: Turn,
+    pub weight: u16,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Entry {
+    key: u64,
+    raw: u16,
+    weight: u16,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Book {
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Book> {
+        Ok(Book::from_bytes(&fs::read(path)?))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Book {
+        let mut entries: Vec<Entry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| Entry {
+                key: u64::from_be_bytes([
+                    chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
+                ]),
+                raw: u16::from_be_bytes([chunk[8], chunk[9]]),
+                weight: u16::from_be_bytes([chunk[10], chunk[11]]),
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.key);
+
+        Book { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn moves(&self, game: &Game) -> Vec<BookMove> {
+        let board = game.get_board();
+        let custom = board
+            .current
+            .iter()
+            .flatten()
+            .any(|piece| matches!(piece.kind, Kind::Custom(_)));
+        if board.width != (1..=8)
+            || board.height != (1..=8)
+            || custom
+            || board.crazyhouse
+            || game.get_variant().is_some()
+        {
+            return vec![];
+        }
+
+        let key = key(game);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let legal = game.clone().legal_turns();
+
+        let mut moves: Vec<BookMove> = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .map(|entry| BookMove {
+                turn: decode(game, entry.raw),
+                weight: entry.weight,
+            })
+            .filter(|book_move| legal.contains(&book_move.turn))
+            .collect();
+        moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.weight));
+
+        moves
+    }
+
+    pub fn choose(&self, game: &Game, selection: Selection) -> Option<Turn> {
+        match selection {
+            Selection::Best => self.moves(game).first().map(|book_move| book_move.turn),
+            Selection::Weighted => self.pick(game, RandomState::new().build_hasher().finish()),
+        }
+    }
+
+    pub fn pick(&self, game: &Game, random: u64) -> Option<Turn> {
+        let moves = self.moves(game);
+        let total: u64 = moves.iter().map(|book_move| book_move.weight as u64).sum();
+        if total == 0 {
+            return moves.first().map(|book_move| book_move.turn);
+        }
+
+        let mut remaining = random % total;
+        for book_move in moves {
+            if remaining < book_move.weight as u64 {
+                return Some(book_move.turn);
+            }
+            remaining -= book_move.weight as u64;
+        }
+        None
+    }
+}
+
+pub fn key(game: &Game) -> u64 {
+    let board = game.get_board();
+    let mut key = 0;
+
+    for x in 1..=8 {
+        for y in 1..=8 {
+            if let Some(piece) = board.at_point(&Point(x, y)) {
+                let role = match piece.kind {
+                    Kind::Pawn => 0,
+                    Kind::Knight => 1,
+                    Kind::Bishop => 2,
+                    Kind::Rook => 3,
+                    Kind::Queen => 4,
+                    Kind::King => 5,
+                    Kind::Custom(_) => continue,
+                };
+                let kind = role * 2 + if piece.color == Color::White { 1 } else { 0 };
+                key ^= keys::RANDOM[64 * kind + 8 * (y - 1) as usize + (x - 1) as usize];
+            }
+        }
+    }
+
+    let castling = [
+        (Color::White, *board.width.end()),
+        (Color::White, *board.width.start()),
+        (Color::Black, *board.width.end()),
+        (Color::Black, *board.width.start()),
+    ];
+    for (offset, (color, corner)) in castling.iter().enumerate() {
+        if board.can_castle(color, *corner) {
+            key ^= keys::RANDOM[768 + offset];
+        }
+    }
+
+    if let Some([passed, pawn]) = board.enpassant {
+        let capturable = [-1, 1].iter().any(|dx| {
+            let neighbour = pawn.add(&Point(*dx, 0));
+            board.is_in_bounds(&neighbour)
+                && board
+                    .at_point(&neighbour)
+                    .is_some_and(|piece| piece.kind == Kind::Pawn && piece.color == game.color)
+        });
+        if capturable {
+            key ^= keys::RANDOM[772 + (passed.0 - 1) as usize];
+        }
+    }
+
+    if game.color == Color::White {
+        key ^= keys::RANDOM[780];
+    }
+
+    key
+}
+
+fn decode(game: &Game, raw: u16) -> Turn {
+    let square = |bits: u16| Point((bits & 7) as i8 + 1, ((bits >> 3) & 7) as i8 + 1);
+    let source = square(raw >> 6);
+    let mut target = square(raw);
+
+    let board = game.get_board();
+    if let (Some(king), Some(rook)) = (board.at_point(&source), board.at_point(&target)) {
+        if !board.chess960
+            && king.kind == Kind::King
+            && rook.kind == Kind::Rook
+            && king.color == rook.color
+        {
+            let direction = if target.0 > source.0 { 1 } else { -1 };
+            target = Point(source.0 + 2 * direction, source.1);
+        }
+    }
+
+    let promotion = match (raw >> 12) & 7 {
+        1 => Some(Kind::Knight),
+        2 => Some(Kind::Bishop),
+        3 => Some(Kind::Rook),
+        4 => Some(Kind::Queen),
+        _ => None,
+    };
+
+    Turn {
+        promotion,
+        ..Turn::new(source, target)
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+use std::env;
+
+fn encode(source: Point, target: Point, promotion: u16) -> u16 {
+    let square = |point: Point| (point.0 - 1) as u16 + 8 * (point.1 - 1) as u16;
+    square(target) | square(source) << 6 | promotion << 12
+}
+
+fn entry(game: &Game, source: Point, target: Point, weight: u16) -> Vec<u8> {
+    let mut bytes = key(game).to_be_bytes().to_vec();
+    bytes.extend_from_slice(&encode(source, target, 0).to_be_bytes());
+    bytes.extend_from_slice(&weight.to_be_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes
+}
+
+fn test_book() -> Book {
+    let start = Game::new();
+    let castling = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    let mut bytes = vec![];
+    bytes.append(&mut entry(&castling, Point(5, 1), Point(8, 1), 3));
+    bytes.append(&mut entry(&start, Point(4, 2), Point(4, 4), 5));
+    bytes.append(&mut entry(&start, Point(5, 2), Point(5, 5), 100));
+    bytes.append(&mut entry(&start, Point(5, 2), Point(5, 4), 10));
+    bytes.append(&mut entry(&castling, Point(5, 1), Point(1, 1), 1));
+    Book::from_bytes(&bytes)
+}
+
+#[test]
+fn test_polyglot_keys() {
+    let reference = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            0x463b_9618_1691_fc9c,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            0x823c_9b50_fd11_4196,
+        ),
+        (
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+            0x0756_b944_61c5_0fb0,
+        ),
+        (
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+            0x662f_afb9_65db_29d4,
+        ),
+        (
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            0x22a4_8b5a_8e47_ff78,
+        ),
+        (
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+            0x652a_607c_a3f2_42c1,
+        ),
+        (
+            "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+            0x00fd_d303_c946_bdd9,
+        ),
+        (
+            "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+            0x3c81_23ea_7b06_7637,
+        ),
+        (
+            "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+            0x5c3f_9b82_9b27_9560,
+        ),
+    ];
+
+    for (fen, expected) in reference.iter() {
+        assert_eq!(key(&Game::from_fen(fen).unwrap()), *expected, "{}", fen);
+    }
+}
+
+#[test]
+fn test_key_follows_played_moves() {
+    let mut game = Game::new();
+    for notation in ["e2e4", "d7d5", "e4e5", "f7f5"].iter() {
+        game.play(&Turn::parse(notation).unwrap());
+    }
+
+    assert_eq!(key(&game), 0x22a4_8b5a_8e47_ff78);
+}
+
+#[test]
+fn test_book_moves_are_weighted_and_legal() {
+    let book = test_book();
+
+    assert_eq!(book.len(), 5);
+    assert_eq!(
+        book.moves(&Game::new()),
+        vec![
+            BookMove {
+                turn: Turn::parse("e2e4").unwrap(),
+                weight: 10,
+            },
+            BookMove {
+                turn: Turn::parse("d2d4").unwrap(),
+                weight: 5,
+            },
+        ]
+    );
+    assert!(book
+        .moves(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap())
+        .is_empty());
+}
+
+#[test]
+fn test_castling_is_decoded_as_king_move() {
+    let book = test_book();
+    let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    assert_eq!(
+        book.moves(&game)
+            .iter()
+            .map(|book_move| book_move.turn)
+            .collect::<Vec<Turn>>(),
+        vec![Turn::parse("e1g1").unwrap(), Turn::parse("e1c1").unwrap()]
+    );
+}
+
+#[test]
+fn test_selection() {
+    let book = test_book();
+    let game = Game::new();
+
+    assert_eq!(book.choose(&game, Selection::Best), Turn::parse("e2e4"));
+    assert_eq!(book.pick(&game, 0), Turn::parse("e2e4"));
+    assert_eq!(book.pick(&game, 9), Turn::parse("e2e4"));
+    assert_eq!(book.pick(&game, 10), Turn::parse("d2d4"));
+    assert_eq!(book.pick(&game, 29), Turn::parse("d2d4"));
+
+    let weighted = book.choose(&game, Selection::Weighted).unwrap();
+    assert!(weighted == Turn::parse("e2e4").unwrap() || weighted == Turn::parse("d2d4").unwrap());
+}
+
+#[test]
+fn test_open_book_file() {
+    let mut bytes = vec![];
+    bytes.append(&mut entry(&Game::new(), Point(7, 1), Point(6, 3), 1));
+    let path = env::temp_dir().join(format!("mvid-chess-book-{}.bin", std::process::id()));
+    fs::write(&path, bytes).unwrap();
+
+    let book = Book::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        book.choose(&Game::new(), Selection::Best),
+        Turn::parse("g1f3")
+    );
+    assert!(Book::open(&path).is_err());
+}
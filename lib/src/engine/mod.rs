@@ -0,0 +1,655 @@
+use crate::book::{Book, Selection};
+use crate::game::{EndResult, Game, Turn, TurnResult};
+use crate::pieces::Kind;
+use crate::tablebase::{Tablebase, Wdl};
+use crate::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+const MATE: i32 = 100_000;
+const TB_WIN: i32 = 20_000;
+const INFINITY: i32 = 1_000_000;
+const MAX_DEPTH: u32 = 64;
+const QUIESCENCE_DEPTH: u32 = 4;
+const ENTRY_SIZE: usize = 48;
+const FALLBACK_BUDGET: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Limits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub white_time: Option<Duration>,
+    pub black_time: Option<Duration>,
+    pub white_increment: Option<Duration>,
+    pub black_increment: Option<Duration>,
+    pub moves_to_go: Option<u32>,
+}
+
+impl Limits {
+    fn deadline(&self, color: &Color) -> Option<Duration> {
+        if let Some(movetime) = self.movetime {
+            return Some(movetime);
+        }
+
+        let (time, increment) = match color {
+            Color::White => (self.white_time, self.white_increment),
+            Color::Black => (self.black_time, self.black_increment),
+        };
+        // A search on the clock has to end even when only the other side's
+        // time was given.
+        let time = match time {
+            Some(time) => time,
+            None if self.white_time.is_some() || self.black_time.is_some() => {
+                return Some(FALLBACK_BUDGET)
+            }
+            None => return None,
+        };
+        let increment = increment.unwrap_or_default();
+        let safety = Duration::from_millis(50);
+
+        let budget = time / self.moves_to_go.unwrap_or(30).max(1) + increment * 3 / 4;
+        Some(budget.min(time.checked_sub(safety).unwrap_or_default()))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl Score {
+    fn from_value(value: i32) -> Self {
+        if value > MATE - MAX_DEPTH as i32 * 2 {
+            Score::Mate((MATE - value + 1) / 2)
+        } else if value < -MATE + MAX_DEPTH as i32 * 2 {
+            Score::Mate(-(MATE + value) / 2)
+        } else {
+            Score::Centipawns(value)
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Info {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub time: Duration,
+    pub pv: Vec<Turn>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    depth: u32,
+    value: i32,
+    bound: Bound,
+    best: Option<Turn>,
+}
+
+struct Shared {
+    table: Mutex<HashMap<u64, Entry>>,
+    capacity: usize,
+    stop: AtomicBool,
+    nodes: AtomicU64,
+}
+
+#[derive(Clone)]
+pub struct Engine {
+    shared: Arc<Shared>,
+    threads: usize,
+    book: Option<(Arc<Book>, Selection)>,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        let mut engine = Engine {
+            shared: Arc::new(Shared {
+                table: Mutex::new(HashMap::new()),
+                capacity: 0,
+                stop: AtomicBool::new(false),
+                nodes: AtomicU64::new(0),
+            }),
+            threads: 1,
+            book: None,
+            tablebase: None,
+        };
+        engine.set_hash_size(16);
+        engine
+    }
+
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.shared = Arc::new(Shared {
+            table: Mutex::new(HashMap::new()),
+            capacity: megabytes.max(1) * 1024 * 1024 / ENTRY_SIZE,
+            stop: AtomicBool::new(false),
+            nodes: AtomicU64::new(0),
+        });
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn set_book(&mut self, book: Option<Book>, selection: Selection) {
+        self.book = book.map(|book| (Arc::new(book), selection));
+    }
+
+    pub fn set_tablebase(&mut self, tablebase: Option<Tablebase>) {
+        self.tablebase = tablebase.map(Arc::new);
+    }
+
+    pub fn clear(&self) {
+        self.shared.table.lock().unwrap().clear();
+    }
+
+    pub fn stop(&self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+    }
+
+    // Takes back a stop that came after the last search had already ended.
+    // Searches on another thread are started after this rather than clearing
+    // the flag themselves, so that a stop sent straight after still counts.
+    pub fn reset_stop(&self) {
+        self.shared.stop.store(false, Ordering::SeqCst);
+    }
+
+    pub fn search<F>(&self, game: &Game, limits: &Limits, mut report: F) -> Option<Turn>
+    where
+        F: FnMut(&Info),
+    {
+        if let Some((book, selection)) = &self.book {
+            if let Some(turn) = book.choose(game, *selection) {
+                return Some(turn);
+            }
+        }
+
+        let start = Instant::now();
+        if let Some(tablebase) = &self.tablebase {
+            if let Some((turn, value)) = root_probe(tablebase, game) {
+                report(&Info {
+                    depth: 1,
+                    score: Score::from_value(value),
+                    nodes: 0,
+                    time: start.elapsed(),
+                    pv: vec![turn],
+                });
+                return Some(turn);
+            }
+        }
+
+        let deadline = limits.deadline(&game.color);
+        self.shared.nodes.store(0, Ordering::SeqCst);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let helpers: Vec<_> = (1..self.threads)
+            .map(|index| {
+                let mut searcher = Searcher::new(
+                    self.shared.clone(),
+                    self.tablebase.clone(),
+                    limits,
+                    deadline,
+                    start,
+                );
+                searcher.done = done.clone();
+                let mut game = game.clone();
+                thread::spawn(move || {
+                    let mut depth = 1 + index as u32 % 2;
+                    while depth <= MAX_DEPTH && !searcher.should_stop() {
+                        let mut pv = vec![];
+                        searcher.negamax(&mut game, depth, 0, -INFINITY, INFINITY, &mut pv);
+                        depth += 1;
+                    }
+                })
+            })
+            .collect();
+
+        let mut searcher = Searcher::new(
+            self.shared.clone(),
+            self.tablebase.clone(),
+            limits,
+            deadline,
+            start,
+        );
+        let mut best: Option<Turn> = None;
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+        for depth in 1..=max_depth {
+            let mut root = game.clone();
+            let mut pv = vec![];
+            searcher.must_finish = depth == 1;
+            let value = searcher.negamax(&mut root, depth, 0, -INFINITY, INFINITY, &mut pv);
+
+            if searcher.stopped {
+                break;
+            }
+
+            if let Some(turn) = pv.first() {
+                best = Some(*turn);
+            }
+
+            report(&Info {
+                depth,
+                score: Score::from_value(value),
+                nodes: self.shared.nodes.load(Ordering::SeqCst),
+                time: start.elapsed(),
+                pv,
+            });
+
+            if best.is_none() || value.abs() > MATE - depth as i32 {
+                break;
+            }
+            if let Some(deadline) = deadline {
+                if start.elapsed() > deadline / 2 {
+                    break;
+                }
+            }
+            if searcher.should_stop() {
+                break;
+            }
+        }
+
+        done.store(true, Ordering::SeqCst);
+        for helper in helpers {
+            helper.join().unwrap();
+        }
+        self.shared.stop.store(false, Ordering::SeqCst);
+
+        best
+    }
+}
+
+struct Searcher {
+    shared: Arc<Shared>,
+    tablebase: Option<Arc<Tablebase>>,
+    done: Arc<AtomicBool>,
+    nodes: Option<u64>,
+    deadline: Option<Duration>,
+    start: Instant,
+    stopped: bool,
+    must_finish: bool,
+}
+
+impl Searcher {
+    fn new(
+        shared: Arc<Shared>,
+        tablebase: Option<Arc<Tablebase>>,
+        limits: &Limits,
+        deadline: Option<Duration>,
+        start: Instant,
+    ) -> Self {
+        Searcher {
+            shared,
+            tablebase,
+            done: Arc::new(AtomicBool::new(false)),
+            nodes: limits.nodes,
+            deadline,
+            start,
+            stopped: false,
+            must_finish: false,
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.shared.stop.load(Ordering::Relaxed)
+            || self.done.load(Ordering::Relaxed)
+            || self
+                .nodes
+                .is_some_and(|nodes| self.shared.nodes.load(Ordering::Relaxed) >= nodes)
+            || self
+                .deadline
+                .is_some_and(|deadline| self.start.elapsed() >= deadline)
+    }
+
+    fn visit(&mut self) -> bool {
+        let nodes = self.shared.nodes.fetch_add(1, Ordering::Relaxed);
+        if !self.stopped && !self.must_finish && nodes.is_multiple_of(64) && self.should_stop() {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    // Only probed right after captures and pawn moves, since the tables do not
+    // know how many moves were played since the last one.
+    fn probe(&self, game: &Game, ply: u32) -> Option<i32> {
+        let tablebase = self.tablebase.as_ref()?;
+        if game.get_halfmove_clock() != 0 {
+            return None;
+        }
+
+        Some(match game.probe_wdl(tablebase)? {
+            Wdl::Win | Wdl::CursedWin => TB_WIN - ply as i32,
+            Wdl::Draw => 0,
+            Wdl::BlessedLoss | Wdl::Loss => -TB_WIN + ply as i32,
+        })
+    }
+
+    fn negamax(
+        &mut self,
+        game: &mut Game,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Turn>,
+    ) -> i32 {
+        if self.visit() && ply > 0 {
+            return 0;
+        }
+
+        // Some variants are won by the side left without a move, so the
+        // winner is not always the side that just moved.
+        match game.get_result() {
+            Some(EndResult::Win(winner)) if winner == game.color => return MATE - ply as i32,
+            Some(EndResult::Win(_)) => return -MATE + ply as i32,
+            Some(EndResult::Tie) => return 0,
+            None => {}
+        }
+
+        if ply > 0 {
+            if let Some(value) = self.probe(game, ply) {
+                return value;
+            }
+        }
+
+        if depth == 0 {
+            return self.quiesce(game, QUIESCENCE_DEPTH, alpha, beta);
+        }
+
+        let key = game.hash_key();
+        let entry = self.shared.table.lock().unwrap().get(&key).copied();
+        if let Some(entry) = entry {
+            let value = from_table(entry.value, ply);
+            if ply > 0 && entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return value,
+                    Bound::Lower if value >= beta => return value,
+                    Bound::Upper if value <= alpha => return value,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut turns = game.legal_turns();
+        if turns.is_empty() {
+            return if game.is_checked() {
+                -MATE + ply as i32
+            } else {
+                0
+            };
+        }
+        order(game, &mut turns, entry.and_then(|entry| entry.best));
+
+        let original_alpha = alpha;
+        let mut best_value = -INFINITY;
+        let mut best_turn = None;
+
+        for turn in turns {
+            if game.play(&turn) == TurnResult::Failed {
+                continue;
+            }
+
+            let mut line = vec![];
+            let value = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            game.undo();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if value > best_value {
+                best_value = value;
+                best_turn = Some(turn);
+            }
+
+            if value > alpha {
+                alpha = value;
+                pv.clear();
+                pv.push(turn);
+                pv.append(&mut line);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_value <= original_alpha {
+            Bound::Upper
+        } else if best_value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let mut table = self.shared.table.lock().unwrap();
+        if table.len() >= self.shared.capacity {
+            table.clear();
+        }
+        table.insert(
+            key,
+            Entry {
+                depth,
+                value: to_table(best_value, ply),
+                bound,
+                best: best_turn,
+            },
+        );
+
+        best_value
+    }
+
+    fn quiesce(&mut self, game: &mut Game, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        if self.visit() {
+            return 0;
+        }
+
+        let stand_pat = evaluate(game);
+        if depth == 0 || stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut captures: Vec<Turn> = game
+            .legal_turns()
+            .into_iter()
+            .filter(|turn| is_capture(game, turn) || turn.promotion == Some(Kind::Queen))
+            .collect();
+        order(game, &mut captures, None);
+
+        for turn in captures {
+            if game.play(&turn) == TurnResult::Failed {
+                continue;
+            }
+
+            let value = match game.get_result() {
+                Some(EndResult::Win(winner)) if winner == game.color => -MATE,
+                Some(EndResult::Win(_)) => MATE,
+                Some(EndResult::Tie) => 0,
+                None => -self.quiesce(game, depth - 1, -beta, -alpha),
+            };
+            game.undo();
+
+            if value >= beta {
+                return value;
+            }
+            alpha = alpha.max(value);
+        }
+
+        alpha
+    }
+}
+
+// Picks the move that keeps the best tablebase result, winning as fast and
+// losing as slowly as the distance to zeroing allows.
+fn root_probe(tablebase: &Tablebase, game: &Game) -> Option<(Turn, i32)> {
+    let wdl = game.probe_wdl(tablebase)?;
+    let dtz = game.probe_dtz(tablebase)?;
+
+    let mut game = game.clone();
+    let mut best: Option<(Turn, (i32, i32))> = None;
+
+    for turn in game.legal_turns() {
+        if game.play(&turn) == TurnResult::Failed {
+            continue;
+        }
+        let rank = match game.get_result() {
+            Some(EndResult::Win(winner)) if winner == game.color => Some((0, 0)),
+            Some(EndResult::Win(_)) => Some((5, 0)),
+            Some(EndResult::Tie) => Some((2, 0)),
+            None => match (game.probe_wdl(tablebase), game.probe_dtz(tablebase)) {
+                (Some(wdl), Some(dtz)) => {
+                    let ours = 4 - wdl as i32;
+                    Some((ours, if ours > 2 { -dtz.abs() } else { dtz.abs() }))
+                }
+                _ => None,
+            },
+        };
+        game.undo();
+
+        let rank = rank?;
+        if best.is_none_or(|(_, best)| rank > best) {
+            best = Some((turn, rank));
+        }
+    }
+
+    let value = match wdl {
+        Wdl::Win | Wdl::CursedWin => TB_WIN - dtz.abs(),
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss | Wdl::Loss => -TB_WIN + dtz.abs(),
+    };
+    best.map(|(turn, _)| (turn, value))
+}
+
+fn to_table(value: i32, ply: u32) -> i32 {
+    if value > MATE - MAX_DEPTH as i32 * 2 {
+        value + ply as i32
+    } else if value < -MATE + MAX_DEPTH as i32 * 2 {
+        value - ply as i32
+    } else {
+        value
+    }
+}
+
+fn from_table(value: i32, ply: u32) -> i32 {
+    if value > MATE - MAX_DEPTH as i32 * 2 {
+        value - ply as i32
+    } else if value < -MATE + MAX_DEPTH as i32 * 2 {
+        value + ply as i32
+    } else {
+        value
+    }
+}
+
+fn value_of(kind: &Kind) -> i32 {
+    match kind {
+        Kind::Pawn => 100,
+        Kind::Knight => 320,
+        Kind::Bishop => 330,
+        Kind::Rook => 500,
+        Kind::Queen => 900,
+        Kind::King => 0,
+        // A rough guess from how many directions the piece can go.
+        Kind::Custom(_) => pieces::custom::definition(kind).map_or(0, |piece| {
+            piece
+                .moves
+                .iter()
+                .map(|mv| if mv.1 { 110 } else { 40 })
+                .sum()
+        }),
+    }
+}
+
+fn is_capture(game: &Game, turn: &Turn) -> bool {
+    game.get_board().is_capture(&turn.source, &turn.target)
+}
+
+fn order(game: &Game, turns: &mut [Turn], first: Option<Turn>) {
+    let board = game.get_board();
+    turns.sort_by_key(|turn| {
+        if Some(*turn) == first {
+            return -INFINITY;
+        }
+
+        let victim = match board.at_point(&turn.target) {
+            Some(piece) if board.is_capture(&turn.source, &turn.target) => value_of(&piece.kind),
+            _ => 0,
+        };
+        let attacker = board
+            .at_point(&turn.source)
+            .map_or(0, |piece| value_of(&piece.kind));
+        let promotion = turn.promotion.map_or(0, |kind| value_of(&kind));
+
+        -(victim * 10 - attacker / 10 + promotion)
+    });
+}
+
+pub fn evaluate(game: &Game) -> i32 {
+    let board = game.get_board();
+    let mut score = 0;
+
+    let (files, ranks) = (*board.width.end(), *board.height.end());
+    let edge = (files.max(ranks) - 1) as i32;
+
+    for x in board.width.clone() {
+        for y in board.height.clone() {
+            let piece = match board.at_point(&Point(x, y)) {
+                Some(piece) => piece,
+                None => continue,
+            };
+
+            let center = (2 * x - files - 1).abs().max((2 * y - ranks - 1).abs()) as i32;
+            let advance = match piece.color {
+                Color::White => y as i32 - 2,
+                Color::Black => (ranks - 1 - y) as i32,
+            };
+            let positional = match piece.kind {
+                Kind::Knight | Kind::Bishop => 4 * (edge - center),
+                Kind::Queen => edge - center,
+                Kind::Pawn => 5 * advance + edge - center,
+                _ => 0,
+            };
+
+            let value = value_of(&piece.kind) + positional;
+            if piece.color == game.color {
+                score += value;
+            } else {
+                score -= value;
+            }
+        }
+    }
+
+    for (color, pocket) in board.pockets.iter() {
+        let value: i32 = pocket.iter().map(value_of).sum();
+        if *color == game.color {
+            score += value;
+        } else {
+            score -= value;
+        }
+    }
+
+    match game.get_variant() {
+        Some(variant) if variant.wins_by_losing_pieces() => -score,
+        _ => score,
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+
+fn search(fen: &str, limits: Limits) -> (Option<Turn>, Vec<Info>) {
+    let game = Game::from_fen(fen).unwrap();
+    let mut infos = vec![];
+    let best = Engine::new().search(&game, &limits, |info| infos.push(info.clone()));
+    (best, infos)
+}
+
+fn depth(depth: u32) -> Limits {
+    Limits {
+        depth: Some(depth),
+        ..Limits::default()
+    }
+}
+
+#[test]
+fn test_finds_mate_in_one() {
+    let (best, infos) = search("k7/8/1K6/8/8/8/8/7R w - - 0 1", depth(3));
+
+    assert_eq!(best, Turn::parse("h1h8"));
+    assert_eq!(infos.last().unwrap().score, Score::Mate(1));
+}
+
+#[test]
+fn test_captures_hanging_queen() {
+    let (best, _) = search("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", depth(2));
+
+    assert_eq!(best, Turn::parse("d2d5"));
+}
+
+#[test]
+fn test_reports_each_depth() {
+    let (best, infos) = search(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        depth(2),
+    );
+
+    assert!(best.is_some());
+    assert_eq!(
+        infos.iter().map(|info| info.depth).collect::<Vec<u32>>(),
+        vec![1, 2]
+    );
+    assert_eq!(infos[1].pv.first().copied(), best);
+    assert!(infos[1].nodes > infos[0].nodes);
+}
+
+#[test]
+fn test_no_move_when_game_is_over() {
+    let (best, _) = search("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1", depth(2));
+
+    assert_eq!(best, None);
+}
+
+#[test]
+fn test_antichess_gives_pieces_away() {
+    let fen = "7k/8/8/8/8/1r6/8/K7 w - - 0 1";
+    let game = Game::from_fen_with_variant(fen, game::variant::by_name("antichess")).unwrap();
+    assert!(evaluate(&game) > 0);
+
+    let mut infos = vec![];
+    let best = Engine::new().search(&game, &depth(3), |info| infos.push(info.clone()));
+
+    // The king steps where the rook has to take it, leaving White without
+    // a move.
+    assert!(
+        best == Turn::parse("a1b1") || best == Turn::parse("a1b2"),
+        "{:?}",
+        best
+    );
+    assert_eq!(infos.last().unwrap().score, Score::Mate(1));
+}
+
+#[test]
+fn test_node_limit() {
+    let limits = Limits {
+        nodes: Some(200),
+        ..Limits::default()
+    };
+    let (best, infos) = search(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        limits,
+    );
+
+    assert!(best.is_some());
+    assert_eq!(infos.len(), 1);
+}
+
+#[test]
+fn test_stop_returns_completed_move() {
+    let engine = Engine::new();
+    let game = Game::new();
+    engine.stop();
+
+    let best = engine.search(&game, &Limits::default(), |_| {});
+    assert!(best.is_some());
+    assert!(Game::new().legal_turns().contains(&best.unwrap()));
+}
+
+#[test]
+fn test_threads_share_search() {
+    let mut engine = Engine::new();
+    engine.set_threads(3);
+    engine.set_hash_size(1);
+
+    let game = Game::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+    assert_eq!(engine.search(&game, &depth(3), |_| {}), Turn::parse("h1h8"));
+}
+
+#[test]
+fn test_clock_deadline() {
+    let limits = Limits {
+        white_time: Some(Duration::from_secs(60)),
+        white_increment: Some(Duration::from_secs(2)),
+        moves_to_go: Some(20),
+        ..Limits::default()
+    };
+
+    assert_eq!(
+        limits.deadline(&Color::White),
+        Some(Duration::from_millis(4500))
+    );
+    // Black has no time of its own given, so it gets a fixed budget.
+    assert_eq!(limits.deadline(&Color::Black), Some(FALLBACK_BUDGET));
+    assert_eq!(Limits::default().deadline(&Color::Black), None);
+
+    let movetime = Limits {
+        movetime: Some(Duration::from_millis(300)),
+        ..limits
+    };
+    assert_eq!(
+        movetime.deadline(&Color::Black),
+        Some(Duration::from_millis(300))
+    );
+}
+
+#[test]
+fn test_plays_book_move() {
+    let mut game = Game::new();
+    let key = crate::book::key(&game);
+
+    let mut bytes = key.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&[0x03, 0x1c, 0x00, 0x01, 0, 0, 0, 0]);
+    let mut engine = Engine::new();
+    engine.set_book(Some(Book::from_bytes(&bytes)), Selection::Best);
+
+    let mut infos = 0;
+    assert_eq!(
+        engine.search(&game, &depth(2), |_| infos += 1),
+        Turn::parse("e2e4")
+    );
+    assert_eq!(infos, 0);
+
+    game.play(&Turn::parse("e2e4").unwrap());
+    assert!(engine.search(&game, &depth(1), |_| infos += 1).is_some());
+    assert_eq!(infos, 1);
+}
@@ -0,0 +1,166 @@
+use super::{EndResult, Game, Turn, TurnResult};
+use crate::pieces::Kind;
+use crate::{Color, Point};
+use std::time::Duration;
+
+// Team A plays White on the first board and Black on the second, so partners
+// always have opposite colors and can drop each other's captures as they are.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Team {
+    A,
+    B,
+}
+
+impl Team {
+    pub fn of(board: usize, color: Color) -> Team {
+        match (board, color) {
+            (0, Color::White) | (1, Color::Black) => Team::A,
+            _ => Team::B,
+        }
+    }
+
+    pub fn color(&self, board: usize) -> Color {
+        match (board, self) {
+            (0, Team::A) | (1, Team::B) => Color::White,
+            _ => Color::Black,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TeamResult {
+    Win(Team),
+    Tie,
+}
+
+// Two crazyhouse games played side by side, where a piece captured on one
+// board goes to the partner's pocket on the other. All four clocks run at
+// once, and the first board to finish decides the match for both.
+#[derive(Clone)]
+pub struct Bughouse {
+    games: [Game; 2],
+    clocks: [[Duration; 2]; 2],
+    result: Option<TeamResult>,
+}
+
+fn side(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+impl Bughouse {
+    pub fn new(time: Duration) -> Self {
+        Bughouse::from_games([Game::crazyhouse(), Game::crazyhouse()], time)
+    }
+
+    pub fn from_games(mut games: [Game; 2], time: Duration) -> Self {
+        for game in games.iter_mut() {
+            game.board.crazyhouse = true;
+        }
+
+        Bughouse {
+            games,
+            clocks: [[time; 2]; 2],
+            result: None,
+        }
+    }
+
+    pub fn game(&self, board: usize) -> &Game {
+        &self.games[board]
+    }
+
+    pub fn time_left(&self, board: usize, color: Color) -> Duration {
+        self.clocks[board][side(color)]
+    }
+
+    pub fn get_result(&self) -> Option<TeamResult> {
+        self.result
+    }
+
+    // The elapsed time is how long the side to move on that board has been
+    // thinking since the last move there.
+    pub fn turn(
+        &mut self,
+        board: usize,
+        source: Point,
+        target: Point,
+        elapsed: Duration,
+    ) -> TurnResult {
+        self.act(board, elapsed, |game| game.turn(source, target))
+    }
+
+    pub fn promote(&mut self, board: usize, kind: Kind) -> TurnResult {
+        self.act(board, Duration::ZERO, |game| game.promote(kind))
+    }
+
+    pub fn drop_piece(
+        &mut self,
+        board: usize,
+        kind: Kind,
+        target: Point,
+        elapsed: Duration,
+    ) -> TurnResult {
+        self.act(board, elapsed, |game| game.drop_piece(kind, target))
+    }
+
+    pub fn play(&mut self, board: usize, turn: &Turn, elapsed: Duration) -> TurnResult {
+        self.act(board, elapsed, |game| game.play(turn))
+    }
+
+    // Ends the match when the side to move on the board has used up its time.
+    pub fn flag(&mut self, board: usize, elapsed: Duration) -> Option<TeamResult> {
+        if self.result.is_none() && board < 2 {
+            let color = self.games[board].color;
+            if elapsed >= self.clocks[board][side(color)] {
+                self.clocks[board][side(color)] = Duration::ZERO;
+                self.result = Some(TeamResult::Win(Team::of(board, color.inverse())));
+            }
+        }
+        self.result
+    }
+
+    fn act<F>(&mut self, board: usize, elapsed: Duration, action: F) -> TurnResult
+    where
+        F: FnOnce(&mut Game) -> TurnResult,
+    {
+        if self.result.is_some() || board > 1 {
+            return TurnResult::Failed;
+        }
+
+        let mover = self.games[board].color;
+        if let Some(TeamResult::Win(team)) = self.flag(board, elapsed) {
+            return TurnResult::GameEnd(EndResult::Win(team.color(board)));
+        }
+
+        let pocket_size = self.games[board].board.pockets[&mover].len();
+        let result = action(&mut self.games[board]);
+        if result == TurnResult::Failed {
+            return result;
+        }
+        self.clocks[board][side(mover)] -= elapsed;
+
+        // A capture lands in the mover's pocket first and is then handed over
+        // to the partner, who plays the captured color on the other board.
+        let pocket = self.games[board].board.pockets.get_mut(&mover).unwrap();
+        if pocket.len() > pocket_size {
+            let kind = pocket.pop().unwrap();
+            self.games[1 - board]
+                .board
+                .pockets
+                .entry(mover.inverse())
+                .or_default()
+                .push(kind);
+        }
+
+        self.result = match &result {
+            TurnResult::GameEnd(EndResult::Win(color)) => {
+                Some(TeamResult::Win(Team::of(board, *color)))
+            }
+            TurnResult::GameEnd(EndResult::Tie) => Some(TeamResult::Tie),
+            _ => None,
+        };
+        result
+    }
+}
@@ -0,0 +1,264 @@
+use super::fen::{back_row, has_moved, GEOMETRIES};
+use super::Game;
+use crate::board::Board;
+use crate::pieces::{Kind, Piece};
+use crate::{Color, Point};
+
+// Something that makes a custom setup impossible to play from.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Problem {
+    OutOfBounds(Point),
+    MissingKing(Color),
+    ExtraKing(Color),
+    PawnOnBackRank(Point),
+    // The side that just moved would have left its king in check.
+    OpponentInCheck(Color),
+    // A castling right without an unmoved king and rook to back it, named by
+    // the rook's square.
+    Castling(Color, Point),
+    EnPassant(Point),
+}
+
+// Sets up a position for standard rules piece by piece. Castling rights and
+// the en passant square are given explicitly and checked against the pieces,
+// the way a FEN would spell them out.
+#[derive(Clone)]
+pub struct BoardBuilder {
+    board: Board,
+    color: Color,
+    castling: Vec<(Color, Point)>,
+    enpassant: Option<Point>,
+    outside: Vec<Point>,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        BoardBuilder::new(8, 8).unwrap()
+    }
+}
+
+impl BoardBuilder {
+    // An empty board of one of the sizes FEN can describe, or None for any
+    // other size.
+    pub fn new(width: i8, height: i8) -> Option<Self> {
+        let (_, _, double_step) = GEOMETRIES
+            .iter()
+            .find(|(w, h, _)| *w as i8 == width && *h as i8 == height)?;
+        let mut board = Board::new(width, height);
+        board.double_step = *double_step;
+
+        Some(BoardBuilder {
+            board,
+            color: Color::White,
+            castling: vec![],
+            enpassant: None,
+            outside: vec![],
+        })
+    }
+
+    // Starts from an existing position, keeping its castling rights and en
+    // passant square.
+    pub fn from_game(game: &Game) -> Self {
+        let board = game.board.clone();
+        let mut castling = vec![];
+        for color in [Color::White, Color::Black].iter() {
+            for direction in [1, -1].iter() {
+                if let Some(rook) = board.castling_rook(color, *direction) {
+                    castling.push((*color, rook));
+                }
+            }
+        }
+
+        BoardBuilder {
+            enpassant: board.enpassant.map(|[passed, _]| passed),
+            board,
+            color: game.color,
+            castling,
+            outside: vec![],
+        }
+    }
+
+    pub fn place(&mut self, point: Point, piece: Piece) -> &mut Self {
+        if self.board.is_in_bounds(&point) {
+            self.board.set(&point, Some(piece));
+        } else {
+            self.outside.push(point);
+        }
+        self
+    }
+
+    pub fn remove(&mut self, point: Point) -> &mut Self {
+        if self.board.is_in_bounds(&point) {
+            self.board.set(&point, None);
+        }
+        self
+    }
+
+    // Empties the board along with the castling rights and en passant square.
+    pub fn clear(&mut self) -> &mut Self {
+        for square in self.board.current.iter_mut() {
+            *square = None;
+        }
+        self.castling.clear();
+        self.enpassant = None;
+        self.outside.clear();
+        self
+    }
+
+    pub fn to_move(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    // Lets the king castle with the rook on the given square.
+    pub fn castling(&mut self, color: Color, rook: Point) -> &mut Self {
+        if !self.castling.contains(&(color, rook)) {
+            self.castling.push((color, rook));
+        }
+        self
+    }
+
+    // The square a pawn just passed over with its double step.
+    pub fn enpassant(&mut self, passed: Option<Point>) -> &mut Self {
+        self.enpassant = passed;
+        self
+    }
+
+    pub fn validate(&self) -> Vec<Problem> {
+        self.assemble().1
+    }
+
+    pub fn build(&self) -> Result<Game, Vec<Problem>> {
+        let (board, problems) = self.assemble();
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        let mut game = Game::from_board(board);
+        game.color = self.color;
+        Ok(game)
+    }
+
+    fn assemble(&self) -> (Board, Vec<Problem>) {
+        let mut board = self.board.clone();
+        board.enpassant = None;
+        board.chess960 = false;
+        let mut problems: Vec<Problem> = self
+            .outside
+            .iter()
+            .map(|point| Problem::OutOfBounds(*point))
+            .collect();
+
+        let mut kings = [0, 0];
+        for x in board.width.clone() {
+            for y in board.height.clone() {
+                let point = Point(x, y);
+                let mut piece = match board.at_point(&point) {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+                match piece.kind {
+                    Kind::King => kings[piece.color as usize] += 1,
+                    Kind::Pawn if y == *board.height.start() || y == *board.height.end() => {
+                        problems.push(Problem::PawnOnBackRank(point))
+                    }
+                    _ => (),
+                }
+                piece.has_moved = has_moved(&board, &piece, y);
+                board.set(&point, Some(piece));
+            }
+        }
+        for color in [Color::White, Color::Black].iter() {
+            match kings[*color as usize] {
+                0 => problems.push(Problem::MissingKing(*color)),
+                1 => (),
+                _ => problems.push(Problem::ExtraKing(*color)),
+            }
+        }
+
+        let opponent = self.color.inverse();
+        if board.detect_check(&opponent).is_some() {
+            problems.push(Problem::OpponentInCheck(opponent));
+        }
+
+        for (color, rook) in self.castling.iter() {
+            if !grant_castling(&mut board, color, *rook) {
+                problems.push(Problem::Castling(*color, *rook));
+            }
+        }
+
+        if let Some(passed) = self.enpassant {
+            match enpassant_pawn(&board, &self.color, passed) {
+                Some(pawn) => board.enpassant = Some([passed, pawn]),
+                None => problems.push(Problem::EnPassant(passed)),
+            }
+        }
+
+        (board, problems)
+    }
+}
+
+fn grant_castling(board: &mut Board, color: &Color, rook: Point) -> bool {
+    if !board.is_in_bounds(&rook) {
+        return false;
+    }
+    let row = back_row(board, color);
+    let king = match board.find_king(color) {
+        Some(king) if king.1 == row && rook.1 == row && king.0 != rook.0 => king,
+        _ => return false,
+    };
+    let owned_rook = board
+        .at_point(&rook)
+        .is_some_and(|piece| piece.kind == Kind::Rook && &piece.color == color);
+    // Only one rook per side of the king can hold the right.
+    let direction = (rook.0 - king.0).signum();
+    if !owned_rook || board.castling_rook(color, direction).is_some() {
+        return false;
+    }
+
+    for point in [king, rook].iter() {
+        let index = board.index(point);
+        if let Some(piece) = board.current[index].as_mut() {
+            piece.has_moved = false;
+        }
+    }
+
+    let corner = if direction > 0 {
+        *board.width.end()
+    } else {
+        *board.width.start()
+    };
+    if rook.0 != corner || king.0 != *board.width.end() / 2 + 1 {
+        board.chess960 = true;
+    }
+    true
+}
+
+// The pawn that can be taken en passant, which has to have just made a double
+// step past the given square.
+fn enpassant_pawn(board: &Board, color: &Color, passed: Point) -> Option<Point> {
+    let step = match color {
+        Color::White => -1,
+        Color::Black => 1,
+    };
+    let rank = back_row(board, &color.inverse()) + 2 * step;
+    let pawn = Point(passed.0, passed.1 + step);
+    let origin = Point(passed.0, passed.1 - step);
+    if !board.double_step
+        || passed.1 != rank
+        || ![passed, pawn, origin]
+            .iter()
+            .all(|point| board.is_in_bounds(point))
+    {
+        return None;
+    }
+
+    let moved_pawn = board
+        .at_point(&pawn)
+        .is_some_and(|piece| piece.kind == Kind::Pawn && piece.color != *color);
+    if moved_pawn && board.at_point(&passed).is_none() && board.at_point(&origin).is_none() {
+        Some(pawn)
+    } else {
+        None
+    }
+}
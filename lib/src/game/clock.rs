@@ -0,0 +1,322 @@
+use crate::Color;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Where the clock reads the time from. Only differences between readings
+// matter, so a source can count from whenever it likes.
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+pub struct WallClock(Instant);
+
+impl Default for WallClock {
+    fn default() -> Self {
+        WallClock(Instant::now())
+    }
+}
+
+impl TimeSource for WallClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+// Time that only passes when told to, for tests and replays. Clones share
+// the same time.
+#[derive(Clone, Default)]
+pub struct ManualTime(Arc<Mutex<Duration>>);
+
+impl ManualTime {
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bonus {
+    None,
+    // Fischer: added after every move.
+    Increment(Duration),
+    // Bronstein: the time used is given back after the move, up to the delay.
+    Bronstein(Duration),
+    // Simple delay: the clock only starts counting down after the delay.
+    Delay(Duration),
+}
+
+// A number of moves to be played in a given time, or the rest of the game
+// when there is no number.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stage {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+// No stage may last longer than this, nor any bonus be larger, so that the
+// times stay far from overflowing.
+const LONGEST: Duration = Duration::from_secs(24 * 60 * 60);
+
+// The stages are played in order, and a last stage with a move count
+// repeats, as in PGN time controls.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl(pub Vec<Stage>);
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        TimeControl::single(time, Bonus::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        TimeControl::single(time, Bonus::Increment(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        TimeControl::single(time, Bonus::Bronstein(delay))
+    }
+
+    pub fn delay(time: Duration, delay: Duration) -> Self {
+        TimeControl::single(time, Bonus::Delay(delay))
+    }
+
+    fn single(time: Duration, bonus: Bonus) -> Self {
+        TimeControl(vec![Stage {
+            moves: None,
+            time,
+            bonus,
+        }])
+    }
+
+    // Reads controls the way players write them, with minutes for the time
+    // and seconds for the bonus: "5", "3+2", "15d5" for a simple delay,
+    // "15b5" for Bronstein, and stages separated by colons as in
+    // "40/90+30:30+30".
+    pub fn parse(control: &str) -> Option<Self> {
+        let mut stages = vec![];
+        for stage in control.split(':') {
+            let (moves, rest) = match stage.split_once('/') {
+                Some((moves, rest)) => (Some(moves.parse().ok().filter(|n| *n > 0)?), rest),
+                None => (None, stage),
+            };
+            let split = rest.find(['+', 'd', 'b']);
+            let (time, bonus) = match split {
+                Some(index) => {
+                    let seconds = rest[index + 1..]
+                        .parse()
+                        .ok()
+                        .map(Duration::from_secs)
+                        .filter(|seconds| *seconds <= LONGEST)?;
+                    let bonus = match &rest[index..=index] {
+                        "+" => Bonus::Increment(seconds),
+                        "d" => Bonus::Delay(seconds),
+                        _ => Bonus::Bronstein(seconds),
+                    };
+                    (&rest[..index], bonus)
+                }
+                None => (rest, Bonus::None),
+            };
+            let minutes: f64 = time.parse().ok()?;
+            let time = Duration::try_from_secs_f64(minutes * 60.0)
+                .ok()
+                .filter(|time| *time <= LONGEST)?;
+            stages.push(Stage { moves, time, bonus });
+        }
+        Some(TimeControl(stages))
+    }
+}
+
+// Writes controls back in the form parse reads.
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, stage) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = stage.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write!(f, "{}", stage.time.as_secs_f64() / 60.0)?;
+            match stage.bonus {
+                Bonus::None => {}
+                Bonus::Increment(bonus) => write!(f, "+{}", bonus.as_secs())?,
+                Bonus::Delay(bonus) => write!(f, "d{}", bonus.as_secs())?,
+                Bonus::Bronstein(bonus) => write!(f, "b{}", bonus.as_secs())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    stage: [usize; 2],
+    // Moves made in the current stage.
+    moves: [u32; 2],
+    // The side whose time is running, and when its move began.
+    running: Option<(Color, Duration)>,
+    source: Arc<dyn TimeSource>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Clock::with_source(control, Arc::new(WallClock::default()))
+    }
+
+    pub fn with_source(control: TimeControl, source: Arc<dyn TimeSource>) -> Self {
+        let time = control
+            .0
+            .first()
+            .map(|stage| stage.time)
+            .unwrap_or_default();
+        Clock {
+            control,
+            remaining: [time; 2],
+            stage: [0, 0],
+            moves: [0, 0],
+            running: None,
+            source,
+        }
+    }
+
+    pub fn get_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn running(&self) -> Option<Color> {
+        self.running.map(|(color, _)| color)
+    }
+
+    pub fn start(&mut self, color: Color) {
+        self.running = Some((color, self.source.now()));
+    }
+
+    // Stops the clock, keeping the time used on the current move.
+    pub fn stop(&mut self) {
+        if let Some(color) = self.running() {
+            self.remaining[color as usize] = self.remaining(&color);
+            self.running = None;
+        }
+    }
+
+    pub fn remaining(&self, color: &Color) -> Duration {
+        let left = self.remaining[*color as usize];
+        match self.running {
+            Some((running, since)) if running == *color => {
+                let mut used = self.source.now().saturating_sub(since);
+                if let Bonus::Delay(delay) = self.bonus(color) {
+                    used = used.saturating_sub(delay);
+                }
+                left.saturating_sub(used)
+            }
+            _ => left,
+        }
+    }
+
+    // Corrects a side's time, as when the opponent's clock is the one that
+    // counts. A running clock goes on from the new time.
+    pub fn set(&mut self, color: &Color, left: Duration) {
+        self.remaining[*color as usize] = left;
+        if self.running() == Some(*color) {
+            self.start(*color);
+        }
+    }
+
+    pub fn is_flagged(&self, color: &Color) -> bool {
+        self.remaining(color) == Duration::ZERO
+    }
+
+    // Ends the move of the side whose time is running and starts the
+    // opponent's. A side that has already run out gets no bonus.
+    pub fn press(&mut self, color: &Color) {
+        let since = match self.running {
+            Some((running, since)) if running == *color => since,
+            _ => return,
+        };
+        let side = *color as usize;
+        let left = self.remaining(color);
+        let used = self.source.now().saturating_sub(since);
+        self.remaining[side] = match self.bonus(color) {
+            _ if left == Duration::ZERO => left,
+            Bonus::Increment(increment) => left.saturating_add(increment),
+            Bonus::Bronstein(delay) => left.saturating_add(used.min(delay)),
+            Bonus::None | Bonus::Delay(_) => left,
+        };
+
+        self.moves[side] += 1;
+        let stages = &self.control.0;
+        let stage_over = stages
+            .get(self.stage[side])
+            .is_some_and(|stage| stage.moves == Some(self.moves[side]));
+        if stage_over {
+            self.moves[side] = 0;
+            if self.stage[side] + 1 < stages.len() {
+                self.stage[side] += 1;
+            }
+            self.remaining[side] =
+                self.remaining[side].saturating_add(stages[self.stage[side]].time);
+        }
+
+        self.start(color.inverse());
+    }
+
+    fn bonus(&self, color: &Color) -> Bonus {
+        self.control
+            .0
+            .get(self.stage[*color as usize])
+            .map_or(Bonus::None, |stage| stage.bonus)
+    }
+}
+
+// A clock is saved as the time each side has left and whose time is running.
+// A loaded clock reads the wall clock, and the running side's time goes on
+// from when it was loaded.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedClock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    stage: [usize; 2],
+    moves: [u32; 2],
+    running: Option<Color>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Clock {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedClock {
+            control: self.control.clone(),
+            remaining: [self.remaining(&Color::White), self.remaining(&Color::Black)],
+            stage: self.stage,
+            moves: self.moves,
+            running: self.running(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Clock {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedClock::deserialize(deserializer)?;
+        let mut clock = Clock::new(saved.control);
+        clock.remaining = saved.remaining;
+        clock.stage = saved.stage;
+        clock.moves = saved.moves;
+        if let Some(color) = saved.running {
+            clock.start(color);
+        }
+        Ok(clock)
+    }
+}
@@ -0,0 +1,398 @@
+use super::variant::{self, Variant};
+use super::Game;
+use crate::board::Board;
+use crate::pieces::{custom, Kind, Piece};
+use crate::{Color, Point};
+use std::sync::Arc;
+
+// The board sizes a placement can have, as width, height and whether pawns
+// may step two squares: standard and Capablanca chess, then Gardner and Los
+// Alamos minichess.
+pub(crate) const GEOMETRIES: [(usize, usize, bool); 4] =
+    [(8, 8, true), (10, 8, true), (5, 5, false), (6, 6, false)];
+
+impl Game {
+    // A position for standard play, which needs exactly one king per side.
+    pub fn from_fen(fen: &str) -> Option<Game> {
+        Game::from_fen_with_variant(fen, None)
+    }
+
+    // A position for the variant, which decides which kings there must be,
+    // as in horde where White has none.
+    pub fn from_fen_with_variant(fen: &str, variant: Option<Arc<dyn Variant>>) -> Option<Game> {
+        let mut game = Game::parse_fen(fen)?;
+        let kings_allowed = match &variant {
+            Some(variant) => variant.accepts(&game.board),
+            None => variant::one_king_each(&game.board),
+        };
+        if !kings_allowed {
+            return None;
+        }
+        game.set_variant(variant);
+        Some(game)
+    }
+
+    // Reads any position that fits one of the boards, whatever its kings.
+    pub(super) fn parse_fen(fen: &str) -> Option<Game> {
+        let mut fields = fen.split_whitespace();
+
+        let mut board = parse_placement(fields.next()?)?;
+
+        let color = match fields.next().unwrap_or("w") {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return None,
+        };
+
+        apply_castling(&mut board, fields.next().unwrap_or("-"))?;
+
+        board.enpassant = match fields.next().unwrap_or("-") {
+            "-" => None,
+            square => {
+                let passed = Point::parse(square)?;
+                let pawn = if passed.1 == board.height.start() + 2 {
+                    Point(passed.0, passed.1 + 1)
+                } else if passed.1 == board.height.end() - 2 {
+                    Point(passed.0, passed.1 - 1)
+                } else {
+                    return None;
+                };
+                if !board.is_in_bounds(&passed) {
+                    return None;
+                }
+                Some([passed, pawn])
+            }
+        };
+
+        let halfmove_clock = fields.next().unwrap_or("0").parse().ok()?;
+        let fullmove_number = fields.next().unwrap_or("1").parse().ok()?;
+
+        Some(Game {
+            board,
+            color,
+            promotion: None,
+            awaiting_duck: false,
+            result: None,
+            halfmove_clock,
+            fullmove_number,
+            checks: [0, 0],
+            history: vec![],
+            variant: None,
+            clock: None,
+        })
+    }
+
+    pub fn to_fen(&self) -> String {
+        let board = &self.board;
+        let mut rows: Vec<String> = vec![];
+
+        for y in board.height.clone().rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for x in board.width.clone() {
+                match board.at_point(&Point(x, y)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece.symbol());
+                        if board.crazyhouse && piece.promoted {
+                            row.push('~');
+                        }
+                    }
+                    None if board.duck == Some(Point(x, y)) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push('*');
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            rows.push(row);
+        }
+
+        let mut placement = rows.join("/");
+        if board.crazyhouse {
+            placement.push('[');
+            for color in [Color::White, Color::Black].iter() {
+                let mut pocket: Vec<Piece> = board.pockets[color]
+                    .iter()
+                    .map(|kind| Piece::new(*color, *kind))
+                    .collect();
+                pocket.sort_by_key(|piece| pocket_order(&piece.kind));
+                placement.extend(pocket.iter().map(|piece| piece.symbol()));
+            }
+            placement.push(']');
+        }
+
+        let mut castling = String::new();
+        for color in [Color::White, Color::Black].iter() {
+            for (direction, symbol) in [(1, 'k'), (-1, 'q')].iter() {
+                let rook = match board.castling_rook(color, *direction) {
+                    Some(rook) => rook,
+                    None => continue,
+                };
+                // X-FEN only names the file when an outer rook would make the
+                // letter ambiguous.
+                let symbol = if outer_rook(board, color, rook, *direction).is_some() {
+                    (b'a' + (rook.0 - board.width.start()) as u8) as char
+                } else {
+                    *symbol
+                };
+                castling.push(match color {
+                    Color::White => symbol.to_ascii_uppercase(),
+                    Color::Black => symbol,
+                });
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let enpassant = match board.enpassant {
+            Some([passed, _]) => passed.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            match self.color {
+                Color::White => 'w',
+                Color::Black => 'b',
+            },
+            castling,
+            enpassant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+fn pocket_order(kind: &Kind) -> usize {
+    [
+        Kind::Queen,
+        Kind::Rook,
+        Kind::Bishop,
+        Kind::Knight,
+        Kind::Pawn,
+    ]
+    .iter()
+    .position(|other| other == kind)
+    .unwrap_or(5)
+}
+
+pub(super) fn back_row(board: &Board, color: &Color) -> i8 {
+    match color {
+        Color::White => *board.height.start(),
+        Color::Black => *board.height.end(),
+    }
+}
+
+// Kings and rooks only keep castling rights that are given explicitly.
+pub(super) fn has_moved(board: &Board, piece: &Piece, rank: i8) -> bool {
+    match piece.kind {
+        // Pawns behind their usual rank, as in horde, may still advance two
+        // squares.
+        Kind::Pawn => match piece.color {
+            Color::White => rank > board.height.start() + 1,
+            Color::Black => rank < board.height.end() - 1,
+        },
+        Kind::King | Kind::Rook => true,
+        _ => false,
+    }
+}
+
+// The board size follows from the placement, so minichess and 10x8 positions
+// can be read as well, but only on the boards in GEOMETRIES.
+// Crazyhouse positions carry the pockets in brackets after the placement, as
+// in "...RNBQKBNR[Qp]", and mark promoted pieces with a tilde. The duck of
+// duck chess is written as a star.
+fn parse_placement(placement: &str) -> Option<Board> {
+    let (placement, pockets) = match placement.split_once('[') {
+        Some((placement, pockets)) => (placement, Some(pockets.strip_suffix(']')?)),
+        None => (placement, None),
+    };
+    let mut rows: Vec<Vec<Option<Piece>>> = vec![];
+    let mut duck: Option<(usize, usize)> = None;
+    // Long digit runs are turned away before they are counted out.
+    let widest = GEOMETRIES.iter().map(|(width, _, _)| *width).max()?;
+
+    for row in placement.split('/') {
+        let mut squares = vec![];
+        let mut skip = 0;
+        for symbol in row.chars() {
+            if let Some(digit) = symbol.to_digit(10) {
+                skip = skip * 10 + digit as usize;
+                if skip > widest {
+                    return None;
+                }
+                continue;
+            }
+            if symbol == '~' {
+                let piece: &mut Option<Piece> = squares.last_mut()?;
+                piece.as_mut()?.promoted = true;
+                continue;
+            }
+            squares.extend((0..skip).map(|_| None));
+            skip = 0;
+            if symbol == '*' && duck.is_none() {
+                duck = Some((rows.len(), squares.len()));
+                squares.push(None);
+                continue;
+            }
+            squares.push(Some(Piece::from_symbol(symbol)?));
+        }
+        squares.extend((0..skip).map(|_| None));
+        rows.push(squares);
+    }
+
+    let width = rows[0].len();
+    if rows.iter().any(|row| row.len() != width) {
+        return None;
+    }
+    let (_, _, double_step) = GEOMETRIES
+        .iter()
+        .find(|(w, h, _)| *w == width && *h == rows.len())?;
+
+    let mut board = Board::new(width as i8, rows.len() as i8);
+    board.double_step = *double_step;
+    // Pawns on the Capablanca board may also become the compound pieces.
+    if (width, rows.len()) == (10, 8) {
+        board
+            .promotions
+            .extend([custom::ARCHBISHOP, custom::CHANCELLOR].iter());
+    }
+    board.duck = duck.map(|(row, column)| Point(column as i8 + 1, (rows.len() - row) as i8));
+    for (row, y) in rows.iter().zip(board.height.clone().rev()) {
+        for (piece, x) in row.iter().zip(board.width.clone()) {
+            let mut piece = match piece {
+                Some(piece) => *piece,
+                None => continue,
+            };
+
+            piece.has_moved = has_moved(&board, &piece, y);
+            board.set(&Point(x, y), Some(piece));
+        }
+    }
+
+    if let Some(pockets) = pockets {
+        board.crazyhouse = true;
+        for symbol in pockets.chars() {
+            let piece = Piece::from_symbol(symbol)?;
+            if piece.kind == Kind::King {
+                return None;
+            }
+            board
+                .pockets
+                .entry(piece.color)
+                .or_default()
+                .push(piece.kind);
+        }
+    }
+
+    Some(board)
+}
+
+fn outer_rook(board: &Board, color: &Color, rook: Point, direction: i8) -> Option<Point> {
+    let mut x = rook.0 + direction;
+    while board.is_in_bounds(&Point(x, rook.1)) {
+        let point = Point(x, rook.1);
+        if board
+            .at_point(&point)
+            .is_some_and(|piece| piece.kind == Kind::Rook && &piece.color == color)
+        {
+            return Some(point);
+        }
+        x += direction;
+    }
+
+    None
+}
+
+// The rook furthest from the king on its side, found by scanning in from the
+// corner.
+fn outermost_rook(board: &Board, color: &Color, king: Point, direction: i8) -> Option<Point> {
+    let corner = if direction > 0 {
+        *board.width.end()
+    } else {
+        *board.width.start()
+    };
+    let mut x = corner;
+    while x != king.0 {
+        let point = Point(x, king.1);
+        if board
+            .at_point(&point)
+            .is_some_and(|piece| piece.kind == Kind::Rook && &piece.color == color)
+        {
+            return Some(point);
+        }
+        x -= direction;
+    }
+
+    None
+}
+
+// Accepts the standard KQkq letters, which mean the outermost rook on that
+// side, as well as Shredder-FEN and X-FEN rook files.
+fn apply_castling(board: &mut Board, castling: &str) -> Option<()> {
+    if castling == "-" {
+        return Some(());
+    }
+
+    for symbol in castling.chars() {
+        let color = if symbol.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let row = back_row(board, &color);
+
+        let king = board.width.clone().map(|x| Point(x, row)).find(|point| {
+            board
+                .at_point(point)
+                .is_some_and(|piece| piece.kind == Kind::King && piece.color == color)
+        })?;
+
+        let rook = match symbol.to_ascii_lowercase() {
+            'k' => outermost_rook(board, &color, king, 1)?,
+            'q' => outermost_rook(board, &color, king, -1)?,
+            file @ 'a'..='z' => Point(*board.width.start() + (file as u8 - b'a') as i8, row),
+            _ => return None,
+        };
+        if !board.is_in_bounds(&rook) || rook.0 == king.0 {
+            return None;
+        }
+
+        for (point, kind) in [(king, Kind::King), (rook, Kind::Rook)].iter() {
+            let index = board.index(point);
+            match board.current[index].as_mut() {
+                Some(piece) if piece.color == color && &piece.kind == kind => {
+                    piece.has_moved = false
+                }
+                _ => return None,
+            }
+        }
+
+        let direction = (rook.0 - king.0).signum();
+        let corner = if direction > 0 {
+            *board.width.end()
+        } else {
+            *board.width.start()
+        };
+        // Anything but the king on the e file (f on 10x8) and rooks in the
+        // corners needs Chess960 castling.
+        let king_file = *board.width.end() / 2 + 1;
+        if rook.0 != corner || king.0 != king_file {
+            board.chess960 = true;
+        }
+    }
+
+    Some(())
+}
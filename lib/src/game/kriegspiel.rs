@@ -0,0 +1,158 @@
+use super::{EndResult, Game, Turn, TurnResult};
+use crate::board::Board;
+use crate::pieces::Kind;
+use crate::{Color, Point};
+use std::collections::HashSet;
+
+// How a check is announced, seen from the checked king. The long diagonal is
+// the longer of the two diagonals through the king's square.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Check {
+    File,
+    Rank,
+    LongDiagonal,
+    ShortDiagonal,
+    Knight,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Announcement {
+    Illegal,
+    Legal {
+        capture: Option<Point>,
+        checks: Vec<Check>,
+        // Pawn captures the side to move now has.
+        pawn_tries: usize,
+        result: Option<EndResult>,
+    },
+}
+
+// Kriegspiel is normal chess where neither player sees the other's pieces.
+// The umpire sees both, and tells the players only whether a try was legal
+// and what everyone may hear: captures, checks and pawn tries.
+#[derive(Clone, Default)]
+pub struct Kriegspiel {
+    game: Game,
+}
+
+impl Kriegspiel {
+    pub fn new() -> Self {
+        Kriegspiel::from_game(Game::new())
+    }
+
+    pub fn from_game(game: Game) -> Self {
+        Kriegspiel { game }
+    }
+
+    pub fn color(&self) -> Color {
+        self.game.color
+    }
+
+    pub fn get_result(&self) -> Option<EndResult> {
+        self.game.get_result()
+    }
+
+    // A player only knows where their own pieces are.
+    pub fn view(&self, color: &Color) -> Board {
+        let mut board = self.game.board.clone();
+        for x in board.width.clone() {
+            for y in board.height.clone() {
+                let point = Point(x, y);
+                if board
+                    .at_point(&point)
+                    .is_some_and(|piece| &piece.color != color)
+                {
+                    board.set(&point, None);
+                }
+            }
+        }
+        board.enpassant = None;
+        board
+    }
+
+    pub fn attempt(&mut self, turn: &Turn) -> Announcement {
+        let board = &self.game.board;
+        let capture = if turn.drop.is_none() && board.is_capture(&turn.source, &turn.target) {
+            match board.at_point(&turn.target) {
+                Some(_) => Some(turn.target),
+                None => Some(Point(turn.target.0, turn.source.1)),
+            }
+        } else {
+            None
+        };
+
+        if self.game.play(turn) == TurnResult::Failed {
+            return Announcement::Illegal;
+        }
+
+        let board = &self.game.board;
+        let checks = match (
+            board.find_king(&self.game.color),
+            board.detect_check(&self.game.color),
+        ) {
+            (Some(king), Some(attackers)) => attackers
+                .iter()
+                .map(|attacker| classify(board, &king, attacker))
+                .collect(),
+            _ => vec![],
+        };
+
+        // A capture that promotes is one try, whatever the pawn becomes.
+        let pawn_tries: HashSet<(Point, Point)> = self
+            .game
+            .legal_turns()
+            .iter()
+            .filter(|turn| {
+                let board = &self.game.board;
+                board.is_capture(&turn.source, &turn.target)
+                    && board
+                        .at_point(&turn.source)
+                        .is_some_and(|piece| piece.kind == Kind::Pawn)
+            })
+            .map(|turn| (turn.source, turn.target))
+            .collect();
+
+        Announcement::Legal {
+            capture,
+            checks,
+            pawn_tries: pawn_tries.len(),
+            result: self.game.get_result(),
+        }
+    }
+}
+
+fn classify(board: &Board, king: &Point, attacker: &Point) -> Check {
+    if board
+        .at_point(attacker)
+        .is_some_and(|piece| piece.kind == Kind::Knight)
+    {
+        return Check::Knight;
+    }
+
+    let (dx, dy) = (attacker.0 - king.0, attacker.1 - king.1);
+    if dx == 0 {
+        return Check::File;
+    } else if dy == 0 {
+        return Check::Rank;
+    }
+
+    let step = Point(dx.signum(), dy.signum());
+    let other = Point(-step.0, step.1);
+    if diagonal_length(board, king, &step) >= diagonal_length(board, king, &other) {
+        Check::LongDiagonal
+    } else {
+        Check::ShortDiagonal
+    }
+}
+
+fn diagonal_length(board: &Board, point: &Point, step: &Point) -> usize {
+    let mut length = 1;
+    for direction in [*step, Point(-step.0, -step.1)].iter() {
+        let mut current = point.add(direction);
+        while board.is_in_bounds(&current) {
+            length += 1;
+            current = current.add(direction);
+        }
+    }
+    length
+}
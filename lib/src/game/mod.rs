@@ -1,29 +1,237 @@
 use crate::board::Board;
 use crate::pieces::{Kind, Piece};
 use crate::*;
+use clock::Clock;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use variant::Variant;
+
+pub mod bughouse;
+pub mod builder;
+pub mod clock;
+mod fen;
+pub mod kriegspiel;
+mod san;
+pub mod variant;
+
+pub(crate) use fen::GEOMETRIES;
 
 #[cfg(test)]
 mod tests;
 
-#[derive(PartialEq, Debug)]
+#[cfg(test)]
+mod test_fen;
+
+#[cfg(test)]
+mod test_chess960;
+
+#[cfg(test)]
+mod test_board_size;
+
+#[cfg(test)]
+mod test_fairy;
+
+#[cfg(test)]
+mod test_capablanca;
+
+#[cfg(test)]
+mod test_crazyhouse;
+
+#[cfg(test)]
+mod test_bughouse;
+
+#[cfg(test)]
+mod test_variant;
+
+#[cfg(test)]
+mod test_atomic;
+
+#[cfg(test)]
+mod test_antichess;
+
+#[cfg(test)]
+mod test_horde;
+
+#[cfg(test)]
+mod test_fog;
+
+#[cfg(test)]
+mod test_duck;
+
+#[cfg(test)]
+mod test_builder;
+
+#[cfg(test)]
+mod test_clock;
+
+#[cfg(test)]
+mod test_san;
+
+#[cfg(all(test, feature = "serde"))]
+mod test_serde;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EndResult {
     Win(Color),
     Tie,
 }
 
 #[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TurnResult {
     Moved,
     Checked,
     GameEnd(EndResult),
     Promotion,
+    // The move is made, and the duck has to be placed to finish the turn.
+    Duck,
     Failed,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Turn {
+    pub source: Point,
+    pub target: Point,
+    pub promotion: Option<Kind>,
+    // A piece dropped from the pocket onto the target, as in crazyhouse.
+    pub drop: Option<Kind>,
+    // Where the duck goes after the move, in duck chess.
+    pub duck: Option<Point>,
+}
+
+impl Turn {
+    pub fn new(source: Point, target: Point) -> Self {
+        Turn {
+            source,
+            target,
+            promotion: None,
+            drop: None,
+            duck: None,
+        }
+    }
+
+    pub fn drop(kind: Kind, target: Point) -> Self {
+        Turn {
+            drop: Some(kind),
+            ..Turn::new(target, target)
+        }
+    }
+
+    pub fn parse(notation: &str) -> Option<Turn> {
+        if !notation.is_ascii() {
+            return None;
+        }
+
+        // Duck moves follow the move itself, as in "e2e4,e6".
+        if let Some((notation, duck)) = notation.split_once(',') {
+            return Some(Turn {
+                duck: Some(Point::parse(duck)?),
+                ..Turn::parse(notation)?
+            });
+        }
+
+        if let Some((symbol, square)) = notation.split_once('@') {
+            let mut symbols = symbol.chars();
+            let kind = Kind::from_symbol(symbols.next()?)?;
+            if symbols.next().is_some() || kind == Kind::King {
+                return None;
+            }
+            return Some(Turn::drop(kind, Point::parse(square)?));
+        }
+
+        if notation.len() < 4 {
+            return None;
+        }
+
+        let (squares, promotion) = match notation.chars().last() {
+            Some(symbol) if symbol.is_ascii_alphabetic() => {
+                let kind = Kind::from_symbol(symbol)?;
+                (&notation[..notation.len() - 1], Some(kind))
+            }
+            _ => (notation, None),
+        };
+
+        let split = squares[1..].find(|c: char| c.is_ascii_alphabetic())? + 1;
+
+        Some(Turn {
+            promotion,
+            ..Turn::new(
+                Point::parse(&squares[..split])?,
+                Point::parse(&squares[split..])?,
+            )
+        })
+    }
+}
+
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(kind) = self.drop {
+            write!(f, "{}@{}", kind.symbol().to_ascii_uppercase(), self.target)?;
+        } else {
+            write!(f, "{}{}", self.source, self.target)?;
+            if let Some(kind) = self.promotion {
+                write!(f, "{}", kind.symbol())?;
+            }
+        }
+        if let Some(duck) = self.duck {
+            write!(f, ",{}", duck)?;
+        }
+        Ok(())
+    }
+}
+
+// The whole board, including what fog of war would hide. Other styles go
+// through get_board().render().
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.board.fmt(f)
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Snapshot {
+    board: Board,
+    color: Color,
+    result: Option<EndResult>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    checks: [u32; 2],
+    turn: Turn,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
     board: Board,
     pub color: Color,
     promotion: Option<(Point, Point)>,
+    awaiting_duck: bool,
+    result: Option<EndResult>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    // Checks given by White and Black, which three-check counts.
+    checks: [u32; 2],
+    history: Vec<Snapshot>,
+    // Saved by name, so only the built-in variants can be loaded again.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "variant::serialize",
+            deserialize_with = "variant::deserialize"
+        )
+    )]
+    variant: Option<Arc<dyn Variant>>,
+    clock: Option<Clock>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
@@ -32,11 +240,99 @@ impl Game {
             board: Board::default(),
             color: Color::White,
             promotion: None,
+            awaiting_duck: false,
+            result: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            checks: [0, 0],
+            history: vec![],
+            variant: None,
+            clock: None,
         }
     }
 
+    // Takes the board as it is, so only boards known to be playable are
+    // passed in. Custom setups go through builder::BoardBuilder, which checks
+    // them first.
+    fn from_board(board: Board) -> Self {
+        Game {
+            board,
+            ..Game::new()
+        }
+    }
+
+    pub fn with_variant(variant: Arc<dyn Variant>) -> Self {
+        let mut game = Game::from_board(variant.start());
+        game.set_variant(Some(variant));
+        game
+    }
+
+    pub fn king_of_the_hill() -> Self {
+        Game::with_variant(Arc::new(variant::KingOfTheHill))
+    }
+
+    pub fn three_check() -> Self {
+        Game::with_variant(Arc::new(variant::ThreeCheck))
+    }
+
+    pub fn atomic() -> Self {
+        Game::with_variant(Arc::new(variant::Atomic))
+    }
+
+    pub fn antichess() -> Self {
+        Game::with_variant(Arc::new(variant::Antichess))
+    }
+
+    pub fn horde() -> Self {
+        Game::with_variant(Arc::new(variant::Horde))
+    }
+
+    pub fn fog_of_war() -> Self {
+        Game::with_variant(Arc::new(variant::FogOfWar))
+    }
+
+    pub fn duck_chess() -> Self {
+        Game::with_variant(Arc::new(variant::DuckChess))
+    }
+
+    pub fn racing_kings() -> Self {
+        Game::with_variant(Arc::new(variant::RacingKings))
+    }
+
+    // Plays the rest of the game, for example one set up from FEN, under the
+    // variant's rules.
+    pub fn set_variant(&mut self, variant: Option<Arc<dyn Variant>>) {
+        if let Some(variant) = &variant {
+            variant.setup(&mut self.board);
+        }
+        self.variant = variant;
+    }
+
+    pub fn get_variant(&self) -> Option<&dyn Variant> {
+        self.variant.as_deref()
+    }
+
+    pub fn get_checks(&self, color: &Color) -> u32 {
+        self.checks[*color as usize]
+    }
+
+    pub fn crazyhouse() -> Self {
+        Game::from_board(Board::crazyhouse())
+    }
+
+    pub fn capablanca() -> Self {
+        Game::from_board(Board::capablanca())
+    }
+
+    pub fn chess960(index: u16) -> Option<Self> {
+        Board::chess960(index).map(Game::from_board)
+    }
+
     pub fn turn(&mut self, source: Point, target: Point) -> TurnResult {
-        if self.promotion.is_some() {
+        if self.is_flagged() {
+            return TurnResult::Failed;
+        }
+        if self.promotion.is_some() || self.awaiting_duck || self.result.is_some() {
             return TurnResult::Failed;
         };
 
@@ -51,78 +347,543 @@ impl Game {
         if let Some(piece) = self.board.at_point(&source) {
             if piece.color != self.color {
                 return TurnResult::Failed;
-            } else if piece.kind == Kind::Pawn {
-                let last_row = match self.color {
-                    Color::White => 8,
-                    Color::Black => 1,
-                };
-                if target.1 == last_row {
-                    self.promotion = Some((source, target));
-                    return TurnResult::Promotion;
-                };
+            } else if piece.kind == Kind::Pawn && target.1 == self.promotion_row() {
+                self.promotion = Some((source, target));
+                return TurnResult::Promotion;
             };
         };
 
-        if !self.board.move_piece(source, target) {
+        self.advance(Turn::new(source, target))
+    }
+
+    pub fn drop_piece(&mut self, kind: Kind, target: Point) -> TurnResult {
+        if self.is_flagged() {
+            return TurnResult::Failed;
+        }
+        let allowed = self
+            .get_drops(kind)
+            .is_some_and(|drops| drops.contains(&target));
+        if !allowed {
             return TurnResult::Failed;
         }
 
-        let opponent_can_move = self.color_can_move(&self.color.inverse());
+        self.advance(Turn::drop(kind, target))
+    }
+
+    pub fn get_drops(&mut self, kind: Kind) -> Option<Vec<Point>> {
+        if self.promotion.is_some() || self.awaiting_duck || self.result.is_some() {
+            return None;
+        }
+
+        let color = self.color;
+        self.allowed_drops(&color, kind)
+    }
 
-        let opponent_is_checked = match self.board.detect_check(&self.color.inverse()) {
-            Some(_) => true,
-            None => false,
+    pub fn promote(&mut self, kind: Kind) -> TurnResult {
+        if self.is_flagged() {
+            return TurnResult::Failed;
+        }
+        let (source, target) = match self.promotion {
+            Some(points) => (points.0, points.1),
+            None => return TurnResult::Failed,
         };
+        if !self.board.promotions.contains(&kind) {
+            return TurnResult::Failed;
+        }
+
+        self.promotion = None;
+
+        self.advance(Turn {
+            promotion: Some(kind),
+            ..Turn::new(source, target)
+        })
+    }
+
+    // Finishes a turn of duck chess by moving the duck to another empty square.
+    pub fn place_duck(&mut self, target: Point) -> TurnResult {
+        if self.is_flagged() {
+            return TurnResult::Failed;
+        }
+        if !self.awaiting_duck || !self.board.place_duck(target) {
+            return TurnResult::Failed;
+        }
+
+        self.awaiting_duck = false;
+        if let Some(snapshot) = self.history.last_mut() {
+            snapshot.turn.duck = Some(target);
+        }
+        self.conclude()
+    }
+
+    pub fn is_awaiting_duck(&self) -> bool {
+        self.awaiting_duck
+    }
+
+    pub fn get_duck_squares(&self) -> Option<Vec<Point>> {
+        if !self.awaiting_duck {
+            return None;
+        }
+
+        let mut squares: Vec<Point> = vec![];
+        for x in self.board.width.clone() {
+            for y in self.board.height.clone() {
+                if self.board.is_empty(&Point(x, y)) {
+                    squares.push(Point(x, y));
+                }
+            }
+        }
+        Some(squares)
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let snapshot = match self.history.pop() {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+
+        self.board = snapshot.board;
+        self.color = snapshot.color;
+        self.result = snapshot.result;
+        self.halfmove_clock = snapshot.halfmove_clock;
+        self.fullmove_number = snapshot.fullmove_number;
+        self.checks = snapshot.checks;
+        self.promotion = None;
+        self.awaiting_duck = false;
+        // The clock keeps the time already used and just changes sides.
+        let color = self.color;
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+            if self.result.is_none() {
+                clock.start(color);
+            }
+        }
+
+        true
+    }
+
+    pub fn get_history(&self) -> Vec<Turn> {
+        self.history.iter().map(|snapshot| snapshot.turn).collect()
+    }
+
+    fn advance(&mut self, turn: Turn) -> TurnResult {
+        let snapshot = Snapshot {
+            board: self.board.clone(),
+            color: self.color,
+            result: self.result,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            checks: self.checks,
+            turn,
+        };
+
+        let resets_clock = turn.drop == Some(Kind::Pawn)
+            || self.board.is_capture(&turn.source, &turn.target)
+            || self
+                .board
+                .at_point(&turn.source)
+                .is_some_and(|piece| piece.kind == Kind::Pawn);
+
+        if let Some(kind) = turn.promotion {
+            let mut piece = Piece::new(self.color, kind);
+            piece.has_moved = true;
+            piece.promoted = true;
+            self.board.set(&turn.source, Some(piece));
+        }
+
+        let moved = match turn.drop {
+            Some(kind) => self.board.drop_piece(&self.color, kind, turn.target),
+            None => self.board.move_piece(turn.source, turn.target),
+        };
+        if !moved {
+            self.board = snapshot.board;
+            return TurnResult::Failed;
+        }
+
+        self.history.push(snapshot);
+
+        self.halfmove_clock = if resets_clock {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        if self.color == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        let has_duck = self.variant.as_ref().is_some_and(|v| v.has_duck());
+        if has_duck && self.board.find_king(&self.color.inverse()).is_some() {
+            self.awaiting_duck = true;
+            return TurnResult::Duck;
+        }
+
+        self.conclude()
+    }
+
+    // Finishes a turn, pressing the clock for the mover or stopping it when
+    // the game is over.
+    fn conclude(&mut self) -> TurnResult {
+        let mover = self.color;
+        let result = self.hand_over();
+        if let Some(clock) = &mut self.clock {
+            match self.result {
+                Some(_) => clock.stop(),
+                None => clock.press(&mover),
+            }
+        }
+        result
+    }
+
+    // Hands the turn over and works out whether the game has ended.
+    fn hand_over(&mut self) -> TurnResult {
+        let opponent_can_move = self.color_can_move(&self.color.inverse());
+
+        let opponent_is_checked = self.board.detect_check(&self.color.inverse()).is_some();
+
+        let mover = self.color;
+        self.color = self.color.inverse();
+        if opponent_is_checked {
+            self.checks[mover as usize] += 1;
+        }
+
+        if let Some(result) = self.variant.as_ref().and_then(|v| v.result(self, mover)) {
+            self.result = Some(result);
+            return TurnResult::GameEnd(result);
+        }
 
         if opponent_is_checked && opponent_can_move {
-            self.color = self.color.inverse();
             TurnResult::Checked
         } else if opponent_is_checked && !opponent_can_move {
-            TurnResult::GameEnd(EndResult::Win(self.color))
+            self.result = Some(EndResult::Win(mover));
+            TurnResult::GameEnd(EndResult::Win(mover))
         } else if !opponent_is_checked && !opponent_can_move {
+            self.result = Some(EndResult::Tie);
             TurnResult::GameEnd(EndResult::Tie)
         } else {
-            self.color = self.color.inverse();
             TurnResult::Moved
         }
     }
 
-    pub fn promote(&mut self, kind: Kind) -> TurnResult {
-        let (source, target) = match self.promotion {
-            Some(points) => (points.0, points.1),
-            None => return TurnResult::Failed,
+    pub fn play(&mut self, turn: &Turn) -> TurnResult {
+        let result = match turn.drop {
+            Some(kind) => self.drop_piece(kind, turn.target),
+            None => match self.turn(turn.source, turn.target) {
+                TurnResult::Promotion => match turn.promotion {
+                    Some(kind) if self.board.promotions.contains(&kind) => self.promote(kind),
+                    _ => {
+                        self.promotion = None;
+                        TurnResult::Failed
+                    }
+                },
+                result => result,
+            },
         };
 
-        self.board.current[source.index()] = Some(Piece::new(self.color, kind));
+        if result != TurnResult::Duck {
+            return result;
+        }
+        match turn.duck.map(|duck| self.place_duck(duck)) {
+            Some(TurnResult::Failed) | None => {
+                self.undo();
+                TurnResult::Failed
+            }
+            Some(result) => result,
+        }
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn get_result(&self) -> Option<EndResult> {
+        self.result
+    }
+
+    pub fn resign(&mut self, color: Color) -> TurnResult {
+        self.end(EndResult::Win(color.inverse()))
+    }
+
+    pub fn agree_draw(&mut self) -> TurnResult {
+        self.end(EndResult::Tie)
+    }
+
+    fn end(&mut self, result: EndResult) -> TurnResult {
+        if self.result.is_some() {
+            return TurnResult::Failed;
+        }
 
+        self.result = Some(result);
         self.promotion = None;
+        self.awaiting_duck = false;
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        TurnResult::GameEnd(result)
+    }
 
-        self.turn(source, target)
+    // The clock starts running for the side to move straight away.
+    pub fn set_clock(&mut self, clock: Option<Clock>) {
+        self.clock = clock;
+        let color = self.color;
+        if let Some(clock) = &mut self.clock {
+            match self.result {
+                Some(_) => clock.stop(),
+                None => clock.start(color),
+            }
+        }
     }
 
-    pub fn get_board(&self) -> &Board {
-        &self.board
+    pub fn get_clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    // Ends the game once the side to move has run out of time. Running out
+    // only loses when the opponent could still mate, and is a draw otherwise.
+    // Moves fail once the flag has fallen, without ending the game, so
+    // whoever plays or waits for a move should ask here every now and then.
+    pub fn check_time(&mut self) -> Option<EndResult> {
+        if !self.is_flagged() {
+            return None;
+        }
+
+        match self.forfeit_on_time() {
+            TurnResult::GameEnd(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    fn is_flagged(&self) -> bool {
+        self.result.is_none()
+            && self
+                .clock
+                .as_ref()
+                .is_some_and(|clock| clock.is_flagged(&self.color))
+    }
+
+    // Ends the game as if the side to move had run out of time, for limits
+    // kept outside the clock such as days per move.
+    pub fn forfeit_on_time(&mut self) -> TurnResult {
+        let opponent = self.color.inverse();
+        if self.board.has_mating_material(&opponent) {
+            self.end(EndResult::Win(opponent))
+        } else {
+            self.end(EndResult::Tie)
+        }
+    }
+
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    // The board as one color sees it, with every square its pieces cannot
+    // reach left empty.
+    pub fn view(&self, color: &Color) -> Board {
+        let visible = self.board.visible(color);
+        let mut board = self.board.clone();
+        for x in self.board.width.clone() {
+            for y in self.board.height.clone() {
+                if !visible.contains(&Point(x, y)) {
+                    board.set(&Point(x, y), None);
+                }
+            }
+        }
+        if board
+            .enpassant
+            .is_some_and(|[_, pawn]| board.at_point(&pawn).is_none())
+        {
+            board.enpassant = None;
+        }
+        board
+    }
+
+    pub fn visible(&self, color: &Color) -> Vec<Point> {
+        self.board.visible(color)
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.board.detect_check(&self.color).is_some()
     }
 
     pub fn get_moves(&mut self, source: &Point) -> Option<Vec<Point>> {
-        if let Some(piece) = &self.board.current[source.index()] {
+        if let Some(piece) = &self.board.at_point(source) {
             if piece.color != self.color {
                 return None;
             }
         }
 
-        self.board.get_allowed_moves(source)
+        self.allowed_moves(source)
+    }
+
+    pub fn legal_turns(&mut self) -> Vec<Turn> {
+        let mut turns: Vec<Turn> = vec![];
+
+        if self.promotion.is_some() || self.awaiting_duck || self.result.is_some() {
+            return turns;
+        }
+
+        for x in self.board.width.clone() {
+            for y in self.board.height.clone() {
+                let source = Point(x, y);
+                let piece = match self.board.at_point(&source) {
+                    Some(piece) if piece.color == self.color => piece,
+                    _ => continue,
+                };
+
+                for target in self.allowed_moves(&source).unwrap_or_default() {
+                    if piece.kind == Kind::Pawn && target.1 == self.promotion_row() {
+                        for kind in self.board.promotions.iter() {
+                            turns.push(Turn {
+                                promotion: Some(*kind),
+                                ..Turn::new(source, target)
+                            });
+                        }
+                    } else {
+                        turns.push(Turn::new(source, target));
+                    }
+                }
+            }
+        }
+
+        let color = self.color;
+        for kind in self.pocket_kinds(&color) {
+            for target in self.allowed_drops(&color, kind).unwrap_or_default() {
+                turns.push(Turn::drop(kind, target));
+            }
+        }
+
+        if self.variant.as_ref().is_some_and(|v| v.has_duck()) {
+            return self.with_duck(turns);
+        }
+        turns
+    }
+
+    // Every move followed by every square the duck can go to, unless the move
+    // takes the king and ends the game.
+    fn with_duck(&self, turns: Vec<Turn>) -> Vec<Turn> {
+        let mut with_duck: Vec<Turn> = vec![];
+        for turn in turns {
+            let mut game = self.clone();
+            if game.advance(turn) != TurnResult::Duck {
+                with_duck.push(turn);
+                continue;
+            }
+            for duck in game.get_duck_squares().unwrap_or_default() {
+                with_duck.push(Turn {
+                    duck: Some(duck),
+                    ..turn
+                });
+            }
+        }
+        with_duck
+    }
+
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let turns = self.legal_turns();
+        if depth == 1 {
+            return turns.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for turn in turns {
+            if self.play(&turn) != TurnResult::Failed {
+                nodes += self.perft(depth - 1);
+                self.undo();
+            }
+        }
+        nodes
+    }
+
+    pub fn hash_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.board.current.hash(&mut hasher);
+        self.board.enpassant.hash(&mut hasher);
+        self.board.duck.hash(&mut hasher);
+        self.color.hash(&mut hasher);
+        for color in [Color::White, Color::Black].iter() {
+            let mut pocket = self.board.pockets.get(color).cloned().unwrap_or_default();
+            pocket.sort_by_key(|kind| kind.symbol());
+            pocket.hash(&mut hasher);
+        }
+        if self
+            .variant
+            .as_ref()
+            .is_some_and(|variant| variant.counts_checks())
+        {
+            self.checks.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn promotion_row(&self) -> i8 {
+        match self.color {
+            Color::White => *self.board.height.end(),
+            Color::Black => *self.board.height.start(),
+        }
+    }
+
+    // The board's legal moves, narrowed down by the variant if there is one.
+    fn allowed_moves(&mut self, source: &Point) -> Option<Vec<Point>> {
+        let moves = self.board.get_allowed_moves(source)?;
+        let (variant, piece) = match (&self.variant, self.board.at_point(source)) {
+            (Some(variant), Some(piece)) => (variant, piece),
+            _ => return Some(moves),
+        };
+
+        let moves: Vec<Point> = moves
+            .into_iter()
+            .filter(|target| variant.allows(&self.board, piece.color, &Turn::new(*source, *target)))
+            .collect();
+        if moves.is_empty() {
+            None
+        } else {
+            Some(moves)
+        }
+    }
+
+    fn allowed_drops(&mut self, color: &Color, kind: Kind) -> Option<Vec<Point>> {
+        let drops = self.board.get_drops(color, kind)?;
+        let variant = match &self.variant {
+            Some(variant) => variant,
+            None => return Some(drops),
+        };
+
+        let drops: Vec<Point> = drops
+            .into_iter()
+            .filter(|target| variant.allows(&self.board, *color, &Turn::drop(kind, *target)))
+            .collect();
+        if drops.is_empty() {
+            None
+        } else {
+            Some(drops)
+        }
+    }
+
+    fn pocket_kinds(&self, color: &Color) -> Vec<Kind> {
+        let mut kinds: Vec<Kind> = vec![];
+        for kind in self.board.pockets.get(color).into_iter().flatten() {
+            if !kinds.contains(kind) {
+                kinds.push(*kind);
+            }
+        }
+        kinds
     }
 
     fn color_can_move(&mut self, color: &Color) -> bool {
+        for kind in self.pocket_kinds(color) {
+            if self.allowed_drops(color, kind).is_some() {
+                return true;
+            }
+        }
+
         for x in self.board.width.clone() {
             for y in self.board.height.clone() {
                 let point = Point(x, y);
                 if let Some(piece) = self.board.at_point(&point) {
-                    if &piece.color == color {
-                        if self.board.get_allowed_moves(&point).is_some() {
-                            return true;
-                        }
+                    if &piece.color == color && self.allowed_moves(&point).is_some() {
+                        return true;
                     }
                 }
             }
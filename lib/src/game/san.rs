@@ -0,0 +1,136 @@
+use super::{EndResult, Game, Turn, TurnResult};
+use crate::pieces::Kind;
+use crate::Point;
+
+// Standard algebraic notation, as in "Nbd7", "exd5", "e8=Q+" and "O-O".
+// Drops are written "N@f3" and duck moves end in ",e6", as in turn notation.
+impl Game {
+    // Writes a legal turn in SAN, with "+" for check and "#" for mate.
+    pub fn to_san(&self, turn: &Turn) -> Option<String> {
+        let mut game = self.clone();
+        game.set_clock(None);
+        let turns = game.legal_turns();
+        if !turns.contains(turn) {
+            return None;
+        }
+
+        let mut san = self.san_body(turn, &turns, false);
+        match game.play(turn) {
+            TurnResult::GameEnd(EndResult::Win(_)) => san.push('#'),
+            TurnResult::Checked => san.push('+'),
+            _ => {}
+        }
+        if let Some(duck) = turn.duck {
+            san.push_str(&format!(",{}", duck));
+        }
+        Some(san)
+    }
+
+    // Finds the legal turn written in SAN. Check marks, capture marks and the
+    // "=" before a promotion are optional, and a piece may name more of its
+    // square than it needs to.
+    pub fn parse_san(&self, san: &str) -> Option<Turn> {
+        let (san, duck) = match san.split_once(',') {
+            Some((san, duck)) => (san, Some(Point::parse(duck)?)),
+            None => (san, None),
+        };
+        let wanted = normalize(san);
+
+        let mut game = self.clone();
+        let turns: Vec<Turn> = game
+            .legal_turns()
+            .into_iter()
+            .filter(|turn| turn.duck == duck)
+            .collect();
+        for full in [false, true].iter() {
+            let found: Vec<&Turn> = turns
+                .iter()
+                .filter(|turn| normalize(&self.san_body(turn, &turns, *full)) == wanted)
+                .collect();
+            if let [turn] = found.as_slice() {
+                return Some(**turn);
+            }
+        }
+        None
+    }
+
+    // The notation without the check mark. With full set, pieces always name
+    // their square.
+    fn san_body(&self, turn: &Turn, turns: &[Turn], full: bool) -> String {
+        if let Some(kind) = turn.drop {
+            return format!("{}@{}", kind.symbol().to_ascii_uppercase(), turn.target);
+        }
+        let board = &self.board;
+        let piece = match board.at_point(&turn.source) {
+            Some(piece) => piece,
+            None => return turn.to_string(),
+        };
+
+        let onto_own = board
+            .at_point(&turn.target)
+            .is_some_and(|other| other.color == piece.color);
+        let castles = piece.kind == Kind::King
+            && if board.chess960 {
+                onto_own
+            } else {
+                (turn.target.0 - turn.source.0).abs() >= 2
+            };
+        if castles {
+            return match turn.target.0 > turn.source.0 {
+                true => "O-O".to_string(),
+                false => "O-O-O".to_string(),
+            };
+        }
+
+        let capture = board.is_capture(&turn.source, &turn.target);
+        let mut san = String::new();
+        if piece.kind == Kind::Pawn {
+            if capture {
+                san.push_str(&turn.source.to_string()[..1]);
+            }
+        } else {
+            san.push(piece.kind.symbol().to_ascii_uppercase());
+            let rivals: Vec<&Turn> = turns
+                .iter()
+                .filter(|other| {
+                    other.target == turn.target
+                        && other.source != turn.source
+                        && other.drop.is_none()
+                        && board
+                            .at_point(&other.source)
+                            .is_some_and(|other| other.kind == piece.kind)
+                })
+                .collect();
+            let square = turn.source.to_string();
+            let (file, rank) = square.split_at(1);
+            if full {
+                san.push_str(&square);
+            } else if !rivals.is_empty() {
+                if rivals.iter().all(|other| other.source.0 != turn.source.0) {
+                    san.push_str(file);
+                } else if rivals.iter().all(|other| other.source.1 != turn.source.1) {
+                    san.push_str(rank);
+                } else {
+                    san.push_str(&square);
+                }
+            }
+        }
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&turn.target.to_string());
+        if let Some(kind) = turn.promotion {
+            san.push('=');
+            san.push(kind.symbol().to_ascii_uppercase());
+        }
+        san
+    }
+}
+
+fn normalize(san: &str) -> String {
+    san.replace("0-0-0", "O-O-O")
+        .replace("0-0", "O-O")
+        .chars()
+        .filter(|c| !"+#!?x=".contains(*c))
+        .collect()
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+fn antichess(fen: &str) -> Game {
+    Game::from_fen_with_variant(fen, Some(Arc::new(variant::Antichess))).unwrap()
+}
+
+#[test]
+fn test_captures_are_compulsory() {
+    let mut game = antichess("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    assert_eq!(
+        game.legal_turns(),
+        vec![Turn::new(Point(5, 4), Point(4, 5))]
+    );
+    assert_eq!(game.get_moves(&Point(5, 1)), None);
+}
+
+#[test]
+fn test_kings_are_not_royal() {
+    let mut game = antichess("8/8/8/8/8/8/3k4/4K3 w - - 0 1");
+    assert!(!game.is_checked());
+    assert_eq!(
+        game.turn(Point(5, 1), Point(4, 2)),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+}
+
+#[test]
+fn test_stalemate_wins() {
+    let mut game = antichess("8/8/8/8/p7/8/P7/8 w - - 0 1");
+    assert_eq!(
+        game.turn(Point(1, 2), Point(1, 3)),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+}
+
+#[test]
+fn test_promotion_to_king() {
+    let mut game = antichess("8/P7/8/8/8/8/8/7k w - - 0 1");
+    assert_eq!(game.play(&Turn::parse("a7a8k").unwrap()), TurnResult::Moved);
+    assert_eq!(
+        game.get_board()
+            .at_point(&Point(1, 8))
+            .map(|piece| piece.kind),
+        Some(Kind::King)
+    );
+}
+
+#[test]
+fn test_perft() {
+    let mut game = Game::antichess();
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+    );
+    assert_eq!(game.perft(1), 20);
+    assert_eq!(game.perft(2), 400);
+    assert_eq!(game.perft(3), 8067);
+    assert_eq!(game.perft(4), 153299);
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+fn atomic(fen: &str) -> Game {
+    Game::from_fen_with_variant(fen, Some(Arc::new(variant::Atomic))).unwrap()
+}
+
+#[test]
+fn test_explosion() {
+    let mut game = atomic("4k3/8/2p5/3pnb2/4P3/8/8/4K3 w - - 0 1");
+    assert_eq!(game.turn(Point(5, 4), Point(4, 5)), TurnResult::Moved);
+    assert_eq!(game.to_fen(), "4k3/8/2p5/5b2/8/8/8/4K3 b - - 0 1");
+
+    let board = game.get_board();
+    assert_eq!(board.graveyard[&Color::White].len(), 1);
+    assert_eq!(board.graveyard[&Color::Black].len(), 2);
+}
+
+#[test]
+fn test_kings_cannot_capture() {
+    let mut game = atomic("4k3/8/8/8/8/8/4p3/4K3 w - - 0 1");
+    let moves = game.get_moves(&Point(5, 1)).unwrap();
+    assert!(!moves.contains(&Point(5, 2)));
+    assert!(moves.contains(&Point(6, 2)));
+}
+
+#[test]
+fn test_exploding_the_king_wins_out_of_check() {
+    let mut game = atomic("6k1/5n2/8/7Q/8/8/8/r3K3 w - - 0 1");
+    assert!(game.is_checked());
+    // Besides blocking on d1, the queen may ignore the check entirely.
+    assert_eq!(
+        game.get_moves(&Point(8, 5)).unwrap().as_sorted(),
+        vec![Point(4, 1), Point(6, 7)]
+    );
+    assert_eq!(
+        game.turn(Point(8, 5), Point(6, 7)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    assert_eq!(game.get_board().find_king(&Color::Black), None);
+}
+
+#[test]
+fn test_touching_kings() {
+    let mut game = atomic("8/8/8/8/8/4k3/r3K3/8 w - - 0 1");
+    // The rook cannot take a king standing next to its own.
+    assert!(!game.is_checked());
+    assert!(game.get_moves(&Point(5, 2)).unwrap().contains(&Point(6, 2)));
+}
+
+#[test]
+fn test_perft() {
+    let mut game = Game::atomic();
+    assert_eq!(game.perft(1), 20);
+    assert_eq!(game.perft(2), 400);
+    assert_eq!(game.perft(3), 8902);
+    assert_eq!(game.perft(4), 197326);
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+#[test]
+fn test_minichess_start_positions() {
+    let mut gardner = Game::from_board(Board::gardner());
+    assert_eq!(gardner.to_fen(), "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1");
+    assert_eq!(gardner.perft(1), 7);
+    assert_eq!(gardner.perft(2), 53);
+
+    let mut los_alamos = Game::from_board(Board::los_alamos());
+    assert_eq!(
+        los_alamos.to_fen(),
+        "rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1"
+    );
+    assert_eq!(los_alamos.perft(1), 10);
+    assert_eq!(los_alamos.perft(2), 100);
+}
+
+#[test]
+fn test_custom_size_indexing() {
+    let mut board = Board::new(10, 8);
+    assert_eq!(board.current.len(), 80);
+    assert_eq!(board.index(&Point(10, 1)), 9);
+    assert_eq!(board.index(&Point(1, 2)), 10);
+
+    board.set(&Point(10, 8), Some(Piece::new(Color::Black, Kind::King)));
+    board.set(&Point(1, 1), Some(Piece::new(Color::White, Kind::King)));
+    assert!(board.is_in_bounds(&Point(10, 8)));
+    assert!(!board.is_in_bounds(&Point(11, 8)));
+    assert_eq!(
+        board.at_point(&Point(10, 8)).map(|piece| piece.kind),
+        Some(Kind::King)
+    );
+}
+
+#[test]
+fn test_wide_board_fen() {
+    let fen = "r3k4r/10/10/10/10/10/10/R3K4R w KQkq - 0 1";
+    let mut game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.board.width, 1..=10);
+    assert_eq!(game.to_fen(), fen);
+
+    // The rook slides all the way to the j file.
+    assert!(game
+        .get_moves(&Point(10, 1))
+        .unwrap()
+        .contains(&Point(10, 7)));
+    assert!(Game::from_fen("r3k4r/10/10/10/10/10/10/R3K4 w - - 0 1").is_none());
+}
+
+#[test]
+fn test_promotion_rank_follows_height() {
+    let mut game = Game::from_fen("k4/3P1/5/5/K4 w - - 0 1").unwrap();
+    assert_eq!(game.turn(Point(4, 4), Point(4, 5)), TurnResult::Promotion);
+    assert_eq!(game.promote(Kind::Queen), TurnResult::Checked);
+
+    let mut game = Game::from_fen("4k/5/5/1p3/K4 b - - 0 1").unwrap();
+    assert_eq!(game.turn(Point(2, 2), Point(2, 1)), TurnResult::Promotion);
+}
+
+#[test]
+fn test_no_double_step_on_minichess() {
+    let mut game = Game::from_board(Board::gardner());
+    assert_eq!(game.get_moves(&Point(3, 2)), Some(vec![Point(3, 3)]));
+
+    let mut game = Game::from_fen("rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1").unwrap();
+    assert_eq!(game.get_moves(&Point(3, 2)), Some(vec![Point(3, 3)]));
+}
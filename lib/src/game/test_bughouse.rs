@@ -0,0 +1,89 @@
+use super::bughouse::*;
+use super::*;
+use std::time::Duration;
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_captures_go_to_partner() {
+    let mut bughouse = Bughouse::new(60 * SECOND);
+
+    for notation in ["e2e4", "d7d5", "e4d5"].iter() {
+        let turn = Turn::parse(notation).unwrap();
+        assert_ne!(bughouse.play(0, &turn, SECOND), TurnResult::Failed);
+    }
+    assert!(bughouse.game(0).board.pockets[&Color::White].is_empty());
+    assert_eq!(
+        bughouse.game(1).board.pockets[&Color::Black],
+        vec![Kind::Pawn]
+    );
+    assert_eq!(
+        bughouse.game(1).to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[p] w KQkq - 0 1"
+    );
+
+    // Black on the second board has to wait for White to move first.
+    let drop = Turn::drop(Kind::Pawn, Point(5, 5));
+    assert_eq!(bughouse.play(1, &drop, SECOND), TurnResult::Failed);
+    let turn = Turn::parse("g1f3").unwrap();
+    assert_eq!(bughouse.play(1, &turn, SECOND), TurnResult::Moved);
+    assert_eq!(bughouse.play(1, &drop, SECOND), TurnResult::Moved);
+    assert!(bughouse.game(1).board.pockets[&Color::Black].is_empty());
+
+    assert_eq!(bughouse.time_left(0, Color::White), 58 * SECOND);
+    assert_eq!(bughouse.time_left(1, Color::Black), 59 * SECOND);
+}
+
+#[test]
+fn test_mate_decides_both_boards() {
+    let mut bughouse = Bughouse::new(60 * SECOND);
+
+    for notation in ["f2f3", "e7e5", "g2g4"].iter() {
+        let turn = Turn::parse(notation).unwrap();
+        assert_ne!(bughouse.play(1, &turn, SECOND), TurnResult::Failed);
+    }
+    let mate = Turn::parse("d8h4").unwrap();
+    assert_eq!(
+        bughouse.play(1, &mate, SECOND),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+    assert_eq!(bughouse.get_result(), Some(TeamResult::Win(Team::A)));
+
+    let turn = Turn::parse("e2e4").unwrap();
+    assert_eq!(bughouse.play(0, &turn, SECOND), TurnResult::Failed);
+}
+
+#[test]
+fn test_flag_fall() {
+    let mut bughouse = Bughouse::new(10 * SECOND);
+    assert_eq!(bughouse.flag(0, 9 * SECOND), None);
+
+    let turn = Turn::parse("e2e4").unwrap();
+    assert_eq!(
+        bughouse.play(0, &turn, 10 * SECOND),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+    assert_eq!(bughouse.get_result(), Some(TeamResult::Win(Team::B)));
+    assert_eq!(bughouse.time_left(0, Color::White), Duration::ZERO);
+}
+
+#[test]
+fn test_promotion_capture() {
+    let games = [
+        Game::from_fen("1r2k3/P7/8/8/8/8/8/4K3[] w - - 0 1").unwrap(),
+        Game::new(),
+    ];
+    let mut bughouse = Bughouse::from_games(games, 60 * SECOND);
+
+    assert_eq!(
+        bughouse.turn(0, Point(1, 7), Point(2, 8), SECOND),
+        TurnResult::Promotion
+    );
+    assert!(bughouse.game(1).board.pockets[&Color::Black].is_empty());
+    assert_eq!(bughouse.promote(0, Kind::Queen), TurnResult::Checked);
+    assert_eq!(
+        bughouse.game(1).board.pockets[&Color::Black],
+        vec![Kind::Rook]
+    );
+    assert_eq!(bughouse.time_left(0, Color::White), 59 * SECOND);
+}
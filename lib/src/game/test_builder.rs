@@ -0,0 +1,121 @@
+use super::builder::{BoardBuilder, Problem};
+use super::*;
+
+fn kings() -> BoardBuilder {
+    let mut builder = BoardBuilder::default();
+    builder
+        .place(Point(5, 1), Piece::new(Color::White, Kind::King))
+        .place(Point(5, 8), Piece::new(Color::Black, Kind::King));
+    builder
+}
+
+#[test]
+fn test_build() {
+    let mut builder = kings();
+    builder
+        .place(Point(1, 1), Piece::new(Color::White, Kind::Rook))
+        .place(Point(8, 1), Piece::new(Color::White, Kind::Rook))
+        .place(Point(4, 5), Piece::new(Color::Black, Kind::Pawn))
+        .place(Point(5, 5), Piece::new(Color::White, Kind::Pawn))
+        .castling(Color::White, Point(8, 1))
+        .enpassant(Some(Point(4, 6)));
+    assert_eq!(builder.validate(), vec![]);
+
+    let mut game = builder.build().unwrap();
+    assert_eq!(game.to_fen(), "4k3/8/8/3pP3/8/8/8/R3K2R w K d6 0 1");
+    assert_eq!(game.turn(Point(5, 5), Point(4, 6)), TurnResult::Moved);
+    assert_eq!(game.get_moves(&Point(5, 8)).unwrap().len(), 4);
+
+    let game = Game::from_fen("r3k2r/8/8/8/4Pp2/8/8/R3K2R b Kq e3 0 1").unwrap();
+    let rebuilt = BoardBuilder::from_game(&game).build().unwrap();
+    assert_eq!(rebuilt.to_fen(), "r3k2r/8/8/8/4Pp2/8/8/R3K2R b Kq e3 0 1");
+}
+
+#[test]
+fn test_remove_and_clear() {
+    let mut builder = BoardBuilder::from_game(&Game::new());
+    builder.remove(Point(8, 1)).remove(Point(7, 1));
+    assert_eq!(
+        builder.validate(),
+        vec![Problem::Castling(Color::White, Point(8, 1))]
+    );
+
+    builder.clear();
+    assert_eq!(
+        builder.validate(),
+        vec![
+            Problem::MissingKing(Color::White),
+            Problem::MissingKing(Color::Black)
+        ]
+    );
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn test_problems() {
+    let mut builder = kings();
+    builder
+        .place(Point(4, 4), Piece::new(Color::White, Kind::King))
+        .place(Point(1, 8), Piece::new(Color::White, Kind::Pawn))
+        .place(Point(9, 1), Piece::new(Color::White, Kind::Queen))
+        .to_move(Color::White)
+        .castling(Color::Black, Point(8, 8))
+        .enpassant(Some(Point(3, 6)));
+    assert_eq!(
+        builder.validate(),
+        vec![
+            Problem::OutOfBounds(Point(9, 1)),
+            Problem::PawnOnBackRank(Point(1, 8)),
+            Problem::ExtraKing(Color::White),
+            Problem::Castling(Color::Black, Point(8, 8)),
+            Problem::EnPassant(Point(3, 6)),
+        ]
+    );
+
+    let mut builder = kings();
+    builder
+        .place(Point(5, 2), Piece::new(Color::White, Kind::Rook))
+        .to_move(Color::Black);
+    assert_eq!(builder.validate(), vec![]);
+    builder.to_move(Color::White);
+    assert_eq!(
+        builder.validate(),
+        vec![Problem::OpponentInCheck(Color::Black)]
+    );
+}
+
+#[test]
+fn test_rights_off_the_board() {
+    let mut builder = kings();
+    builder
+        .castling(Color::White, Point(9, 1))
+        .castling(Color::White, Point(-1, 1))
+        .enpassant(Some(Point(12, 6)));
+    assert_eq!(
+        builder.validate(),
+        vec![
+            Problem::Castling(Color::White, Point(9, 1)),
+            Problem::Castling(Color::White, Point(-1, 1)),
+            Problem::EnPassant(Point(12, 6)),
+        ]
+    );
+
+    // Pawns on the Gardner board never step two squares.
+    let mut builder = BoardBuilder::new(5, 5).unwrap();
+    builder
+        .place(Point(5, 1), Piece::new(Color::White, Kind::King))
+        .place(Point(5, 5), Piece::new(Color::Black, Kind::King))
+        .place(Point(2, 2), Piece::new(Color::Black, Kind::Pawn))
+        .enpassant(Some(Point(2, 3)));
+    assert_eq!(builder.validate(), vec![Problem::EnPassant(Point(2, 3))]);
+}
+
+#[test]
+fn test_board_sizes() {
+    assert!(BoardBuilder::new(10, 8).is_some());
+    assert!(BoardBuilder::new(6, 6).is_some());
+    assert!(BoardBuilder::new(4, 3).is_none());
+    assert!(BoardBuilder::new(0, 8).is_none());
+    assert!(BoardBuilder::new(-8, -8).is_none());
+    assert!(BoardBuilder::new(8, 10).is_none());
+}
@@ -0,0 +1,107 @@
+use super::*;
+use crate::pieces::custom::{ARCHBISHOP, CHANCELLOR};
+
+const START: &str = "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+
+#[test]
+fn test_start_position() {
+    let mut game = Game::capablanca();
+    assert_eq!(game.to_fen(), START);
+    assert_eq!(Game::from_fen(START).unwrap().to_fen(), START);
+    assert!(!game.get_board().chess960);
+
+    assert_eq!(game.perft(1), 28);
+    assert_eq!(game.perft(2), 784);
+    assert_eq!(game.perft(3), 25228);
+}
+
+#[test]
+fn test_compound_pieces() {
+    let mut game = Game::from_fen("5k4/10/10/10/4A5/10/10/C4K4 w - - 0 1").unwrap();
+
+    let archbishop = game.get_moves(&Point(5, 4)).unwrap();
+    assert!(archbishop.contains(&Point(9, 8)));
+    assert!(archbishop.contains(&Point(6, 6)));
+    assert!(!archbishop.contains(&Point(5, 5)));
+
+    let chancellor = game.get_moves(&Point(1, 1)).unwrap();
+    assert!(chancellor.contains(&Point(1, 8)));
+    assert!(chancellor.contains(&Point(2, 3)));
+    assert!(!chancellor.contains(&Point(2, 2)));
+
+    let game = Game::from_fen("5k4/10/4c5/10/10/10/10/5K4 w - - 0 1").unwrap();
+    assert!(!game.is_checked());
+    let game = Game::from_fen("5k4/10/10/10/10/10/4a5/5K4 w - - 0 1").unwrap();
+    assert!(game.is_checked());
+    let game = Game::from_fen("5k4/10/10/10/10/4c5/10/5K4 w - - 0 1").unwrap();
+    assert!(game.is_checked());
+}
+
+#[test]
+fn test_castling() {
+    let mut game = Game::from_fen("r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1").unwrap();
+
+    let moves = game.get_moves(&Point(6, 1)).unwrap();
+    assert!(moves.contains(&Point(9, 1)));
+    assert!(moves.contains(&Point(3, 1)));
+    assert!(!moves.contains(&Point(8, 1)));
+
+    assert_eq!(game.turn(Point(6, 1), Point(9, 1)), TurnResult::Moved);
+    assert_eq!(
+        game.get_board()
+            .at_point(&Point(8, 1))
+            .map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.get_board().at_point(&Point(10, 1)), None);
+
+    assert_eq!(game.turn(Point(6, 8), Point(3, 8)), TurnResult::Moved);
+    assert_eq!(
+        game.get_board()
+            .at_point(&Point(4, 8))
+            .map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.to_fen(), "2kr5r/10/10/10/10/10/10/R6RK1 w - - 2 2");
+}
+
+#[test]
+fn test_promotion_options() {
+    let fen = "5k4/1P8/10/10/10/10/10/5K4 w - - 0 1";
+    let promotions = |game: &mut Game| -> Vec<Kind> {
+        game.legal_turns()
+            .iter()
+            .filter_map(|turn| turn.promotion)
+            .collect()
+    };
+
+    let mut game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.board.promotions, Board::capablanca().promotions);
+    let kinds = promotions(&mut game);
+    assert_eq!(kinds.len(), 6);
+    assert!(kinds.contains(&ARCHBISHOP));
+    assert!(kinds.contains(&CHANCELLOR));
+
+    assert_eq!(game.turn(Point(2, 7), Point(2, 8)), TurnResult::Promotion);
+    assert_eq!(game.promote(Kind::King), TurnResult::Failed);
+    assert_eq!(game.promote(CHANCELLOR), TurnResult::Checked);
+}
+
+#[test]
+fn test_promotion_after_fen() {
+    let mut game = Game::from_fen("5k4/P9/10/10/10/10/10/5K4 w - - 0 1").unwrap();
+    assert_eq!(game.play(&Turn::parse("a7a8a").unwrap()), TurnResult::Moved);
+    assert_eq!(
+        game.get_board()
+            .at_point(&Point(1, 8))
+            .map(|piece| piece.kind),
+        Some(ARCHBISHOP)
+    );
+
+    // The standard board keeps the standard pieces.
+    let mut game = Game::from_fen("5k2/P7/8/8/8/8/8/5K2 w - - 0 1").unwrap();
+    assert_eq!(
+        game.play(&Turn::parse("a7a8a").unwrap()),
+        TurnResult::Failed
+    );
+}
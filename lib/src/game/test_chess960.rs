@@ -0,0 +1,128 @@
+use super::*;
+use std::collections::HashSet;
+
+#[test]
+fn test_start_positions() {
+    assert_eq!(Game::chess960(518).unwrap().to_fen(), Game::new().to_fen());
+    assert_eq!(
+        Game::chess960(0).unwrap().to_fen(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    );
+    assert_eq!(
+        Game::chess960(959).unwrap().to_fen(),
+        "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+    );
+    assert!(Game::chess960(960).is_none());
+
+    let mut placements = HashSet::new();
+    for index in 0..960 {
+        let board = Board::chess960(index).unwrap();
+        let row: Vec<Kind> = (1..=8)
+            .map(|x| board.at_point(&Point(x, 1)).unwrap().kind)
+            .collect();
+
+        let files = |kind: Kind| -> Vec<usize> { (0..8).filter(|x| row[*x] == kind).collect() };
+        let bishops = files(Kind::Bishop);
+        let rooks = files(Kind::Rook);
+        let king = files(Kind::King)[0];
+        assert_ne!(bishops[0] % 2, bishops[1] % 2);
+        assert!(rooks[0] < king && king < rooks[1]);
+        assert!(placements.insert(row));
+    }
+}
+
+#[test]
+fn test_castling_onto_rook() {
+    let mut game = Game::from_fen("1r4kr/5p2/8/8/8/8/5P2/1R4KR w HBhb - 0 1").unwrap();
+    assert!(game.board.chess960);
+
+    let moves = game.get_moves(&Point(7, 1)).unwrap();
+    assert!(moves.contains(&Point(8, 1)));
+    assert!(moves.contains(&Point(2, 1)));
+
+    // The king already stands on g1, so only the rook moves.
+    assert_eq!(game.turn(Point(7, 1), Point(8, 1)), TurnResult::Moved);
+    assert_eq!(
+        game.board.at_point(&Point(7, 1)).map(|piece| piece.kind),
+        Some(Kind::King)
+    );
+    assert_eq!(
+        game.board.at_point(&Point(6, 1)).map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.board.at_point(&Point(8, 1)), None);
+
+    assert_eq!(game.turn(Point(7, 8), Point(2, 8)), TurnResult::Moved);
+    assert_eq!(
+        game.board.at_point(&Point(3, 8)).map(|piece| piece.kind),
+        Some(Kind::King)
+    );
+    assert_eq!(
+        game.board.at_point(&Point(4, 8)).map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.board.at_point(&Point(2, 8)), None);
+    assert_eq!(game.board.at_point(&Point(7, 8)), None);
+    assert_eq!(game.to_fen(), "2kr3r/5p2/8/8/8/8/5P2/1R3RK1 w - - 2 2");
+}
+
+#[test]
+fn test_no_castling_when_blocked_or_attacked() {
+    // The knight on c1 stands where the king has to go.
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/RKN5 w A - 0 1").unwrap();
+    assert!(!game.get_moves(&Point(2, 1)).unwrap().contains(&Point(1, 1)));
+
+    // The rook on d8 covers d1, which the king passes on its way to g1.
+    let mut game = Game::from_fen("3rk3/8/8/8/8/8/8/2K4R w H - 0 1").unwrap();
+    assert!(!game.get_moves(&Point(3, 1)).unwrap().contains(&Point(8, 1)));
+
+    // An attacked rook may still castle.
+    let mut game = Game::from_fen("4k2r/8/8/8/8/8/8/2K4R w H - 0 1").unwrap();
+    assert!(game.get_moves(&Point(3, 1)).unwrap().contains(&Point(8, 1)));
+
+    // The rook on b1 shields the king from the a1 rook until it castles.
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/rRK5 w B - 0 1").unwrap();
+    assert!(!game.get_moves(&Point(3, 1)).unwrap().contains(&Point(2, 1)));
+}
+
+#[test]
+fn test_castling_fields() {
+    let game = Game::from_fen("rk2r3/8/8/8/8/8/8/RK2R3 w EAea - 0 1").unwrap();
+    assert!(game.board.chess960);
+    assert_eq!(game.to_fen(), "rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1");
+
+    // Only the inner rook keeps its rights, so X-FEN needs the file.
+    let fen = "rrk4r/8/8/8/8/8/8/RRK4R w Bk - 0 1";
+    let game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.to_fen(), fen);
+    assert_eq!(
+        Game::from_fen("1k6/8/8/8/8/8/8/RRK4R w B - 0 1")
+            .unwrap()
+            .to_fen(),
+        "1k6/8/8/8/8/8/8/RRK4R w B - 0 1"
+    );
+
+    // K and Q mean the outermost rook, however many stand nearer the king.
+    let fen = "4k3/8/8/8/8/8/8/4KRR1 w K - 0 1";
+    let game = Game::from_fen(fen).unwrap();
+    assert_eq!(
+        game.board.castling_rook(&Color::White, 1),
+        Some(Point(7, 1))
+    );
+    assert_eq!(game.to_fen(), fen);
+
+    assert!(Game::from_fen("4k3/8/8/8/8/8/8/RK6 w C - 0 1").is_none());
+    assert!(!Game::from_fen(KIWIPETE).unwrap().board.chess960);
+}
+
+#[test]
+fn test_perft() {
+    let mut game =
+        Game::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+            .unwrap();
+    assert_eq!(game.perft(1), 21);
+    assert_eq!(game.perft(2), 528);
+    assert_eq!(game.perft(3), 12189);
+}
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
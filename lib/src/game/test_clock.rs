@@ -0,0 +1,166 @@
+use super::clock::{Bonus, Clock, ManualTime, Stage, TimeControl};
+use super::*;
+use std::time::Duration;
+
+fn secs(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+fn clocked(fen: &str, control: TimeControl) -> (Game, ManualTime) {
+    let time = ManualTime::default();
+    let mut game = Game::from_fen(fen).unwrap();
+    game.set_clock(Some(Clock::with_source(control, Arc::new(time.clone()))));
+    (game, time)
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        TimeControl::parse("3+2"),
+        Some(TimeControl::fischer(secs(180), secs(2)))
+    );
+    assert_eq!(
+        TimeControl::parse("15d5"),
+        Some(TimeControl::delay(secs(900), secs(5)))
+    );
+    assert_eq!(
+        TimeControl::parse("0.5b3"),
+        Some(TimeControl::bronstein(secs(30), secs(3)))
+    );
+    let increment = Bonus::Increment(secs(30));
+    assert_eq!(
+        TimeControl::parse("40/90+30:30+30"),
+        Some(TimeControl(vec![
+            Stage {
+                moves: Some(40),
+                time: secs(5400),
+                bonus: increment
+            },
+            Stage {
+                moves: None,
+                time: secs(1800),
+                bonus: increment
+            },
+        ]))
+    );
+    for control in ["3+2", "15d5", "0.5b3", "40/90+30:30+30", "5"].iter() {
+        assert_eq!(TimeControl::parse(control).unwrap().to_string(), *control);
+    }
+    assert_eq!(TimeControl::parse("0/90"), None);
+    assert_eq!(TimeControl::parse("5+"), None);
+    assert_eq!(TimeControl::parse(""), None);
+
+    // Anything longer than a day is turned away rather than overflowing.
+    assert_eq!(TimeControl::parse("1e300"), None);
+    assert_eq!(TimeControl::parse("-5"), None);
+    assert_eq!(TimeControl::parse("NaN"), None);
+    assert_eq!(TimeControl::parse("1441"), None);
+    assert!(TimeControl::parse("1440").is_some());
+    assert_eq!(TimeControl::parse("5+18446744073709551615"), None);
+    assert_eq!(TimeControl::parse("5d86401"), None);
+    assert_eq!(TimeControl::parse("40/90:30b86401"), None);
+}
+
+#[test]
+fn test_bonuses() {
+    let time = ManualTime::default();
+    let controls = [
+        (TimeControl::sudden_death(secs(60)), secs(50)),
+        (TimeControl::fischer(secs(60), secs(2)), secs(52)),
+        (TimeControl::bronstein(secs(60), secs(2)), secs(52)),
+        (TimeControl::bronstein(secs(60), secs(20)), secs(60)),
+        (TimeControl::delay(secs(60), secs(2)), secs(52)),
+        (TimeControl::delay(secs(60), secs(20)), secs(60)),
+    ];
+    for (control, expected) in controls.iter() {
+        let mut clock = Clock::with_source(control.clone(), Arc::new(time.clone()));
+        clock.start(Color::White);
+        time.advance(secs(10));
+        clock.press(&Color::White);
+        assert_eq!(clock.remaining(&Color::White), *expected);
+        assert_eq!(clock.running(), Some(Color::Black));
+        assert_eq!(clock.remaining(&Color::Black), secs(60));
+    }
+}
+
+#[test]
+fn test_bonus_saturates() {
+    let time = ManualTime::default();
+    let control = TimeControl::fischer(Duration::MAX, secs(2));
+    let mut clock = Clock::with_source(control, Arc::new(time.clone()));
+    clock.start(Color::White);
+    clock.press(&Color::White);
+    assert_eq!(clock.remaining(&Color::White), Duration::MAX);
+}
+
+#[test]
+fn test_stages() {
+    let time = ManualTime::default();
+    let control = TimeControl::parse("2/1:1/0.5").unwrap();
+    let mut clock = Clock::with_source(control, Arc::new(time.clone()));
+    clock.start(Color::White);
+    for _ in 0..2 {
+        time.advance(secs(10));
+        clock.press(&Color::White);
+        clock.press(&Color::Black);
+    }
+    assert_eq!(clock.remaining(&Color::White), secs(70));
+    assert_eq!(clock.remaining(&Color::Black), secs(90));
+
+    // The last stage has a move count, so it starts over after every move.
+    clock.press(&Color::White);
+    clock.press(&Color::Black);
+    assert_eq!(clock.remaining(&Color::White), secs(100));
+}
+
+#[test]
+fn test_turns_run_the_clock() {
+    let (mut game, time) = clocked(
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        TimeControl::fischer(secs(60), secs(1)),
+    );
+    time.advance(secs(5));
+    assert_eq!(game.turn(Point(5, 2), Point(5, 4)), TurnResult::Moved);
+    let clock = game.get_clock().unwrap();
+    assert_eq!(clock.remaining(&Color::White), secs(56));
+    assert_eq!(clock.running(), Some(Color::Black));
+
+    // Black keeps the time it spent before the move was taken back.
+    time.advance(secs(3));
+    assert!(game.undo());
+    assert_eq!(game.get_clock().unwrap().running(), Some(Color::White));
+    assert_eq!(game.get_clock().unwrap().remaining(&Color::Black), secs(57));
+    time.advance(secs(2));
+    assert_eq!(game.get_clock().unwrap().remaining(&Color::White), secs(54));
+}
+
+#[test]
+fn test_flag_fall() {
+    // White still has a pawn, so Black losing on time loses the game.
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1";
+    let (mut game, time) = clocked(fen, TimeControl::sudden_death(secs(60)));
+    time.advance(secs(30));
+    assert_eq!(game.check_time(), None);
+    time.advance(secs(30));
+    // The move is refused, and asking for the time ends the game.
+    assert_eq!(game.turn(Point(5, 8), Point(4, 8)), TurnResult::Failed);
+    assert_eq!(game.get_history(), vec![]);
+    assert_eq!(game.get_result(), None);
+    assert_eq!(game.check_time(), Some(EndResult::Win(Color::White)));
+    assert_eq!(game.get_result(), Some(EndResult::Win(Color::White)));
+    assert_eq!(game.check_time(), None);
+    assert_eq!(game.turn(Point(5, 8), Point(4, 8)), TurnResult::Failed);
+
+    // A lone knight cannot mate a lone king, so running out is a draw.
+    let fen = "4k3/8/8/8/8/8/8/4KN2 b - - 0 1";
+    let (mut game, time) = clocked(fen, TimeControl::sudden_death(secs(60)));
+    time.advance(secs(61));
+    assert_eq!(game.check_time(), Some(EndResult::Tie));
+    assert_eq!(game.get_clock().unwrap().running(), None);
+
+    // It can once the flagged side has a pawn to block its own king.
+    let fen = "4k3/4p3/8/8/8/8/8/4KN2 b - - 0 1";
+    let (mut game, time) = clocked(fen, TimeControl::sudden_death(secs(60)));
+    time.advance(secs(61));
+    assert_eq!(game.check_time(), Some(EndResult::Win(Color::White)));
+}
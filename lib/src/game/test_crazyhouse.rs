@@ -0,0 +1,112 @@
+use super::*;
+
+#[test]
+fn test_captures_fill_pockets() {
+    let mut game = Game::crazyhouse();
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"
+    );
+
+    for notation in ["e2e4", "d7d5", "e4d5"].iter() {
+        assert_ne!(
+            game.play(&Turn::parse(notation).unwrap()),
+            TurnResult::Failed
+        );
+    }
+    assert_eq!(game.board.pockets[&Color::White], vec![Kind::Pawn]);
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR[P] b KQkq - 0 2"
+    );
+
+    assert_eq!(game.play(&Turn::parse("d8d5").unwrap()), TurnResult::Moved);
+    assert_eq!(game.play(&Turn::parse("P@e6").unwrap()), TurnResult::Moved);
+    assert!(game.board.pockets[&Color::White].is_empty());
+    assert_eq!(
+        game.to_fen(),
+        "rnb1kbnr/ppp1pppp/4P3/3q4/8/8/PPPP1PPP/RNBQKBNR[p] b KQkq - 0 3"
+    );
+
+    game.undo();
+    assert_eq!(game.board.pockets[&Color::White], vec![Kind::Pawn]);
+}
+
+#[test]
+fn test_promoted_pieces_are_demoted() {
+    let fen = "4k3/8/8/8/8/8/4K3/Q~6r[] b - - 0 1";
+    let mut game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.to_fen(), fen);
+
+    assert_eq!(game.turn(Point(8, 1), Point(1, 1)), TurnResult::Moved);
+    assert_eq!(game.board.pockets[&Color::Black], vec![Kind::Pawn]);
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/4K3/r7[p] w - - 0 2");
+
+    let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+    assert_eq!(
+        game.play(&Turn::parse("b7b8q").unwrap()),
+        TurnResult::Checked
+    );
+    assert_eq!(game.to_fen(), "1Q~2k3/8/8/8/8/8/8/4K3[] b - - 0 1");
+}
+
+#[test]
+fn test_drop_rules() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1").unwrap();
+
+    let drops = game.get_drops(Kind::Pawn).unwrap();
+    assert_eq!(drops.len(), 48);
+    assert!(drops.iter().all(|point| point.1 != 1 && point.1 != 8));
+    assert_eq!(game.drop_piece(Kind::Pawn, Point(1, 8)), TurnResult::Failed);
+    assert_eq!(
+        game.drop_piece(Kind::Knight, Point(1, 5)),
+        TurnResult::Failed
+    );
+    assert_eq!(game.drop_piece(Kind::Pawn, Point(5, 8)), TurnResult::Failed);
+
+    // A pawn dropped on its starting rank may still advance two squares.
+    assert_eq!(game.drop_piece(Kind::Pawn, Point(1, 2)), TurnResult::Moved);
+    assert_eq!(
+        game.drop_piece(Kind::Knight, Point(4, 3)),
+        TurnResult::Checked
+    );
+    assert_eq!(game.turn(Point(5, 1), Point(5, 2)), TurnResult::Moved);
+    assert_eq!(game.turn(Point(5, 8), Point(4, 8)), TurnResult::Moved);
+    assert_eq!(
+        game.get_moves(&Point(1, 2)).unwrap().as_sorted(),
+        vec![Point(1, 3), Point(1, 4)]
+    );
+}
+
+#[test]
+fn test_drop_blocks_check() {
+    let fen = "k7/8/8/8/8/8/r5PP/7K[] b - - 0 1";
+    let mut game = Game::from_fen(fen).unwrap();
+    assert_eq!(
+        game.play(&Turn::parse("a2a1").unwrap()),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+
+    let mut game = Game::from_fen("k7/8/8/8/8/8/r5PP/7K[N] b - - 0 1").unwrap();
+    assert_eq!(
+        game.play(&Turn::parse("a2a1").unwrap()),
+        TurnResult::Checked
+    );
+    let turns = game.legal_turns();
+    assert_eq!(turns.len(), 6);
+    assert!(turns
+        .iter()
+        .all(|turn| turn.drop == Some(Kind::Knight) && turn.target.1 == 1));
+}
+
+#[test]
+fn test_drop_notation() {
+    let turn = Turn::parse("N@f3").unwrap();
+    assert_eq!(turn, Turn::drop(Kind::Knight, Point(6, 3)));
+    assert_eq!(turn.to_string(), "N@f3");
+    assert_eq!(Turn::parse("K@f3"), None);
+    assert_eq!(Turn::parse("N@f"), None);
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+    assert_eq!(game.perft(1), 67);
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn test_two_phase_turn() {
+    let mut game = Game::duck_chess();
+    assert_eq!(game.turn(Point(5, 2), Point(5, 4)), TurnResult::Duck);
+    assert!(game.is_awaiting_duck());
+    assert_eq!(game.get_duck_squares().unwrap().len(), 32);
+    assert_eq!(game.turn(Point(4, 2), Point(4, 4)), TurnResult::Failed);
+    assert!(game.legal_turns().is_empty());
+
+    assert_eq!(game.place_duck(Point(5, 4)), TurnResult::Failed);
+    assert_eq!(game.place_duck(Point(5, 6)), TurnResult::Moved);
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/pppppppp/4*3/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+
+    // The duck blocks the pawn in front of it for both steps.
+    assert_eq!(game.get_moves(&Point(5, 7)), None);
+    assert_eq!(game.turn(Point(4, 7), Point(4, 5)), TurnResult::Duck);
+    assert_eq!(game.place_duck(Point(5, 6)), TurnResult::Failed);
+    assert_eq!(game.place_duck(Point(4, 3)), TurnResult::Moved);
+    assert_eq!(
+        game.get_history().last().map(|turn| turn.to_string()),
+        Some("d7d5,d3".to_string())
+    );
+
+    game.undo();
+    assert!(!game.is_awaiting_duck());
+    assert_eq!(game.get_board().duck, Some(Point(5, 6)));
+}
+
+#[test]
+fn test_duck_notation() {
+    let turn = Turn::parse("e2e4,e6").unwrap();
+    assert_eq!(turn.duck, Some(Point(5, 6)));
+    assert_eq!(turn.to_string(), "e2e4,e6");
+    assert_eq!(Turn::parse("P@e4,e6").unwrap().to_string(), "P@e4,e6");
+    assert_eq!(Turn::parse("e2e4,"), None);
+
+    let mut game = Game::duck_chess();
+    assert_eq!(game.play(&Turn::parse("e2e4").unwrap()), TurnResult::Failed);
+    assert_eq!(game.to_fen(), Game::new().to_fen());
+    assert_eq!(game.play(&turn), TurnResult::Moved);
+    // Black loses both steps of the e pawn, and the duck has to leave e6.
+    assert_eq!(game.perft(1), 18 * 31);
+}
+
+#[test]
+fn test_duck_blocks_and_kings_are_taken() {
+    let fen = "4k3/8/8/8/8/8/8/R1*1K3 w - - 0 1";
+    let mut game = Game::from_fen(fen).unwrap();
+    game.set_variant(Some(Arc::new(variant::DuckChess)));
+    assert_eq!(game.to_fen(), fen);
+    let moves = game.get_moves(&Point(1, 1)).unwrap();
+    assert!(moves.contains(&Point(2, 1)));
+    assert!(!moves.contains(&Point(3, 1)));
+    assert!(!moves.contains(&Point(4, 1)));
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+    game.set_variant(Some(Arc::new(variant::DuckChess)));
+    assert_eq!(
+        game.turn(Point(5, 1), Point(5, 8)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+}
+
+#[test]
+fn test_perft() {
+    let mut game = Game::duck_chess();
+    assert_eq!(game.perft(1), 640);
+}
@@ -0,0 +1,83 @@
+use super::*;
+use crate::pieces::custom;
+
+fn camel() -> Kind {
+    custom::define("camel", 'l', custom::symmetric(Point(3, 1), false)).unwrap()
+}
+
+fn nightrider() -> Kind {
+    custom::define("nightrider", 'h', custom::symmetric(Point(2, 1), true)).unwrap()
+}
+
+#[test]
+fn test_custom_piece_moves() {
+    camel();
+    nightrider();
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/L3K2H w - - 0 1").unwrap();
+    assert_eq!(
+        game.get_moves(&Point(1, 1)).unwrap().as_sorted(),
+        vec![Point(2, 4), Point(4, 2)]
+    );
+
+    // The nightrider keeps jumping until it leaves the board.
+    let moves = game.get_moves(&Point(8, 1)).unwrap();
+    for target in [
+        Point(7, 3),
+        Point(6, 5),
+        Point(5, 7),
+        Point(6, 2),
+        Point(4, 3),
+    ]
+    .iter()
+    {
+        assert!(moves.contains(target), "{:?}", target);
+    }
+    assert!(!moves.contains(&Point(4, 9)));
+}
+
+#[test]
+fn test_custom_piece_gives_check() {
+    camel();
+    nightrider();
+
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/1L2K3 b - - 0 1").unwrap();
+    assert!(!game.is_checked());
+    let game = Game::from_fen("8/8/8/8/2k5/8/8/1L2K3 b - - 0 1").unwrap();
+    assert!(game.is_checked());
+
+    // A nightrider checks from far away unless something stands in between.
+    let game = Game::from_fen("8/8/8/8/6k1/8/8/H3K3 b - - 0 1").unwrap();
+    assert!(game.is_checked());
+    let game = Game::from_fen("8/8/8/8/6k1/4p3/8/H3K3 b - - 0 1").unwrap();
+    assert!(!game.is_checked());
+
+    // The king may not step onto a square the camel covers.
+    let mut game = Game::from_fen("8/8/8/8/8/3k4/8/L6K b - - 0 1").unwrap();
+    let moves = game.get_moves(&Point(4, 3)).unwrap();
+    assert!(!moves.contains(&Point(4, 2)));
+    assert_eq!(moves.len(), 7);
+}
+
+#[test]
+fn test_custom_piece_notation() {
+    let camel = camel();
+
+    let fen = "4k3/8/8/8/8/8/8/L3K2l w - - 0 1";
+    assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+
+    let turn = Turn::parse("b7b8l").unwrap();
+    assert_eq!(turn.promotion, Some(camel));
+    assert_eq!(turn.to_string(), "b7b8l");
+
+    let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(game.play(&turn), TurnResult::Failed);
+    game.board.promotions.push(camel);
+    assert_eq!(game.play(&turn), TurnResult::Moved);
+    assert_eq!(
+        game.get_board()
+            .at_point(&Point(2, 8))
+            .map(|piece| piece.kind),
+        Some(camel)
+    );
+}
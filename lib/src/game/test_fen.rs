@@ -0,0 +1,70 @@
+use super::*;
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn test_start_position_round_trip() {
+    assert_eq!(Game::new().to_fen(), START);
+    assert_eq!(Game::from_fen(START).unwrap().to_fen(), START);
+}
+
+#[test]
+fn test_fen_round_trip() {
+    for fen in [
+        KIWIPETE,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b Kq d6 3 12",
+    ]
+    .iter()
+    {
+        assert_eq!(&Game::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn test_fen_defaults_missing_fields() {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+    assert_eq!(game.color, Color::White);
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn test_invalid_fen() {
+    assert!(Game::from_fen("").is_none());
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_none());
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_none());
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1").is_none());
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_none());
+    assert!(Game::from_fen("rnbqkbn1/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_none());
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1").is_none());
+    // Boards of no known size, and positions without one king per side.
+    assert!(Game::from_fen("8/8/8 w - - 0 1").is_none());
+    assert!(Game::from_fen("Kk w - - 0 1").is_none());
+    assert!(Game::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_none());
+    assert!(Game::from_fen("4k3/8/8/8/8/8/8/3KK3 w - - 0 1").is_none());
+    // Digit runs wider than any board are refused before they are spelled
+    // out, however long.
+    assert!(Game::from_fen("1000000000/8/8/8/8/8/8/8 w - - 0 1").is_none());
+    let digits = "9".repeat(40);
+    assert!(Game::from_fen(&format!("4k3/{}/8/8/8/8/8/4K3 w - - 0 1", digits)).is_none());
+    assert!(Game::from_fen("4k3/11/8/8/8/8/8/4K3 w - - 0 1").is_none());
+}
+
+#[test]
+fn test_enpassant_from_fen() {
+    let mut game =
+        Game::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+
+    assert_eq!(
+        game.get_moves(&Point(5, 5)).unwrap().as_sorted(),
+        vec![Point(5, 6), Point(6, 6)].as_sorted()
+    );
+    assert_eq!(game.turn(Point(5, 5), Point(6, 6)), TurnResult::Moved);
+    assert_eq!(game.get_board().at_point(&Point(6, 5)), None);
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+    );
+}
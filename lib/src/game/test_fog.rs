@@ -0,0 +1,98 @@
+use super::kriegspiel::*;
+use super::*;
+
+#[test]
+fn test_view() {
+    let game = Game::new();
+    assert_eq!(game.visible(&Color::White).len(), 32);
+
+    let view = game.view(&Color::White);
+    let pieces: Vec<Piece> = view.current.iter().flatten().copied().collect();
+    assert_eq!(pieces.len(), 16);
+    assert!(pieces.iter().all(|piece| piece.color == Color::White));
+
+    // A blocked pawn still sees what is in its way.
+    let game = Game::from_fen("4k3/8/8/8/4p3/4P3/8/4K3 w - - 0 1").unwrap();
+    let view = game.view(&Color::White);
+    assert_eq!(
+        view.at_point(&Point(5, 4)).map(|piece| piece.color),
+        Some(Color::Black)
+    );
+    assert_eq!(view.at_point(&Point(5, 8)), None);
+}
+
+#[test]
+fn test_fog_of_war_captures_the_king() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").unwrap();
+    game.set_variant(Some(Arc::new(variant::FogOfWar)));
+    assert!(!game.is_checked());
+
+    assert_eq!(game.turn(Point(5, 8), Point(5, 7)), TurnResult::Moved);
+    assert_eq!(
+        game.turn(Point(5, 1), Point(5, 7)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+}
+
+#[test]
+fn test_umpire() {
+    let mut kriegspiel = Kriegspiel::new();
+    assert_eq!(
+        kriegspiel.attempt(&Turn::parse("e2e5").unwrap()),
+        Announcement::Illegal
+    );
+    assert_eq!(kriegspiel.color(), Color::White);
+
+    let announcement = |capture: Option<Point>, pawn_tries: usize| Announcement::Legal {
+        capture,
+        checks: vec![],
+        pawn_tries,
+        result: None,
+    };
+    assert_eq!(
+        kriegspiel.attempt(&Turn::parse("e2e4").unwrap()),
+        announcement(None, 0)
+    );
+    assert_eq!(
+        kriegspiel.attempt(&Turn::parse("d7d5").unwrap()),
+        announcement(None, 1)
+    );
+    assert_eq!(
+        kriegspiel.attempt(&Turn::parse("e4d5").unwrap()),
+        announcement(Some(Point(4, 5)), 0)
+    );
+
+    let view = kriegspiel.view(&Color::Black);
+    assert_eq!(view.current.iter().flatten().count(), 15);
+
+    // Each capture that promotes counts once, not once per piece.
+    let game = Game::from_fen("r1r1k3/1P6/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(
+        Kriegspiel::from_game(game).attempt(&Turn::parse("e8e7").unwrap()),
+        announcement(None, 2)
+    );
+}
+
+#[test]
+fn test_check_announcements() {
+    let announce = |fen: &str, notation: &str| {
+        let game = Game::from_fen(fen).unwrap();
+        match Kriegspiel::from_game(game).attempt(&Turn::parse(notation).unwrap()) {
+            Announcement::Legal { checks, .. } => checks,
+            Announcement::Illegal => panic!("{} is illegal", notation),
+        }
+    };
+
+    let fen = "4k3/8/8/8/8/8/8/3BKB1R w - - 0 1";
+    assert_eq!(announce(fen, "h1h8"), vec![Check::Rank]);
+    assert_eq!(announce(fen, "f1b5"), vec![Check::LongDiagonal]);
+    assert_eq!(announce(fen, "d1h5"), vec![Check::ShortDiagonal]);
+    assert_eq!(
+        announce("4k3/8/8/8/8/8/8/K6R w - - 0 1", "h1e1"),
+        vec![Check::File]
+    );
+
+    let fen = "4k3/8/8/8/4N3/8/8/4K3 w - - 0 1";
+    assert_eq!(announce(fen, "e4f6"), vec![Check::Knight]);
+    assert_eq!(announce(fen, "e4c5"), vec![]);
+}
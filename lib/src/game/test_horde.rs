@@ -0,0 +1,67 @@
+use super::*;
+
+fn horde(fen: &str) -> Game {
+    Game::from_fen_with_variant(fen, Some(Arc::new(variant::Horde))).unwrap()
+}
+
+#[test]
+fn test_start_position() {
+    let mut game = Game::horde();
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+    );
+    assert_eq!(game.get_board().find_king(&Color::White), None);
+    assert!(!game.is_checked());
+
+    assert_eq!(game.perft(1), 8);
+    assert_eq!(game.perft(2), 128);
+    assert_eq!(game.perft(3), 1274);
+
+    // Only horde accepts a side without a king.
+    let fen = game.to_fen();
+    assert!(Game::from_fen(&fen).is_none());
+    assert!(Game::from_fen_with_variant(&fen, Some(Arc::new(variant::Horde))).is_some());
+}
+
+#[test]
+fn test_first_rank_double_step() {
+    let mut game = horde("4k3/8/8/8/8/8/8/P7 w - - 0 1");
+    assert_eq!(
+        game.get_moves(&Point(1, 1)).unwrap().as_sorted(),
+        vec![Point(1, 2), Point(1, 3)]
+    );
+    assert_eq!(game.turn(Point(1, 1), Point(1, 3)), TurnResult::Moved);
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/P7/8/8 b - - 0 1");
+
+    // The position after the double step reads back as it was written.
+    let fen = game.to_fen();
+    assert_eq!(horde(&fen).to_fen(), fen);
+
+    // A step from the second rank still allows en passant.
+    let mut game = horde("4k3/8/8/8/1p6/8/P7/8 w - - 0 1");
+    assert_eq!(game.turn(Point(1, 2), Point(1, 4)), TurnResult::Moved);
+    let fen = game.to_fen();
+    assert_eq!(fen, "4k3/8/8/8/Pp6/8/8/8 b - a3 0 1");
+    assert_eq!(horde(&fen).to_fen(), fen);
+}
+
+#[test]
+fn test_capturing_the_horde_wins() {
+    let mut game = horde("4k3/8/8/8/8/8/1P6/r7 b - - 0 1");
+    assert_eq!(game.turn(Point(1, 1), Point(2, 1)), TurnResult::Moved);
+    assert_eq!(game.turn(Point(2, 2), Point(2, 3)), TurnResult::Moved);
+    assert_eq!(
+        game.turn(Point(2, 1), Point(2, 3)),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+}
+
+#[test]
+fn test_horde_stalemate() {
+    let mut game = horde("4k3/8/8/8/8/p7/P7/8 b - - 0 1");
+    assert_eq!(
+        game.turn(Point(5, 8), Point(5, 7)),
+        TurnResult::GameEnd(EndResult::Tie)
+    );
+}
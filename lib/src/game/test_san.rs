@@ -0,0 +1,73 @@
+use super::*;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn turn(notation: &str) -> Turn {
+    Turn::parse(notation).unwrap()
+}
+
+#[test]
+fn test_to_san() {
+    let game = Game::from_fen(KIWIPETE).unwrap();
+    for (notation, san) in [
+        ("e1g1", "O-O"),
+        ("e1c1", "O-O-O"),
+        ("e5f7", "Nxf7"),
+        ("d5e6", "dxe6"),
+        ("c3b1", "Nb1"),
+        ("e2a6", "Bxa6"),
+        ("f3f6", "Qxf6"),
+    ]
+    .iter()
+    {
+        assert_eq!(game.to_san(&turn(notation)).as_deref(), Some(*san));
+    }
+    assert_eq!(game.to_san(&turn("e1e3")), None);
+
+    // Knights on b1 and f3 both reach d2, and rooks on one file need ranks.
+    let game = Game::from_fen("7k/8/8/R7/8/5N2/8/RN5K w - - 0 1").unwrap();
+    assert_eq!(game.to_san(&turn("b1d2")).as_deref(), Some("Nbd2"));
+    assert_eq!(game.to_san(&turn("a5a3")).as_deref(), Some("R5a3"));
+    assert_eq!(game.to_san(&turn("a1a8")), None);
+
+    let game = Game::from_fen("7k/P7/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(game.to_san(&turn("a7a8q")).as_deref(), Some("a8=Q#"));
+    assert_eq!(game.to_san(&turn("a7a8n")).as_deref(), Some("a8=N"));
+}
+
+#[test]
+fn test_parse_san() {
+    let game = Game::from_fen(KIWIPETE).unwrap();
+    assert_eq!(game.parse_san("O-O"), Some(turn("e1g1")));
+    assert_eq!(game.parse_san("0-0-0"), Some(turn("e1c1")));
+    assert_eq!(game.parse_san("Nxf7"), Some(turn("e5f7")));
+    assert_eq!(game.parse_san("Nf7"), Some(turn("e5f7")));
+    assert_eq!(game.parse_san("Ne5xf7!?"), Some(turn("e5f7")));
+    assert_eq!(game.parse_san("dxe6"), Some(turn("d5e6")));
+    assert_eq!(game.parse_san("Qf6"), Some(turn("f3f6")));
+    assert_eq!(game.parse_san("Nd1"), Some(turn("c3d1")));
+    assert_eq!(game.parse_san("Ke3"), None);
+    assert_eq!(game.parse_san("e2e4"), None);
+
+    let game = Game::from_fen("7k/8/8/R7/8/5N2/8/RN5K w - - 0 1").unwrap();
+    assert_eq!(game.parse_san("Nd2"), None);
+    assert_eq!(game.parse_san("Nfd2"), Some(turn("f3d2")));
+    assert_eq!(game.parse_san("R1a3"), Some(turn("a1a3")));
+
+    let game = Game::from_fen("7k/P7/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(game.parse_san("a8=R"), Some(turn("a7a8r")));
+    assert_eq!(game.parse_san("a8Q#"), Some(turn("a7a8q")));
+
+    let mut game = Game::crazyhouse();
+    for notation in ["e2e4", "d7d5", "e4d5", "d8d5"].iter() {
+        game.play(&turn(notation));
+    }
+    assert_eq!(
+        game.parse_san("P@e6"),
+        Some(Turn::drop(Kind::Pawn, Point(5, 6)))
+    );
+    assert_eq!(
+        game.to_san(&Turn::drop(Kind::Pawn, Point(5, 6))).as_deref(),
+        Some("P@e6")
+    );
+}
@@ -0,0 +1,114 @@
+use super::clock::{Clock, TimeControl};
+use super::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::time::Duration;
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+    assert_eq!(round_trip(&value), value);
+}
+
+#[test]
+fn test_values() {
+    assert_round_trip(Point(5, 4));
+    assert_round_trip(Color::Black);
+    assert_round_trip(Kind::Custom('c'));
+    assert_round_trip(Piece {
+        promoted: true,
+        ..Piece::new(Color::White, Kind::Queen)
+    });
+    assert_round_trip(TurnResult::GameEnd(EndResult::Win(Color::White)));
+    assert_round_trip(EndResult::Tie);
+    assert_round_trip(Turn::parse("N@f3").unwrap());
+    assert_round_trip(TimeControl::parse("40/90+30:30+30").unwrap());
+}
+
+#[test]
+fn test_board() {
+    let mut game = Game::crazyhouse();
+    for turn in ["e2e4", "d7d5", "e4d5", "d8d5"] {
+        game.play(&Turn::parse(turn).unwrap());
+    }
+    let board = round_trip(game.get_board());
+    assert_eq!(board.current, game.get_board().current);
+    assert_eq!(board.pockets, game.get_board().pockets);
+    assert_eq!(board.graveyard, game.get_board().graveyard);
+    assert_eq!(board.width, 1..=8);
+    assert!(board.crazyhouse);
+}
+
+#[test]
+fn test_rejects_broken_boards() {
+    let mut game = Game::duck_chess();
+    game.play(&Turn::parse("e2e4,e6").unwrap());
+    let json = serde_json::to_value(&game).unwrap();
+    assert!(serde_json::from_value::<Game>(json.clone()).is_ok());
+
+    let broken = |path: &str, value: serde_json::Value| {
+        let mut json = json.clone();
+        *json.pointer_mut(path).unwrap() = value;
+        serde_json::from_value::<Game>(json).is_err()
+    };
+    // Too few squares, for the board and for one in the history.
+    assert!(broken("/board/current", serde_json::json!([])));
+    assert!(broken(
+        "/history/0/board/current",
+        serde_json::json!([null])
+    ));
+    assert!(broken("/board/width/end", 40.into()));
+    assert!(broken("/board/duck", serde_json::json!([9, 1])));
+    assert!(broken(
+        "/board/enpassant",
+        serde_json::json!([[5, 3], [5, 0]])
+    ));
+
+    // Fairy pieces have to be defined to be loaded.
+    assert!(serde_json::from_str::<Kind>(r#"{"Custom":"c"}"#).is_ok());
+    assert!(serde_json::from_str::<Kind>(r#"{"Custom":"y"}"#).is_err());
+}
+
+#[test]
+fn test_game() {
+    let mut game = Game::from_fen("k7/7P/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    game.set_variant(variant::by_name("kingofthehill"));
+    game.play(&Turn::parse("a1b1").unwrap());
+    game.play(&Turn::parse("a8b8").unwrap());
+    assert_eq!(game.turn(Point(8, 7), Point(8, 8)), TurnResult::Promotion);
+
+    let mut loaded = round_trip(&game);
+    assert_eq!(loaded.to_fen(), game.to_fen());
+    assert_eq!(loaded.get_history(), game.get_history());
+    assert_eq!(loaded.get_variant().unwrap().name(), "kingofthehill");
+    // The pending promotion is finished after loading, and the history still
+    // goes back to the start.
+    assert_eq!(loaded.promote(Kind::Queen), TurnResult::Checked);
+    assert!(loaded.undo() && loaded.undo() && loaded.undo());
+    assert_eq!(loaded.to_fen(), "k7/7P/8/8/8/8/8/K7 w - - 0 1");
+    assert!(!loaded.undo());
+}
+
+#[test]
+fn test_clock() {
+    let mut game = Game::new();
+    let mut clock = Clock::new(TimeControl::parse("5+3").unwrap());
+    clock.set(&Color::Black, Duration::from_secs(100));
+    clock.start(Color::White);
+    game.set_clock(Some(clock));
+
+    let loaded = round_trip(&game);
+    let clock = loaded.get_clock().unwrap();
+    assert_eq!(clock.get_control(), game.get_clock().unwrap().get_control());
+    assert_eq!(clock.remaining(&Color::Black), Duration::from_secs(100));
+    assert_eq!(clock.running(), Some(Color::White));
+    assert!(clock.remaining(&Color::White) > Duration::from_secs(299));
+
+    let mut json = serde_json::to_value(&game).unwrap();
+    json["variant"] = "nosuchvariant".into();
+    assert!(serde_json::from_value::<Game>(json).is_err());
+}
@@ -0,0 +1,96 @@
+use super::variant::*;
+use super::*;
+
+fn with_variant(fen: &str, variant: Arc<dyn Variant>) -> Game {
+    Game::from_fen_with_variant(fen, Some(variant)).unwrap()
+}
+
+#[test]
+fn test_king_of_the_hill() {
+    let game = Game::king_of_the_hill();
+    assert_eq!(game.to_fen(), Game::new().to_fen());
+    assert_eq!(game.get_variant().map(|v| v.name()), Some("kingofthehill"));
+
+    let mut game = with_variant("4k3/8/8/8/8/2K5/8/8 w - - 0 1", Arc::new(KingOfTheHill));
+    assert_eq!(
+        game.turn(Point(3, 3), Point(4, 4)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    assert_eq!(game.get_result(), Some(EndResult::Win(Color::White)));
+}
+
+#[test]
+fn test_three_check() {
+    let mut game = with_variant("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Arc::new(ThreeCheck));
+
+    for notation in ["a1a8", "e8e7", "a8a7", "e7e6"].iter() {
+        assert_ne!(
+            game.play(&Turn::parse(notation).unwrap()),
+            TurnResult::Failed
+        );
+    }
+    assert_eq!(game.get_checks(&Color::White), 2);
+    assert_eq!(game.get_checks(&Color::Black), 0);
+
+    assert_eq!(
+        game.play(&Turn::parse("a7a6").unwrap()),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    game.undo();
+    assert_eq!(game.get_checks(&Color::White), 2);
+    assert_eq!(game.get_result(), None);
+}
+
+#[test]
+fn test_three_check_hash() {
+    let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+    let checked = |variant: Option<Arc<dyn Variant>>| {
+        let mut game = Game::from_fen_with_variant(fen, variant).unwrap();
+        let start = game.hash_key();
+        game.checks = [1, 0];
+        (start, game.hash_key())
+    };
+
+    let (start, checked_once) = checked(Some(Arc::new(ThreeCheck)));
+    assert_ne!(start, checked_once);
+
+    // Other games never look at the count, so it stays out of the key.
+    let (start, checked_once) = checked(None);
+    assert_eq!(start, checked_once);
+}
+
+#[test]
+fn test_racing_kings_forbids_checks() {
+    let mut game = with_variant("k7/8/8/8/8/8/8/1R5K w - - 0 1", Arc::new(RacingKings));
+    let moves = game.get_moves(&Point(2, 1)).unwrap();
+    assert!(!moves.contains(&Point(1, 1)));
+    assert!(!moves.contains(&Point(2, 8)));
+    assert!(moves.contains(&Point(2, 7)));
+    assert!(moves.contains(&Point(7, 1)));
+}
+
+#[test]
+fn test_racing_kings_finish() {
+    let mut game = with_variant("8/1K6/8/8/8/8/6k1/8 w - - 0 1", Arc::new(RacingKings));
+    assert_eq!(
+        game.turn(Point(2, 7), Point(2, 8)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+
+    // Black is close enough to answer on the last rank.
+    let mut game = with_variant("8/1K4k1/8/8/8/8/8/8 w - - 0 1", Arc::new(RacingKings));
+    assert_eq!(game.turn(Point(2, 7), Point(2, 8)), TurnResult::Moved);
+    assert_eq!(
+        game.turn(Point(7, 7), Point(7, 8)),
+        TurnResult::GameEnd(EndResult::Tie)
+    );
+}
+
+#[test]
+fn test_racing_kings_perft() {
+    let mut game = Game::racing_kings();
+    assert_eq!(game.to_fen(), "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1");
+    assert_eq!(game.perft(1), 21);
+    assert_eq!(game.perft(2), 421);
+    assert_eq!(game.perft(3), 11264);
+}
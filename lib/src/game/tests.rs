@@ -2,9 +2,9 @@ use super::*;
 use crate::pieces::*;
 
 pub fn create_test_board(positions: Vec<(Point, Piece)>) -> Board {
-    let mut current: [Option<Piece>; 64] = [None; 64];
+    let mut current: Vec<Option<Piece>> = vec![None; 64];
     for (point, piece) in positions {
-        current[point.index()] = Some(piece);
+        current[point.index(8)] = Some(piece);
     }
 
     Board {
@@ -15,6 +15,32 @@ pub fn create_test_board(positions: Vec<(Point, Piece)>) -> Board {
         height: (1..=8),
         width: (1..=8),
         enpassant: None,
+        chess960: false,
+        promotions: vec![Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight],
+        crazyhouse: false,
+        pockets: vec![(Color::White, vec![]), (Color::Black, vec![])]
+            .into_iter()
+            .collect(),
+        atomic: false,
+        capturable_kings: false,
+        duck: None,
+        double_step: true,
+    }
+}
+
+pub fn create_test_game(board: Board, color: Color) -> Game {
+    Game {
+        board,
+        color,
+        promotion: None,
+        awaiting_duck: false,
+        result: None,
+        halfmove_clock: 0,
+        fullmove_number: 1,
+        checks: [0, 0],
+        history: vec![],
+        variant: None,
+        clock: None,
     }
 }
 
@@ -26,11 +52,7 @@ fn test_turn_valid_move() {
         (Point(4, 4), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(game.turn(Point(4, 4), Point(5, 4)), TurnResult::Moved);
 
@@ -45,11 +67,7 @@ fn test_failure_on_invalid_move() {
         (Point(4, 4), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(game.turn(Point(4, 4), Point(5, 5)), TurnResult::Failed,);
 
@@ -64,11 +82,7 @@ fn test_failure_on_moving_wrong_color() {
         (Point(4, 4), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::Black,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::Black);
 
     assert_eq!(game.turn(Point(4, 4), Point(4, 5)), TurnResult::Failed);
 
@@ -83,11 +97,7 @@ fn test_checks_opponent() {
         (Point(4, 4), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(game.turn(Point(4, 4), Point(4, 5)), TurnResult::Checked);
 
@@ -105,11 +115,7 @@ fn test_check_mate() {
         (Point(4, 6), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(
         game.turn(Point(4, 4), Point(4, 5)),
@@ -127,11 +133,7 @@ fn test_tie() {
         (Point(3, 3), Piece::new(Color::White, Kind::Rook)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(
         game.turn(Point(3, 3), Point(2, 3)),
@@ -148,11 +150,7 @@ fn test_promotion() {
         (Point(8, 2), Piece::new(Color::Black, Kind::Pawn)),
     ]);
 
-    let mut game = Game {
-        board: board,
-        color: Color::White,
-        promotion: None,
-    };
+    let mut game = create_test_game(board, Color::White);
 
     assert_eq!(game.turn(Point(8, 7), Point(8, 8)), TurnResult::Promotion);
     assert_eq!(game.promotion, Some((Point(8, 7), Point(8, 8))));
@@ -168,3 +166,166 @@ fn test_promotion() {
     assert_eq!(game.promote(Kind::Queen), TurnResult::Checked);
     assert_eq!(game.color, Color::White);
 }
+
+#[test]
+fn test_castling() {
+    let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    assert!(game.get_moves(&Point(5, 1)).unwrap().contains(&Point(7, 1)));
+    assert!(game.get_moves(&Point(5, 1)).unwrap().contains(&Point(3, 1)));
+
+    assert_eq!(game.turn(Point(5, 1), Point(7, 1)), TurnResult::Moved);
+    assert_eq!(
+        game.board.at_point(&Point(6, 1)).map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.board.at_point(&Point(8, 1)), None);
+
+    assert_eq!(game.turn(Point(5, 8), Point(3, 8)), TurnResult::Moved);
+    assert_eq!(
+        game.board.at_point(&Point(4, 8)).map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+    assert_eq!(game.board.at_point(&Point(1, 8)), None);
+}
+
+#[test]
+fn test_no_castling_through_check() {
+    let mut game = Game::from_fen("4k3/4r3/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(game.is_checked());
+    assert!(!game.get_moves(&Point(5, 1)).unwrap().contains(&Point(7, 1)));
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/6r1/R3K2R w KQ - 0 1").unwrap();
+    assert!(!game.get_moves(&Point(5, 1)).unwrap().contains(&Point(7, 1)));
+    assert!(game.get_moves(&Point(5, 1)).unwrap().contains(&Point(3, 1)));
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1").unwrap();
+    assert!(!game.get_moves(&Point(5, 1)).unwrap().contains(&Point(3, 1)));
+    assert!(game.get_moves(&Point(5, 1)).unwrap().contains(&Point(7, 1)));
+}
+
+#[test]
+fn test_play_with_promotion() {
+    let mut game = Game::from_fen("k7/7P/8/8/8/8/8/K7 w - - 0 1").unwrap();
+
+    assert_eq!(game.play(&Turn::parse("h7h8").unwrap()), TurnResult::Failed);
+    assert_eq!(game.color, Color::White);
+    assert_eq!(
+        game.play(&Turn::parse("h7h8r").unwrap()),
+        TurnResult::Checked
+    );
+    assert_eq!(
+        game.board.at_point(&Point(8, 8)).map(|piece| piece.kind),
+        Some(Kind::Rook)
+    );
+}
+
+#[test]
+fn test_turn_notation() {
+    let turn = Turn::parse("e7e8q").unwrap();
+    assert_eq!(turn.source, Point(5, 7));
+    assert_eq!(turn.target, Point(5, 8));
+    assert_eq!(turn.promotion, Some(Kind::Queen));
+    assert_eq!(turn.to_string(), "e7e8q");
+    assert_eq!(Turn::parse("g1f3").unwrap().to_string(), "g1f3");
+    assert!(Turn::parse("g1").is_none());
+    assert!(Turn::parse("e7e8x").is_none());
+}
+
+#[test]
+fn test_result_is_recorded() {
+    let mut game = Game::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+
+    assert_eq!(
+        game.turn(Point(8, 1), Point(8, 8)),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    assert_eq!(game.get_result(), Some(EndResult::Win(Color::White)));
+    assert!(game.legal_turns().is_empty());
+}
+
+#[test]
+fn test_perft() {
+    assert_eq!(Game::new().perft(1), 20);
+    assert_eq!(Game::new().perft(3), 8902);
+
+    let mut kiwipete =
+        Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+    assert_eq!(kiwipete.perft(1), 48);
+    assert_eq!(kiwipete.perft(2), 2039);
+
+    let mut endgame = Game::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+    assert_eq!(endgame.perft(3), 2812);
+
+    let mut promotions =
+        Game::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+    assert_eq!(promotions.perft(2), 264);
+}
+
+#[test]
+fn test_underpromotion_on_capture() {
+    let mut game = Game::from_fen("1r5k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+
+    assert_eq!(game.turn(Point(1, 7), Point(2, 8)), TurnResult::Promotion);
+    assert_eq!(game.promote(Kind::Knight), TurnResult::Moved);
+    assert_eq!(
+        game.board.at_point(&Point(2, 8)),
+        Some(Piece {
+            color: Color::White,
+            kind: Kind::Knight,
+            has_moved: true,
+            promoted: true,
+        })
+    );
+}
+
+#[test]
+fn test_undo() {
+    let mut game = Game::new();
+
+    assert!(!game.undo());
+
+    for notation in ["e2e4", "d7d5", "e4d5", "g8f6"].iter() {
+        assert_ne!(
+            game.play(&Turn::parse(notation).unwrap()),
+            TurnResult::Failed
+        );
+    }
+    assert_eq!(
+        game.get_history(),
+        vec![
+            Turn::parse("e2e4").unwrap(),
+            Turn::parse("d7d5").unwrap(),
+            Turn::parse("e4d5").unwrap(),
+            Turn::parse("g8f6").unwrap(),
+        ]
+    );
+    assert_eq!(game.board.graveyard[&Color::Black].len(), 1);
+
+    assert!(game.undo());
+    assert!(game.undo());
+    assert_eq!(game.color, Color::White);
+    assert_eq!(game.board.graveyard[&Color::Black].len(), 0);
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2"
+    );
+
+    assert!(game.undo());
+    assert!(game.undo());
+    assert_eq!(game.to_fen(), Game::new().to_fen());
+    assert!(game.get_history().is_empty());
+}
+
+#[test]
+fn test_undo_clears_result() {
+    let mut game = Game::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+
+    game.turn(Point(8, 1), Point(8, 8));
+    assert!(game.get_result().is_some());
+
+    assert!(game.undo());
+    assert_eq!(game.get_result(), None);
+    assert_eq!(game.color, Color::White);
+}
@@ -0,0 +1,386 @@
+use super::{EndResult, Game, Turn};
+use crate::board::Board;
+use crate::pieces::Kind;
+use crate::{Color, Point};
+use std::sync::Arc;
+
+// Rules layered on top of normal play. A game without a variant, or with one
+// that keeps the defaults, plays standard chess.
+pub trait Variant: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn start(&self) -> Board {
+        Board::default()
+    }
+
+    // Switches on any rules the board itself has to follow.
+    fn setup(&self, _board: &mut Board) {}
+
+    // Whether every move is followed by moving the duck.
+    fn has_duck(&self) -> bool {
+        false
+    }
+
+    // Further restricts turns that are already legal in normal chess.
+    fn allows(&self, _board: &Board, _color: Color, _turn: &Turn) -> bool {
+        true
+    }
+
+    // Whether the checks each side has given are part of the position, so
+    // that positions differing only in them are told apart.
+    fn counts_checks(&self) -> bool {
+        false
+    }
+
+    // Whether each side tries to get rid of its pieces, so that material
+    // counts against whoever has it.
+    fn wins_by_losing_pieces(&self) -> bool {
+        false
+    }
+
+    // Whether a position read from FEN can be played, judged by its kings.
+    fn accepts(&self, board: &Board) -> bool {
+        one_king_each(board)
+    }
+
+    // Asked after every turn, before checkmate and stalemate, whether the
+    // variant's own goal has been reached.
+    fn result(&self, _game: &Game, _mover: Color) -> Option<EndResult> {
+        None
+    }
+}
+
+fn kings(board: &Board, color: Color) -> usize {
+    board
+        .current
+        .iter()
+        .flatten()
+        .filter(|piece| piece.color == color && piece.kind == Kind::King)
+        .count()
+}
+
+// Standard play needs exactly one king per side.
+pub fn one_king_each(board: &Board) -> bool {
+    kings(board, Color::White) == 1 && kings(board, Color::Black) == 1
+}
+
+// Where kings can be taken or blown up, a finished game may have lost one.
+fn at_most_one_king_each(board: &Board) -> bool {
+    kings(board, Color::White) <= 1 && kings(board, Color::Black) <= 1
+}
+
+// Finds a variant by the name it reports, as when reading a saved game.
+pub fn by_name(name: &str) -> Option<Arc<dyn Variant>> {
+    let variants: [Arc<dyn Variant>; 8] = [
+        Arc::new(KingOfTheHill),
+        Arc::new(ThreeCheck),
+        Arc::new(Atomic),
+        Arc::new(Antichess),
+        Arc::new(Horde),
+        Arc::new(FogOfWar),
+        Arc::new(DuckChess),
+        Arc::new(RacingKings),
+    ];
+    variants
+        .iter()
+        .find(|variant| variant.name() == name)
+        .cloned()
+}
+
+#[cfg(feature = "serde")]
+pub(super) fn serialize<S: serde::Serializer>(
+    variant: &Option<Arc<dyn Variant>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&variant.as_ref().map(|variant| variant.name()), serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(super) fn deserialize<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Arc<dyn Variant>>, D::Error> {
+    let name: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    name.map(|name| {
+        by_name(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown variant {}", name)))
+    })
+    .transpose()
+}
+
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        "kingofthehill"
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        let hill = [Point(4, 4), Point(5, 4), Point(4, 5), Point(5, 5)];
+        if game
+            .board
+            .find_king(&mover)
+            .is_some_and(|king| hill.contains(&king))
+        {
+            Some(EndResult::Win(mover))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &'static str {
+        "3check"
+    }
+
+    fn counts_checks(&self) -> bool {
+        true
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        if game.get_checks(&mover) >= 3 {
+            Some(EndResult::Win(mover))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Atomic;
+
+impl Variant for Atomic {
+    fn name(&self) -> &'static str {
+        "atomic"
+    }
+
+    fn setup(&self, board: &mut Board) {
+        board.atomic = true;
+    }
+
+    fn accepts(&self, board: &Board) -> bool {
+        at_most_one_king_each(board)
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        match game.board.find_king(&mover.inverse()) {
+            Some(_) => None,
+            None => Some(EndResult::Win(mover)),
+        }
+    }
+}
+
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn name(&self) -> &'static str {
+        "antichess"
+    }
+
+    fn start(&self) -> Board {
+        let mut board = Board::default();
+        board.forbid_castling();
+        board
+    }
+
+    fn setup(&self, board: &mut Board) {
+        board.capturable_kings = true;
+        if !board.promotions.contains(&Kind::King) {
+            board.promotions.push(Kind::King);
+        }
+    }
+
+    fn wins_by_losing_pieces(&self) -> bool {
+        true
+    }
+
+    // Kings are ordinary pieces, and pawns can promote to them.
+    fn accepts(&self, _board: &Board) -> bool {
+        true
+    }
+
+    // Whoever can take has to.
+    fn allows(&self, board: &Board, color: Color, turn: &Turn) -> bool {
+        (turn.drop.is_none() && board.is_capture(&turn.source, &turn.target))
+            || !board.clone().has_capture(&color)
+    }
+
+    // Running out of moves, usually by losing every piece, wins.
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        let opponent = mover.inverse();
+        if can_move(&game.board, &opponent) {
+            None
+        } else {
+            Some(EndResult::Win(opponent))
+        }
+    }
+}
+
+pub struct Horde;
+
+const HORDE: &str = "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+
+// White has no king and loses only once every one of its pieces is taken.
+impl Variant for Horde {
+    fn name(&self) -> &'static str {
+        "horde"
+    }
+
+    fn start(&self) -> Board {
+        Game::parse_fen(HORDE).unwrap().board
+    }
+
+    fn accepts(&self, board: &Board) -> bool {
+        kings(board, Color::White) == 0 && kings(board, Color::Black) == 1
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        let white_left = game
+            .board
+            .current
+            .iter()
+            .flatten()
+            .any(|piece| piece.color == Color::White);
+        if mover == Color::Black && !white_left {
+            Some(EndResult::Win(Color::Black))
+        } else {
+            None
+        }
+    }
+}
+
+// Players only see what their pieces reach, so nobody is told about checks
+// and the game is won by taking the king.
+pub struct FogOfWar;
+
+impl Variant for FogOfWar {
+    fn name(&self) -> &'static str {
+        "fogofwar"
+    }
+
+    fn setup(&self, board: &mut Board) {
+        board.capturable_kings = true;
+    }
+
+    fn accepts(&self, board: &Board) -> bool {
+        at_most_one_king_each(board)
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        match game.board.find_king(&mover.inverse()) {
+            Some(_) => None,
+            None => Some(EndResult::Win(mover)),
+        }
+    }
+}
+
+// Each move is followed by moving a duck that blocks its square for both
+// sides. There is no check, the king has to be taken, and a player left
+// without a move wins.
+pub struct DuckChess;
+
+impl Variant for DuckChess {
+    fn name(&self) -> &'static str {
+        "duck"
+    }
+
+    fn setup(&self, board: &mut Board) {
+        board.capturable_kings = true;
+    }
+
+    fn has_duck(&self) -> bool {
+        true
+    }
+
+    fn accepts(&self, board: &Board) -> bool {
+        at_most_one_king_each(board)
+    }
+
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        let opponent = mover.inverse();
+        if game.board.find_king(&opponent).is_none() {
+            Some(EndResult::Win(mover))
+        } else if !can_move(&game.board, &opponent) {
+            Some(EndResult::Win(opponent))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct RacingKings;
+
+const RACING_KINGS: &str = "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1";
+
+impl Variant for RacingKings {
+    fn name(&self) -> &'static str {
+        "racingkings"
+    }
+
+    fn start(&self) -> Board {
+        Game::parse_fen(RACING_KINGS).unwrap().board
+    }
+
+    // Giving check is not allowed at all.
+    fn allows(&self, board: &Board, color: Color, turn: &Turn) -> bool {
+        let mut board = board.clone();
+        match turn.drop {
+            Some(kind) => board.drop_piece(&color, kind, turn.target),
+            None => board.move_piece(turn.source, turn.target),
+        };
+        board.detect_check(&color.inverse()).is_none()
+    }
+
+    // The first king on the last rank wins, but when White gets there first
+    // Black still has one move to draw by reaching it too.
+    fn result(&self, game: &Game, mover: Color) -> Option<EndResult> {
+        let board = &game.board;
+        let goal = *board.height.end();
+        let white = board
+            .find_king(&Color::White)
+            .is_some_and(|king| king.1 == goal);
+        let black = board
+            .find_king(&Color::Black)
+            .is_some_and(|king| king.1 == goal);
+
+        match mover {
+            Color::White if white => {
+                let mut board = board.clone();
+                let king = board.find_king(&Color::Black)?;
+                let catches_up = board
+                    .get_allowed_moves(&king)
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|target| {
+                        target.1 == goal
+                            && self.allows(&board, Color::Black, &Turn::new(king, *target))
+                    });
+                if catches_up {
+                    None
+                } else {
+                    Some(EndResult::Win(Color::White))
+                }
+            }
+            Color::Black if white && black => Some(EndResult::Tie),
+            Color::Black if white => Some(EndResult::Win(Color::White)),
+            Color::Black if black => Some(EndResult::Win(Color::Black)),
+            _ => None,
+        }
+    }
+}
+
+fn can_move(board: &Board, color: &Color) -> bool {
+    let mut board = board.clone();
+    for x in board.width.clone() {
+        for y in board.height.clone() {
+            let point = Point(x, y);
+            let owned = board
+                .at_point(&point)
+                .is_some_and(|piece| &piece.color == color);
+            if owned && board.get_allowed_moves(&point).is_some() {
+                return true;
+            }
+        }
+    }
+    false
+}
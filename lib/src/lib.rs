@@ -1,24 +1,53 @@
 pub mod board;
+pub mod book;
+pub mod engine;
 pub mod game;
+pub mod net;
 pub mod pieces;
+pub mod store;
+pub mod tablebase;
+
+use std::fmt;
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point(pub i8, pub i8);
 
 impl Point {
+    // Saturates rather than overflows, which still lands off every board.
     pub fn add(&self, other: &Point) -> Point {
-        Point(self.0 + other.0, self.1 + other.1)
+        Point(
+            self.0.saturating_add(other.0),
+            self.1.saturating_add(other.1),
+        )
+    }
+
+    // Squares are stored rank by rank, so the index depends on the board width.
+    pub fn index(&self, width: i8) -> usize {
+        (self.1 - 1) as usize * width as usize + (self.0 - 1) as usize
+    }
+
+    pub fn parse(square: &str) -> Option<Point> {
+        let mut chars = square.chars();
+        let file = chars.next().filter(|c| c.is_ascii_lowercase())?;
+        let rank: i8 = chars.as_str().parse().ok().filter(|rank| *rank >= 1)?;
+
+        Some(Point((file as u8 - b'a' + 1) as i8, rank))
     }
+}
 
-    pub fn index(&self) -> usize {
-        ((self.1 - 1) * 8 + (self.0 - 1)) as usize
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + (self.0 - 1) as u8) as char, self.1)
     }
 }
 
+#[cfg(test)]
 trait Sorted {
     fn as_sorted(&self) -> Self;
 }
 
+#[cfg(test)]
 impl Sorted for Vec<Point> {
     fn as_sorted(&self) -> Self {
         let mut clone = self.clone();
@@ -28,13 +57,14 @@ impl Sorted for Vec<Point> {
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
 }
 
 impl Color {
-    fn inverse(&self) -> Self {
+    pub fn inverse(&self) -> Self {
         match self {
             Color::White => Color::Black,
             Color::Black => Color::White,
@@ -43,7 +73,7 @@ impl Color {
 }
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct Move(Point, bool);
+pub struct Move(pub Point, pub bool);
 
 #[cfg(test)]
 mod tests {
@@ -53,5 +83,16 @@ mod tests {
     fn test_point_addition() {
         assert_eq!(Point(3, 3).add(&Point(1, 2)), Point(4, 5));
         assert_eq!(Point(3, 3).add(&Point(-2, -1)), Point(1, 2));
+        assert_eq!(Point(3, 3).add(&Point(127, -128)), Point(127, -125));
+    }
+
+    #[test]
+    fn test_point_algebraic_notation() {
+        assert_eq!(Point::parse("e4"), Some(Point(5, 4)));
+        assert_eq!(Point::parse("a1"), Some(Point(1, 1)));
+        assert_eq!(Point::parse("h8"), Some(Point(8, 8)));
+        assert_eq!(Point::parse("e0"), None);
+        assert_eq!(Point::parse("4e"), None);
+        assert_eq!(Point(7, 1).to_string(), "g1");
     }
 }
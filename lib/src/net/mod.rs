@@ -0,0 +1,396 @@
+#[cfg(test)]
+mod tests;
+
+use crate::game::{EndResult, Game, Turn, TurnResult};
+use crate::Color;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+// Bumped whenever a message changes meaning. Both sides say which version
+// they speak first thing, and give up on a mismatch.
+pub const VERSION: u32 = 2;
+
+// Messages are single lines of text:
+//
+//     hello 2 black <fen>  the host's greeting, naming the guest's color
+//                          and the position the game starts from
+//     hello 2            the guest's answer
+//     move e7e8q         a finished turn, promotion and duck included
+//     resign
+//     draw offer|accept|decline
+//     clock 59000 60000  White's and Black's time in milliseconds
+//     chat <text>
+#[derive(Clone, PartialEq, Debug)]
+pub enum Message {
+    Hello {
+        version: u32,
+        color: Option<Color>,
+        fen: Option<String>,
+    },
+    Move(Turn),
+    Resign,
+    DrawOffer,
+    DrawAccept,
+    DrawDecline,
+    Clock {
+        white: Duration,
+        black: Duration,
+    },
+    Chat(String),
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Message> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let mut tokens = rest.split_whitespace();
+
+        let message = match command {
+            "hello" => {
+                let version = tokens.next()?.parse().ok()?;
+                let color = match tokens.next() {
+                    Some("white") => Some(Color::White),
+                    Some("black") => Some(Color::Black),
+                    Some(_) => return None,
+                    None => None,
+                };
+                // The start position takes up the rest of the line.
+                let fen: Vec<&str> = tokens.by_ref().collect();
+                Message::Hello {
+                    version,
+                    color,
+                    fen: Some(fen.join(" ")).filter(|fen| !fen.is_empty()),
+                }
+            }
+            "move" => Message::Move(Turn::parse(tokens.next()?)?),
+            "resign" => Message::Resign,
+            "draw" => match tokens.next()? {
+                "offer" => Message::DrawOffer,
+                "accept" => Message::DrawAccept,
+                "decline" => Message::DrawDecline,
+                _ => return None,
+            },
+            "clock" => {
+                let mut millis = || tokens.next()?.parse().ok().map(Duration::from_millis);
+                Message::Clock {
+                    white: millis()?,
+                    black: millis()?,
+                }
+            }
+            // Chat keeps the text as it was typed.
+            "chat" => return Some(Message::Chat(rest.to_string())),
+            _ => return None,
+        };
+
+        match tokens.next() {
+            Some(_) => None,
+            None => Some(message),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Hello {
+                version,
+                color,
+                fen,
+            } => {
+                write!(f, "hello {}", version)?;
+                match color {
+                    Some(Color::White) => write!(f, " white")?,
+                    Some(Color::Black) => write!(f, " black")?,
+                    None => (),
+                }
+                match fen {
+                    Some(fen) => write!(f, " {}", fen),
+                    None => Ok(()),
+                }
+            }
+            Message::Move(turn) => write!(f, "move {}", turn),
+            Message::Resign => write!(f, "resign"),
+            Message::DrawOffer => write!(f, "draw offer"),
+            Message::DrawAccept => write!(f, "draw accept"),
+            Message::DrawDecline => write!(f, "draw decline"),
+            Message::Clock { white, black } => {
+                write!(f, "clock {} {}", white.as_millis(), black.as_millis())
+            }
+            // Line breaks would end the message early.
+            Message::Chat(text) => write!(f, "chat {}", text.replace(['\r', '\n'], " ")),
+        }
+    }
+}
+
+// A line connection that reads on its own thread, so that waiting for the
+// other side never blocks a user interface.
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Receiver<io::Result<Message>>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = line.and_then(|line| {
+                    Message::parse(&line).ok_or_else(|| invalid(&format!("bad message {:?}", line)))
+                });
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    return;
+                }
+            }
+            let _ = sender.send(Err(io::ErrorKind::UnexpectedEof.into()));
+        });
+
+        Ok(Connection { stream, incoming })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.stream, "{}", message)?;
+        self.stream.flush()
+    }
+
+    pub fn receive(&mut self, timeout: Duration) -> io::Result<Option<Message>> {
+        match self.incoming.recv_timeout(timeout) {
+            Ok(message) => message.map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    pub fn try_receive(&mut self) -> io::Result<Option<Message>> {
+        match self.incoming.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Event {
+    Moved(Turn, TurnResult),
+    // A move that is not legal here, which is left unplayed.
+    Illegal(Turn),
+    // The other side's time ran out before its move arrived, which ended the
+    // game with the given result.
+    OutOfTime(EndResult),
+    Resigned,
+    DrawOffered,
+    DrawAgreed,
+    DrawDeclined,
+    Chat(String),
+    Disconnected,
+}
+
+// One player's end of a game played over the network. Each side keeps its
+// own game, plays its own moves there and sends them on. Moves that arrive
+// are only played when they are legal in the local game.
+pub struct Peer {
+    connection: Connection,
+    color: Color,
+    // The position both sides started from, as the host sent it.
+    start: String,
+    // Draw offers made by this side and by the other one.
+    offered: bool,
+    offer_received: bool,
+}
+
+impl Peer {
+    // Waits for a guest and tells it to play the other color, starting from
+    // the position of the game given.
+    pub fn host(listener: &TcpListener, color: Color, game: &Game) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        let mut connection = Connection::new(stream)?;
+        let start = game.to_fen();
+        connection.send(&Message::Hello {
+            version: VERSION,
+            color: Some(color.inverse()),
+            fen: Some(start.clone()),
+        })?;
+        match connection.receive(Duration::from_secs(10))? {
+            Some(Message::Hello { version, .. }) if version == VERSION => {
+                Ok(Peer::new(connection, color, start))
+            }
+            _ => Err(invalid("unsupported protocol version")),
+        }
+    }
+
+    pub fn join<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        let (color, start) = match connection.receive(Duration::from_secs(10))? {
+            Some(Message::Hello {
+                version,
+                color: Some(color),
+                fen: Some(fen),
+            }) if version == VERSION => (color, fen),
+            _ => return Err(invalid("unsupported protocol version")),
+        };
+        connection.send(&Message::Hello {
+            version: VERSION,
+            color: None,
+            fen: None,
+        })?;
+        Ok(Peer::new(connection, color, start))
+    }
+
+    fn new(connection: Connection, color: Color, start: String) -> Self {
+        Peer {
+            connection,
+            color,
+            start,
+            offered: false,
+            offer_received: false,
+        }
+    }
+
+    // The color played on this side.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    // The FEN of the position the game started from, which the guest sets up
+    // its own game from.
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    // Sends the turn just finished in the game, along with the clock.
+    pub fn send_turn(&mut self, game: &Game) -> io::Result<()> {
+        let turn = match game.get_history().last() {
+            Some(turn) => *turn,
+            None => return Ok(()),
+        };
+        self.offered = false;
+        self.offer_received = false;
+        self.connection.send(&Message::Move(turn))?;
+        if let Some(clock) = game.get_clock() {
+            self.connection.send(&Message::Clock {
+                white: clock.remaining(&Color::White),
+                black: clock.remaining(&Color::Black),
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn resign(&mut self, game: &mut Game) -> io::Result<TurnResult> {
+        let result = game.resign(self.color);
+        if result != TurnResult::Failed {
+            self.connection.send(&Message::Resign)?;
+        }
+        Ok(result)
+    }
+
+    // Offers a draw, or takes the one on the table.
+    pub fn offer_draw(&mut self, game: &mut Game) -> io::Result<TurnResult> {
+        if game.get_result().is_some() {
+            return Ok(TurnResult::Failed);
+        }
+        if self.offer_received {
+            self.connection.send(&Message::DrawAccept)?;
+            return Ok(game.agree_draw());
+        }
+        self.offered = true;
+        self.connection.send(&Message::DrawOffer)?;
+        Ok(TurnResult::Moved)
+    }
+
+    pub fn decline_draw(&mut self) -> io::Result<()> {
+        if self.offer_received {
+            self.offer_received = false;
+            self.connection.send(&Message::DrawDecline)?;
+        }
+        Ok(())
+    }
+
+    pub fn chat(&mut self, text: &str) -> io::Result<()> {
+        self.connection.send(&Message::Chat(text.to_string()))
+    }
+
+    // Handles whatever the other side has sent, without waiting.
+    pub fn poll(&mut self, game: &mut Game) -> Option<Event> {
+        let message = self.connection.try_receive();
+        self.handle(game, message)
+    }
+
+    pub fn wait(&mut self, game: &mut Game, timeout: Duration) -> Option<Event> {
+        let message = self.connection.receive(timeout);
+        self.handle(game, message)
+    }
+
+    fn handle(&mut self, game: &mut Game, message: io::Result<Option<Message>>) -> Option<Event> {
+        let message = match message {
+            Ok(message) => message?,
+            Err(_) => return Some(Event::Disconnected),
+        };
+
+        match message {
+            Message::Move(turn) => {
+                if game.color != self.color.inverse() {
+                    return Some(Event::Illegal(turn));
+                }
+                if let Some(result) = game.check_time() {
+                    return Some(Event::OutOfTime(result));
+                }
+                match game.play(&turn) {
+                    TurnResult::Failed => Some(Event::Illegal(turn)),
+                    result => {
+                        self.offered = false;
+                        self.offer_received = false;
+                        Some(Event::Moved(turn, result))
+                    }
+                }
+            }
+            // The mover's clock is the one that counts, so the other side
+            // only gets to correct its own time.
+            Message::Clock { white, black } => {
+                let sender = self.color.inverse();
+                let left = match sender {
+                    Color::White => white,
+                    Color::Black => black,
+                };
+                let mut clock = game.get_clock().cloned()?;
+                clock.set(&sender, left);
+                game.set_clock(Some(clock));
+                None
+            }
+            Message::Resign => {
+                game.resign(self.color.inverse());
+                Some(Event::Resigned)
+            }
+            Message::DrawOffer => {
+                self.offer_received = true;
+                Some(Event::DrawOffered)
+            }
+            Message::DrawAccept if self.offered => {
+                self.offered = false;
+                game.agree_draw();
+                Some(Event::DrawAgreed)
+            }
+            Message::DrawDecline if self.offered => {
+                self.offered = false;
+                Some(Event::DrawDeclined)
+            }
+            Message::Chat(text) => Some(Event::Chat(text)),
+            Message::Hello { .. } | Message::DrawAccept | Message::DrawDecline => None,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use super::*;
+use crate::game::clock::{Clock, ManualTime, TimeControl};
+use crate::game::EndResult;
+use crate::Point;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn connect(host_color: Color) -> (Peer, Peer) {
+    connect_from(host_color, &Game::new())
+}
+
+fn connect_from(host_color: Color, start: &Game) -> (Peer, Peer) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let guest = thread::spawn(move || Peer::join(address).unwrap());
+    let host = Peer::host(&listener, host_color, start).unwrap();
+    (host, guest.join().unwrap())
+}
+
+#[test]
+fn test_messages() {
+    let messages = [
+        (
+            "hello 2 black 4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            Message::Hello {
+                version: 2,
+                color: Some(Color::Black),
+                fen: Some("4k3/8/8/8/8/8/8/4K3 w - - 0 1".to_string()),
+            },
+        ),
+        (
+            "hello 2",
+            Message::Hello {
+                version: 2,
+                color: None,
+                fen: None,
+            },
+        ),
+        ("move e7e8q", Message::Move(Turn::parse("e7e8q").unwrap())),
+        ("draw decline", Message::DrawDecline),
+        (
+            "clock 59000 60500",
+            Message::Clock {
+                white: Duration::from_millis(59000),
+                black: Duration::from_millis(60500),
+            },
+        ),
+        ("chat good  luck", Message::Chat("good  luck".to_string())),
+    ];
+    for (line, message) in messages.iter() {
+        assert_eq!(Message::parse(line).as_ref(), Some(message));
+        assert_eq!(message.to_string(), *line);
+    }
+
+    assert_eq!(Message::parse("move e2"), None);
+    assert_eq!(Message::parse("resign now"), None);
+    assert_eq!(Message::parse("hello one"), None);
+    assert_eq!(Message::parse("hello 2 4k3/8/8/8/8/8/8/4K3"), None);
+    assert_eq!(
+        Message::Chat("two\nlines".to_string()).to_string(),
+        "chat two lines"
+    );
+}
+
+#[test]
+fn test_moves_over_loopback() {
+    let (mut host, mut guest) = connect(Color::White);
+    assert_eq!(host.color(), Color::White);
+    assert_eq!(guest.color(), Color::Black);
+    let (mut white, mut black) = (Game::new(), Game::new());
+
+    assert_eq!(white.turn(Point(5, 2), Point(5, 4)), TurnResult::Moved);
+    host.send_turn(&white).unwrap();
+    let e4 = Turn::new(Point(5, 2), Point(5, 4));
+    assert_eq!(
+        guest.wait(&mut black, WAIT),
+        Some(Event::Moved(e4, TurnResult::Moved))
+    );
+    assert_eq!(black.to_fen(), white.to_fen());
+
+    // Moves that are illegal, or made out of turn, are not played.
+    host.send_turn(&white).unwrap();
+    assert_eq!(guest.wait(&mut black, WAIT), Some(Event::Illegal(e4)));
+    assert_eq!(black.to_fen(), white.to_fen());
+
+    assert_eq!(black.turn(Point(4, 7), Point(4, 5)), TurnResult::Moved);
+    guest.send_turn(&black).unwrap();
+    assert!(matches!(
+        host.wait(&mut white, WAIT),
+        Some(Event::Moved(_, TurnResult::Moved))
+    ));
+    assert_eq!(white.to_fen(), black.to_fen());
+
+    guest.chat("good luck").unwrap();
+    assert_eq!(
+        host.wait(&mut white, WAIT),
+        Some(Event::Chat("good luck".to_string()))
+    );
+    assert_eq!(host.poll(&mut white), None);
+
+    assert_eq!(
+        guest.resign(&mut black).unwrap(),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    assert_eq!(host.wait(&mut white, WAIT), Some(Event::Resigned));
+    assert_eq!(white.get_result(), Some(EndResult::Win(Color::White)));
+
+    drop(guest);
+    assert_eq!(host.wait(&mut white, WAIT), Some(Event::Disconnected));
+}
+
+#[test]
+fn test_guest_starts_from_host_position() {
+    let start = Game::chess960(0).unwrap();
+    let (host, guest) = connect_from(Color::White, &start);
+    assert_eq!(host.start(), start.to_fen());
+    assert_eq!(guest.start(), start.to_fen());
+
+    let game = Game::from_fen(guest.start()).unwrap();
+    assert_eq!(game.to_fen(), start.to_fen());
+    assert!(game.get_board().chess960);
+}
+
+#[test]
+fn test_draw_offers() {
+    let (mut host, mut guest) = connect(Color::Black);
+    let (mut white, mut black) = (Game::new(), Game::new());
+
+    assert_eq!(guest.offer_draw(&mut white).unwrap(), TurnResult::Moved);
+    assert_eq!(host.wait(&mut black, WAIT), Some(Event::DrawOffered));
+    host.decline_draw().unwrap();
+    assert_eq!(guest.wait(&mut white, WAIT), Some(Event::DrawDeclined));
+
+    guest.offer_draw(&mut white).unwrap();
+    assert_eq!(host.wait(&mut black, WAIT), Some(Event::DrawOffered));
+    assert_eq!(
+        host.offer_draw(&mut black).unwrap(),
+        TurnResult::GameEnd(EndResult::Tie)
+    );
+    assert_eq!(guest.wait(&mut white, WAIT), Some(Event::DrawAgreed));
+    assert_eq!(white.get_result(), Some(EndResult::Tie));
+}
+
+#[test]
+fn test_clock_sync() {
+    let (mut host, mut guest) = connect(Color::White);
+    let control = TimeControl::fischer(Duration::from_secs(60), Duration::from_secs(2));
+    let (white_time, black_time) = (ManualTime::default(), ManualTime::default());
+    let mut white = Game::new();
+    white.set_clock(Some(Clock::with_source(
+        control.clone(),
+        Arc::new(white_time.clone()),
+    )));
+    let mut black = Game::new();
+    black.set_clock(Some(Clock::with_source(
+        control,
+        Arc::new(black_time.clone()),
+    )));
+
+    white_time.advance(Duration::from_secs(10));
+    white.turn(Point(5, 2), Point(5, 4));
+    host.send_turn(&white).unwrap();
+    // The move arrives later than it was made, which the clock message fixes.
+    black_time.advance(Duration::from_secs(1));
+    guest.wait(&mut black, WAIT);
+    assert_eq!(
+        black.get_clock().unwrap().remaining(&Color::White),
+        Duration::from_secs(61)
+    );
+    assert_eq!(guest.wait(&mut black, WAIT), None);
+    let clock = black.get_clock().unwrap();
+    assert_eq!(clock.remaining(&Color::White), Duration::from_secs(52));
+    assert_eq!(clock.remaining(&Color::Black), Duration::from_secs(60));
+    assert_eq!(clock.running(), Some(Color::Black));
+
+    // The other side cannot set this side's time.
+    host.connection
+        .send(&Message::Clock {
+            white: Duration::from_secs(52),
+            black: Duration::ZERO,
+        })
+        .unwrap();
+    assert_eq!(guest.wait(&mut black, WAIT), None);
+    assert_eq!(
+        black.get_clock().unwrap().remaining(&Color::Black),
+        Duration::from_secs(60)
+    );
+
+    // Black's move only arrives after its time has run out on White's side.
+    white_time.advance(Duration::from_secs(61));
+    assert_eq!(black.turn(Point(4, 7), Point(4, 5)), TurnResult::Moved);
+    guest.send_turn(&black).unwrap();
+    assert_eq!(
+        host.wait(&mut white, WAIT),
+        Some(Event::OutOfTime(EndResult::Win(Color::White)))
+    );
+    assert_eq!(white.get_history().len(), 1);
+}
+
+#[test]
+fn test_version_mismatch() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let guest = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut greeting = String::new();
+        BufReader::new(stream.try_clone().unwrap())
+            .read_line(&mut greeting)
+            .unwrap();
+        writeln!(stream, "hello {}", VERSION + 1).unwrap();
+        greeting
+    });
+    assert!(Peer::host(&listener, Color::White, &Game::new()).is_err());
+    assert_eq!(
+        guest.join().unwrap(),
+        format!("hello {} black {}\n", VERSION, Game::new().to_fen())
+    );
+}
@@ -0,0 +1,192 @@
+use super::{moves, Kind};
+use crate::game::GEOMETRIES;
+use crate::{Move, Point};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+// Fairy pieces are registered once for the whole program, so that `Kind` can
+// stay a small copyable value that refers to them by their symbol. The pieces
+// of Capablanca chess are always there.
+static DEFINITIONS: LazyLock<RwLock<Vec<Definition>>> = LazyLock::new(|| {
+    RwLock::new(vec![
+        Definition {
+            name: "archbishop".to_string(),
+            symbol: 'a',
+            moves: [&moves::BISHOP[..], &moves::KNIGHT[..]].concat(),
+        },
+        Definition {
+            name: "chancellor".to_string(),
+            symbol: 'c',
+            moves: [&moves::ROOK[..], &moves::KNIGHT[..]].concat(),
+        },
+    ])
+});
+
+pub const ARCHBISHOP: Kind = Kind::Custom('a');
+pub const CHANCELLOR: Kind = Kind::Custom('c');
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Definition {
+    pub name: String,
+    pub symbol: char,
+    pub moves: Vec<Move>,
+}
+
+// Returns the existing kind when the same piece is defined twice, and None
+// when the symbol is taken by a different piece.
+pub fn define(name: &str, symbol: char, moves: Vec<Move>) -> Option<Kind> {
+    let definition = Definition {
+        name: name.to_string(),
+        symbol: symbol.to_ascii_lowercase(),
+        moves,
+    };
+    register(vec![definition]).map(|kinds| kinds[0])
+}
+
+// Registers all of the pieces or, when any of them cannot be, none at all.
+fn register(new: Vec<Definition>) -> Option<Vec<Kind>> {
+    let mut definitions = DEFINITIONS.write().unwrap();
+    for (index, definition) in new.iter().enumerate() {
+        let symbol = definition.symbol;
+        if !symbol.is_ascii_lowercase()
+            || "kqbnrp".contains(symbol)
+            || definition.moves.is_empty()
+            || !definition.moves.iter().all(|mv| within_reach(&mv.0))
+        {
+            return None;
+        }
+        let clash = definitions
+            .iter()
+            .chain(new[..index].iter())
+            .any(|other| other.symbol == symbol && other != definition);
+        if clash {
+            return None;
+        }
+    }
+
+    let mut kinds = vec![];
+    for definition in new {
+        kinds.push(Kind::Custom(definition.symbol));
+        if !definitions.contains(&definition) {
+            definitions.push(definition);
+        }
+    }
+    Some(kinds)
+}
+
+// A step longer than the largest board leaves any square it starts from.
+fn within_reach(step: &Point) -> bool {
+    let side = GEOMETRIES
+        .iter()
+        .map(|(width, height, _)| *width.max(height))
+        .max()
+        .unwrap_or(0);
+    step.0.unsigned_abs() as usize <= side && step.1.unsigned_abs() as usize <= side
+}
+
+pub fn definition(kind: &Kind) -> Option<Definition> {
+    match kind {
+        Kind::Custom(symbol) => DEFINITIONS
+            .read()
+            .unwrap()
+            .iter()
+            .find(|definition| definition.symbol == *symbol)
+            .cloned(),
+        _ => None,
+    }
+}
+
+pub fn find(symbol: char) -> Option<Kind> {
+    let kind = Kind::Custom(symbol.to_ascii_lowercase());
+    definition(&kind).map(|_| kind)
+}
+
+// Only pieces defined here can be loaded.
+#[cfg(feature = "serde")]
+pub(super) fn deserialize_symbol<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<char, D::Error> {
+    let symbol: char = serde::Deserialize::deserialize(deserializer)?;
+    match definition(&Kind::Custom(symbol)) {
+        Some(_) => Ok(symbol),
+        None => Err(serde::de::Error::custom(format!(
+            "unknown fairy piece {}",
+            symbol
+        ))),
+    }
+}
+
+// The vector in every direction it can be turned or mirrored, so (2, 1)
+// gives all eight knight jumps.
+pub fn symmetric(step: Point, rides: bool) -> Vec<Move> {
+    let mut moves: Vec<Move> = vec![];
+    for &(x, y) in [(step.0, step.1), (step.1, step.0)].iter() {
+        for &(sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)].iter() {
+            let mv = Move(Point(x * sx, y * sy), rides);
+            if !moves.contains(&mv) {
+                moves.push(mv);
+            }
+        }
+    }
+    moves
+}
+
+// One piece per line: a name, a symbol and any number of "leap x,y" or
+// "ride x,y" vectors, which are expanded in all directions. For example
+//
+//     # name      symbol  moves
+//     camel       l       leap 3,1
+//     archbishop  a       ride 1,1 leap 2,1
+pub fn parse(text: &str) -> Option<Vec<Kind>> {
+    let mut definitions = vec![];
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace();
+        let name = match words.next() {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let mut symbols = words.next()?.chars();
+        let symbol = symbols.next()?;
+        if symbols.next().is_some() {
+            return None;
+        }
+
+        let mut moves: Vec<Move> = vec![];
+        while let Some(mode) = words.next() {
+            let rides = match mode {
+                "leap" => false,
+                "ride" => true,
+                _ => return None,
+            };
+            let (x, y) = words.next()?.split_once(',')?;
+            let step = Point(x.parse().ok()?, y.parse().ok()?);
+            if step == Point(0, 0) || !within_reach(&step) {
+                return None;
+            }
+            for mv in symmetric(step, rides) {
+                if !moves.contains(&mv) {
+                    moves.push(mv);
+                }
+            }
+        }
+
+        definitions.push(Definition {
+            name: name.to_string(),
+            symbol: symbol.to_ascii_lowercase(),
+            moves,
+        });
+    }
+
+    // Nothing is registered until every line has been read.
+    register(definitions)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Kind>> {
+    parse(&fs::read_to_string(path)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid piece definition"))
+}
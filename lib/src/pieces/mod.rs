@@ -1,15 +1,19 @@
-use crate::{Color, Move};
+use crate::{Color, Move, Point};
 
 #[cfg(test)]
 mod tests;
 
+pub mod custom;
 pub mod moves;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub color: Color,
     pub kind: Kind,
     pub has_moved: bool,
+    // Promoted pieces turn back into pawns when captured in crazyhouse.
+    pub promoted: bool,
 }
 
 impl Piece {
@@ -18,6 +22,7 @@ impl Piece {
             color,
             kind,
             has_moved: false,
+            promoted: false,
         }
     }
 
@@ -29,11 +34,42 @@ impl Piece {
             Kind::Knight => moves::KNIGHT.to_vec(),
             Kind::Rook => moves::ROOK.to_vec(),
             Kind::Pawn => moves::PAWN.to_vec(),
+            Kind::Custom(_) => {
+                let moves = custom::definition(&self.kind).map_or(vec![], |piece| piece.moves);
+                // Custom vectors are written from White's side of the board.
+                match self.color {
+                    Color::White => moves,
+                    Color::Black => moves
+                        .into_iter()
+                        .map(|mv| Move(Point(mv.0 .0, -mv.0 .1), mv.1))
+                        .collect(),
+                }
+            }
         }
     }
+
+    pub fn symbol(&self) -> char {
+        match self.color {
+            Color::White => self.kind.symbol().to_ascii_uppercase(),
+            Color::Black => self.kind.symbol(),
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        let color = if symbol.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        Kind::from_symbol(symbol).map(|kind| Piece::new(color, kind))
+    }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+// Fairy pieces are saved by symbol, so whoever loads them has to define the
+// same pieces.
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     King,
     Queen,
@@ -41,4 +77,38 @@ pub enum Kind {
     Knight,
     Rook,
     Pawn,
+    // A fairy piece from the custom module, named by its lower case symbol.
+    Custom(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "custom::deserialize_symbol")
+        )]
+        char,
+    ),
+}
+
+impl Kind {
+    pub fn symbol(&self) -> char {
+        match self {
+            Kind::King => 'k',
+            Kind::Queen => 'q',
+            Kind::Bishop => 'b',
+            Kind::Knight => 'n',
+            Kind::Rook => 'r',
+            Kind::Pawn => 'p',
+            Kind::Custom(symbol) => *symbol,
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol.to_ascii_lowercase() {
+            'k' => Some(Kind::King),
+            'q' => Some(Kind::Queen),
+            'b' => Some(Kind::Bishop),
+            'n' => Some(Kind::Knight),
+            'r' => Some(Kind::Rook),
+            'p' => Some(Kind::Pawn),
+            symbol => custom::find(symbol),
+        }
+    }
 }
@@ -10,6 +10,7 @@ fn test_new_piece() {
             color: Color::White,
             kind: Kind::King,
             has_moved: false,
+            promoted: false,
         }
     );
 }
@@ -27,3 +28,93 @@ fn test_get_moves() {
         ]
     )
 }
+
+#[test]
+fn test_symbols() {
+    assert_eq!(Piece::new(Color::White, Kind::Knight).symbol(), 'N');
+    assert_eq!(Piece::new(Color::Black, Kind::Queen).symbol(), 'q');
+    assert_eq!(
+        Piece::from_symbol('R'),
+        Some(Piece::new(Color::White, Kind::Rook))
+    );
+    assert_eq!(Piece::from_symbol('x'), None);
+}
+
+#[test]
+fn test_symmetric_vectors() {
+    assert_eq!(custom::symmetric(Point(1, 0), true).len(), 4);
+    assert_eq!(custom::symmetric(Point(1, 1), false).len(), 4);
+
+    let mut knight = custom::symmetric(Point(2, 1), false);
+    let mut expected = moves::KNIGHT.to_vec();
+    knight.sort_by_key(|mv| mv.0);
+    expected.sort_by_key(|mv| mv.0);
+    assert_eq!(knight, expected);
+}
+
+#[test]
+fn test_define_custom_pieces() {
+    let camel = custom::define("camel", 'l', custom::symmetric(Point(3, 1), false)).unwrap();
+    assert_eq!(
+        custom::define("camel", 'L', custom::symmetric(Point(3, 1), false)),
+        Some(camel)
+    );
+    assert_eq!(
+        custom::define("zebra", 'l', custom::symmetric(Point(3, 2), false)),
+        None
+    );
+    assert_eq!(custom::define("queen", 'q', moves::QUEEN.to_vec()), None);
+    assert_eq!(
+        custom::define("giant", 'g', vec![Move(Point(127, 0), false)]),
+        None
+    );
+
+    assert_eq!(camel, Kind::Custom('l'));
+    assert_eq!(camel.symbol(), 'l');
+    assert_eq!(Kind::from_symbol('L'), Some(camel));
+    assert_eq!(
+        Piece::from_symbol('l'),
+        Some(Piece::new(Color::Black, camel))
+    );
+    assert_eq!(Piece::new(Color::White, camel).get_moves().len(), 8);
+}
+
+#[test]
+fn test_parse_custom_pieces() {
+    let kinds = custom::parse(
+        "# name symbol moves\n\
+         nightrider h ride 2,1\n\
+         \n\
+         amazon z ride 1,1 ride 1,0 leap 2,1 # queen and knight\n",
+    )
+    .unwrap();
+    assert_eq!(kinds.len(), 2);
+
+    let nightrider = custom::definition(&kinds[0]).unwrap();
+    assert_eq!(nightrider.name, "nightrider");
+    assert!(nightrider.moves.iter().all(|mv| mv.1));
+    assert_eq!(custom::definition(&kinds[1]).unwrap().moves.len(), 16);
+
+    assert_eq!(custom::parse("wazir w step 1,0"), None);
+    assert_eq!(custom::parse("wazir w leap 0,0"), None);
+    assert_eq!(custom::parse("wazir ww leap 1,0"), None);
+    assert_eq!(custom::parse("giant t leap 127,0"), None);
+    assert_eq!(custom::parse("giant t leap -128,1"), None);
+    assert_eq!(custom::parse("giant t leap 11,0"), None);
+    assert!(custom::parse("giant t leap 10,0").is_some());
+
+    // A bad line leaves the good ones before it unregistered.
+    assert_eq!(custom::parse("giraffe g leap 4,1\nwazir w step 1,0"), None);
+    assert_eq!(Kind::from_symbol('g'), None);
+    assert_eq!(custom::parse("giraffe g leap 4,1\ndragon g ride 1,1"), None);
+    assert_eq!(Kind::from_symbol('g'), None);
+}
+
+#[test]
+fn test_black_custom_moves_are_mirrored() {
+    let soldier = custom::define("soldier", 'o', vec![Move(Point(0, 1), false)]).unwrap();
+    assert_eq!(
+        Piece::new(Color::Black, soldier).get_moves(),
+        vec![Move(Point(0, -1), false)]
+    );
+}
@@ -0,0 +1,364 @@
+#[cfg(test)]
+mod tests;
+
+use crate::game::clock::{Clock, TimeControl};
+use crate::game::{variant, EndResult, Game, Turn, TurnResult};
+use crate::Color;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+// A game kept between sittings, as in correspondence chess where each player
+// opens the game, makes a move and puts it away again. Saved games are plain
+// text, one "key value" line each:
+//
+//     White alice
+//     Black bob
+//     Variant atomic              only for variants
+//     Started 1700000000          seconds since the Unix epoch
+//     LastMove 1700086400
+//     DaysPerMove 3               only with a limit
+//     Clock 5+3 297000 300000     the control, then White's and Black's time
+//     Result 1-0                  only once the game is over
+//     Fen <start position>
+//     Moves e2e4 e7e5 g1f3
+//
+// Loading replays the moves from the start, so the history survives too. A
+// clock only runs while the game is open.
+pub struct SavedGame {
+    pub id: String,
+    pub white: String,
+    pub black: String,
+    // The longest a player may think over one move.
+    pub days_per_move: Option<u32>,
+    pub started: SystemTime,
+    pub last_move: SystemTime,
+    game: Game,
+}
+
+impl SavedGame {
+    pub fn new(id: &str, white: &str, black: &str, game: Game, now: SystemTime) -> Self {
+        SavedGame {
+            id: id.to_string(),
+            white: white.to_string(),
+            black: black.to_string(),
+            days_per_move: None,
+            started: now,
+            last_move: now,
+            game,
+        }
+    }
+
+    pub fn get_game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn player(&self, color: Color) -> &str {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    // The player whose move it is, while the game is still going.
+    pub fn to_move(&self) -> Option<&str> {
+        match self.game.get_result() {
+            Some(_) => None,
+            None => Some(self.player(self.game.color)),
+        }
+    }
+
+    pub fn deadline(&self) -> Option<SystemTime> {
+        let days = self.days_per_move?;
+        if self.game.get_result().is_some() {
+            return None;
+        }
+        Some(self.last_move + Duration::from_secs(u64::from(days) * DAY))
+    }
+
+    // Settles a game whose side to move let the deadline pass.
+    pub fn check_deadline(&mut self, now: SystemTime) -> Option<EndResult> {
+        if self.deadline().is_none_or(|deadline| now <= deadline) {
+            return None;
+        }
+        match self.game.forfeit_on_time() {
+            TurnResult::GameEnd(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    // Plays a whole turn for the named player, who has to be the one to move.
+    pub fn play(&mut self, player: &str, turn: &Turn, now: SystemTime) -> TurnResult {
+        if let Some(result) = self.check_deadline(now) {
+            return TurnResult::GameEnd(result);
+        }
+        if self.to_move() != Some(player) {
+            return TurnResult::Failed;
+        }
+
+        let result = self.game.play(turn);
+        if result != TurnResult::Failed {
+            self.last_move = now;
+        }
+        result
+    }
+
+    pub fn resign(&mut self, player: &str) -> TurnResult {
+        if self.white == player {
+            self.game.resign(Color::White)
+        } else if self.black == player {
+            self.game.resign(Color::Black)
+        } else {
+            TurnResult::Failed
+        }
+    }
+
+    pub fn parse(id: &str, text: &str) -> Option<SavedGame> {
+        let mut values = HashMap::new();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            values.insert(key, value);
+        }
+        let time = |key| values.get(key)?.parse().ok().map(from_seconds);
+
+        let variant = match values.get("Variant") {
+            Some(name) => Some(variant::by_name(name)?),
+            None => None,
+        };
+        let mut game = Game::from_fen_with_variant(values.get("Fen")?, variant)?;
+        let control = match values.get("Clock") {
+            Some(clock) => {
+                let mut parts = clock.split(' ');
+                let control = TimeControl::parse(parts.next()?)?;
+                let mut millis = || parts.next()?.parse().ok().map(Duration::from_millis);
+                Some((control, millis()?, millis()?))
+            }
+            None => None,
+        };
+        // The clock runs through the replay so that it counts the moves made
+        // in each stage, and then gets the saved times.
+        if let Some((control, _, _)) = &control {
+            game.set_clock(Some(Clock::new(control.clone())));
+        }
+        for notation in values.get("Moves")?.split_whitespace() {
+            if game.play(&Turn::parse(notation)?) == TurnResult::Failed {
+                return None;
+            }
+        }
+        if let Some((_, white, black)) = control {
+            let mut clock = game.get_clock().cloned()?;
+            clock.set(&Color::White, white);
+            clock.set(&Color::Black, black);
+            game.set_clock(Some(clock));
+        }
+
+        // Resignations, agreed draws and forfeits are not in the moves.
+        match values.get("Result").copied() {
+            _ if game.get_result().is_some() => {}
+            Some("1-0") => {
+                game.resign(Color::Black);
+            }
+            Some("0-1") => {
+                game.resign(Color::White);
+            }
+            Some("1/2-1/2") => {
+                game.agree_draw();
+            }
+            Some(_) => return None,
+            None => {}
+        }
+
+        Some(SavedGame {
+            id: id.to_string(),
+            white: values.get("White")?.to_string(),
+            black: values.get("Black")?.to_string(),
+            days_per_move: match values.get("DaysPerMove") {
+                Some(days) => Some(days.parse().ok()?),
+                None => None,
+            },
+            started: time("Started")?,
+            last_move: time("LastMove")?,
+            game,
+        })
+    }
+
+    // The position before the first move.
+    fn start_fen(&self) -> String {
+        let mut game = self.game.clone();
+        game.set_clock(None);
+        while game.undo() {}
+        game.to_fen()
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Values run to the end of their line.
+        let line = |value: &str| value.replace(['\r', '\n'], " ");
+        writeln!(f, "White {}", line(&self.white))?;
+        writeln!(f, "Black {}", line(&self.black))?;
+        if let Some(variant) = self.game.get_variant() {
+            writeln!(f, "Variant {}", variant.name())?;
+        }
+        writeln!(f, "Started {}", seconds(self.started))?;
+        writeln!(f, "LastMove {}", seconds(self.last_move))?;
+        if let Some(days) = self.days_per_move {
+            writeln!(f, "DaysPerMove {}", days)?;
+        }
+        if let Some(clock) = self.game.get_clock() {
+            writeln!(
+                f,
+                "Clock {} {} {}",
+                clock.get_control(),
+                clock.remaining(&Color::White).as_millis(),
+                clock.remaining(&Color::Black).as_millis()
+            )?;
+        }
+        match self.game.get_result() {
+            Some(EndResult::Win(Color::White)) => writeln!(f, "Result 1-0")?,
+            Some(EndResult::Win(Color::Black)) => writeln!(f, "Result 0-1")?,
+            Some(EndResult::Tie) => writeln!(f, "Result 1/2-1/2")?,
+            None => {}
+        }
+        writeln!(f, "Fen {}", self.start_fen())?;
+        let moves: Vec<String> = self
+            .game
+            .get_history()
+            .iter()
+            .map(|turn| turn.to_string())
+            .collect();
+        writeln!(f, "Moves {}", moves.join(" "))
+    }
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn from_seconds(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+// The games read from a store. Files that could not be read are kept apart
+// with the reason, by id, so that one bad file hides no other game.
+#[derive(Default)]
+pub struct Listing {
+    pub games: Vec<SavedGame>,
+    pub unreadable: Vec<(String, io::Error)>,
+}
+
+// A directory of saved games, one "<id>.game" file each.
+pub struct Store {
+    directory: PathBuf,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        Ok(Store {
+            directory: directory.as_ref().to_path_buf(),
+        })
+    }
+
+    // Starts a game under the first free number.
+    pub fn create(
+        &self,
+        white: &str,
+        black: &str,
+        game: Game,
+        days_per_move: Option<u32>,
+        now: SystemTime,
+    ) -> io::Result<SavedGame> {
+        let mut number = 1;
+        while self.path(&number.to_string())?.exists() {
+            number += 1;
+        }
+        let mut saved = SavedGame::new(&number.to_string(), white, black, game, now);
+        saved.days_per_move = days_per_move;
+        self.save(&saved)?;
+        Ok(saved)
+    }
+
+    // Writes the game next to its old file first, so that a failed save
+    // leaves the old one whole.
+    pub fn save(&self, game: &SavedGame) -> io::Result<()> {
+        let path = self.path(&game.id)?;
+        let partial = path.with_extension("partial");
+        fs::write(&partial, game.to_string())?;
+        fs::rename(&partial, &path)
+    }
+
+    pub fn load(&self, id: &str) -> io::Result<SavedGame> {
+        let text = fs::read_to_string(self.path(id)?)?;
+        SavedGame::parse(id, &text).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("bad saved game {}", id))
+        })
+    }
+
+    pub fn list(&self) -> io::Result<Listing> {
+        let mut listing = Listing::default();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "game")
+            {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    match self.load(id) {
+                        Ok(game) => listing.games.push(game),
+                        Err(error) => listing.unreadable.push((id.to_string(), error)),
+                    }
+                }
+            }
+        }
+        listing
+            .games
+            .sort_by(|a, b| natural(&a.id).cmp(&natural(&b.id)));
+        listing
+            .unreadable
+            .sort_by(|a, b| natural(&a.0).cmp(&natural(&b.0)));
+        Ok(listing)
+    }
+
+    // The games waiting on the player, most urgent first. Games whose
+    // deadline has passed are settled and saved along the way.
+    pub fn awaiting(&self, player: &str, now: SystemTime) -> io::Result<Listing> {
+        let mut listing = self.list()?;
+        let mut games = vec![];
+        for mut game in listing.games {
+            if game.check_deadline(now).is_some() {
+                self.save(&game)?;
+            } else if game.to_move() == Some(player) {
+                games.push(game);
+            }
+        }
+        games.sort_by_key(|game| (game.deadline().is_none(), game.deadline()));
+        listing.games = games;
+        Ok(listing)
+    }
+
+    // Ids become file names, so only plain ones are allowed.
+    fn path(&self, id: &str) -> io::Result<PathBuf> {
+        let plain = id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if id.is_empty() || !plain {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("bad game id {:?}", id),
+            ));
+        }
+        Ok(self.directory.join(format!("{}.game", id)))
+    }
+}
+
+// Sorts numbered games by number and the rest after them by name.
+fn natural(id: &str) -> (u64, &str) {
+    (id.parse().unwrap_or(u64::MAX), id)
+}
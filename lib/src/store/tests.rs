@@ -0,0 +1,201 @@
+use super::*;
+use crate::Point;
+use std::env;
+use std::process;
+
+const DAYS: u64 = 24 * 60 * 60;
+
+// A fresh directory for each test, since tests run side by side.
+fn store(name: &str) -> Store {
+    let directory = env::temp_dir().join(format!("chess-store-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    Store::open(directory).unwrap()
+}
+
+fn at(days: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_700_000_000 + days * DAYS)
+}
+
+fn turn(notation: &str) -> Turn {
+    Turn::parse(notation).unwrap()
+}
+
+#[test]
+fn test_save_and_load() {
+    let store = store("save");
+    let mut game = Game::duck_chess();
+    game.set_clock(Some(Clock::new(TimeControl::parse("40/90+30:30").unwrap())));
+    let mut saved = store.create("alice", "bob", game, Some(3), at(0)).unwrap();
+    assert_eq!(saved.id, "1");
+    assert_eq!(
+        saved.play("alice", &turn("e2e4,e6"), at(1)),
+        TurnResult::Moved
+    );
+    assert_eq!(
+        saved.play("bob", &turn("d7d5,d3"), at(2)),
+        TurnResult::Moved
+    );
+    store.save(&saved).unwrap();
+
+    // White's clock runs on while the game is saved and loaded.
+    let loaded = store.load("1").unwrap();
+    let unclocked = |game: &SavedGame| {
+        let text = game.to_string();
+        text.lines()
+            .filter(|line| !line.starts_with("Clock"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert_eq!(unclocked(&loaded), unclocked(&saved));
+    assert_eq!(loaded.get_game().to_fen(), saved.get_game().to_fen());
+    assert_eq!(
+        loaded.get_game().get_history(),
+        saved.get_game().get_history()
+    );
+    assert_eq!(loaded.get_game().get_variant().unwrap().name(), "duck");
+    assert_eq!(loaded.days_per_move, Some(3));
+    assert_eq!(loaded.last_move, at(2));
+    assert_eq!(loaded.deadline(), Some(at(5)));
+    assert_eq!(loaded.to_move(), Some("alice"));
+    let clock = loaded.get_game().get_clock().unwrap();
+    assert_eq!(clock.running(), Some(Color::White));
+    assert_eq!(
+        clock.remaining(&Color::Black).as_millis(),
+        saved
+            .get_game()
+            .get_clock()
+            .unwrap()
+            .remaining(&Color::Black)
+            .as_millis()
+    );
+    assert!(clock.remaining(&Color::White) > Duration::from_secs(5400));
+
+    assert!(store.load("2").is_err());
+    assert!(store.load("../1").is_err());
+    fs::write(store.directory.join("3.game"), "White alice\n").unwrap();
+    assert_eq!(
+        store.load("3").map(|_| ()).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_correspondence_turns() {
+    let store = store("turns");
+    let mut saved = store
+        .create("alice", "bob", Game::new(), None, at(0))
+        .unwrap();
+    assert_eq!(saved.play("bob", &turn("e7e5"), at(0)), TurnResult::Failed);
+    assert_eq!(
+        saved.play("carol", &turn("e2e4"), at(0)),
+        TurnResult::Failed
+    );
+    assert_eq!(
+        saved.play("alice", &turn("e2e5"), at(0)),
+        TurnResult::Failed
+    );
+    assert_eq!(
+        saved.play("alice", &turn("e2e4"), at(40)),
+        TurnResult::Moved
+    );
+    assert_eq!(saved.deadline(), None);
+    store.save(&saved).unwrap();
+
+    // Each player picks the game up from the store, moves and saves.
+    let mut later = store.load(&saved.id).unwrap();
+    assert_eq!(
+        later.play("alice", &turn("d2d4"), at(41)),
+        TurnResult::Failed
+    );
+    assert_eq!(later.play("bob", &turn("e7e5"), at(41)), TurnResult::Moved);
+    assert!(later
+        .get_game()
+        .get_board()
+        .at_point(&Point(5, 5))
+        .is_some());
+    assert_eq!(
+        later.resign("bob"),
+        TurnResult::GameEnd(EndResult::Win(Color::White))
+    );
+    store.save(&later).unwrap();
+    let finished = store.load(&saved.id).unwrap();
+    assert_eq!(
+        finished.get_game().get_result(),
+        Some(EndResult::Win(Color::White))
+    );
+    assert_eq!(finished.to_move(), None);
+}
+
+#[test]
+fn test_awaiting_and_deadlines() {
+    let store = store("awaiting");
+    let first = store
+        .create("alice", "bob", Game::new(), Some(2), at(0))
+        .unwrap();
+    let mut second = store
+        .create("bob", "alice", Game::new(), Some(1), at(0))
+        .unwrap();
+    second.play("bob", &turn("e2e4"), at(0));
+    store.save(&second).unwrap();
+    let mut third = store
+        .create("alice", "carol", Game::new(), None, at(0))
+        .unwrap();
+    third.play("alice", &turn("g2g4"), at(0));
+    store.save(&third).unwrap();
+    let ids = |listing: Listing| {
+        listing
+            .games
+            .into_iter()
+            .map(|game| game.id)
+            .collect::<Vec<_>>()
+    };
+
+    // The closest deadline comes first.
+    assert_eq!(ids(store.awaiting("alice", at(0)).unwrap()), ["2", "1"]);
+    assert_eq!(ids(store.awaiting("carol", at(0)).unwrap()), ["3"]);
+    assert_eq!(ids(store.list().unwrap()), ["1", "2", "3"]);
+
+    // Letting the time run out loses, and the store remembers.
+    assert_eq!(
+        ids(store
+            .awaiting("alice", at(1) + Duration::from_secs(1))
+            .unwrap()),
+        ["1"]
+    );
+    assert_eq!(
+        store.load("2").unwrap().get_game().get_result(),
+        Some(EndResult::Win(Color::White))
+    );
+    let mut first = store.load(&first.id).unwrap();
+    assert_eq!(
+        first.play("alice", &turn("e2e4"), at(3)),
+        TurnResult::GameEnd(EndResult::Win(Color::Black))
+    );
+}
+
+#[test]
+fn test_list_skips_unreadable_games() {
+    let store = store("unreadable");
+    store
+        .create("alice", "bob", Game::new(), None, at(0))
+        .unwrap();
+    store
+        .create("alice", "carol", Game::new(), None, at(0))
+        .unwrap();
+    fs::write(store.directory.join("1.game"), "Fen nonsense").unwrap();
+    fs::write(store.directory.join("bad id.game"), "").unwrap();
+
+    let listing = store.list().unwrap();
+    let ids: Vec<&str> = listing.games.iter().map(|game| game.id.as_str()).collect();
+    assert_eq!(ids, ["2"]);
+    let unreadable: Vec<&str> = listing
+        .unreadable
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .collect();
+    assert_eq!(unreadable, ["1", "bad id"]);
+
+    let listing = store.awaiting("alice", at(0)).unwrap();
+    assert_eq!(listing.games.len(), 1);
+    assert_eq!(listing.unreadable.len(), 2);
+}
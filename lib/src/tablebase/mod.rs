@@ -0,0 +1,339 @@
+use crate::game::{EndResult, Game, Turn};
+use crate::pieces::Kind;
+use crate::*;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+mod table;
+
+#[cfg(test)]
+mod tests;
+
+use table::{Format, Position, Probe, Table};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Self {
+        match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            1 => Wdl::CursedWin,
+            2 => Wdl::Win,
+            _ => Wdl::Draw,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum State {
+    Ok,
+    ZeroingBestMove,
+}
+
+type Name = (String, Format);
+
+#[derive(Default)]
+pub struct Tablebase {
+    files: HashMap<Name, PathBuf>,
+    tables: Mutex<HashMap<Name, Option<Arc<Table>>>>,
+    max_pieces: usize,
+}
+
+impl Tablebase {
+    // Several directories can be given separated like PATH, for example
+    // "/syzygy/wdl:/syzygy/dtz".
+    pub fn open<P: AsRef<OsStr>>(paths: P) -> io::Result<Tablebase> {
+        let mut tablebase = Tablebase::default();
+
+        for directory in env::split_paths(&paths) {
+            for entry in fs::read_dir(directory)? {
+                let path = entry?.path();
+                let format = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("rtbw") => Format::Wdl,
+                    Some("rtbz") => Format::Dtz,
+                    _ => continue,
+                };
+                let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                };
+                let pieces = match name.split_once('v') {
+                    Some((white, black)) => {
+                        match (table::parse_side(white), table::parse_side(black)) {
+                            (Some(white), Some(black)) => white.len() + black.len(),
+                            _ => continue,
+                        }
+                    }
+                    None => continue,
+                };
+                if pieces > table::MAX_PIECES {
+                    continue;
+                }
+
+                if format == Format::Wdl {
+                    tablebase.max_pieces = tablebase.max_pieces.max(pieces);
+                }
+                tablebase.files.insert((name, format), path);
+            }
+        }
+
+        Ok(tablebase)
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn table(&self, name: &str, format: Format) -> Option<Arc<Table>> {
+        let key = (name.to_string(), format);
+        let mut tables = self.tables.lock().unwrap();
+
+        tables
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let bytes = fs::read(self.files.get(&key)?).ok()?;
+                Table::parse(name, format, bytes).map(Arc::new)
+            })
+            .clone()
+    }
+
+    fn probe_table(&self, game: &Game, format: Format, wdl: i32) -> Option<Probe> {
+        let board = game.get_board();
+        let mut position = Position {
+            pieces: vec![],
+            white_to_move: game.color == Color::White,
+        };
+        let mut white = String::new();
+        let mut black = String::new();
+
+        for kind in [
+            Kind::King,
+            Kind::Queen,
+            Kind::Rook,
+            Kind::Bishop,
+            Kind::Knight,
+            Kind::Pawn,
+        ]
+        .iter()
+        {
+            for piece in board.current.iter().flatten() {
+                if piece.kind == *kind {
+                    match piece.color {
+                        Color::White => white.push(kind.symbol().to_ascii_uppercase()),
+                        Color::Black => black.push(kind.symbol().to_ascii_uppercase()),
+                    }
+                }
+            }
+        }
+
+        for (index, piece) in board.current.iter().enumerate() {
+            if let Some(piece) = piece {
+                let code = match piece.kind {
+                    Kind::Pawn => 1,
+                    Kind::Knight => 2,
+                    Kind::Bishop => 3,
+                    Kind::Rook => 4,
+                    Kind::Queen => 5,
+                    Kind::King => 6,
+                    Kind::Custom(_) => return None,
+                };
+                let color = if piece.color == Color::White { 0 } else { 8 };
+                position.pieces.push((index, code + color));
+            }
+        }
+
+        if position.pieces.len() == 2 {
+            return Some(Probe::Value(0));
+        }
+
+        let name = format!("{}v{}", white, black);
+        if let Some(table) = self.table(&name, format) {
+            return table.probe(&position, false, wdl);
+        }
+
+        let mirrored = format!("{}v{}", black, white);
+        self.table(&mirrored, format)?.probe(&position, true, wdl)
+    }
+
+    fn probe_wdl_table(&self, game: &Game) -> Option<i32> {
+        match self.probe_table(game, Format::Wdl, 0)? {
+            Probe::Value(value) => Some(value),
+            Probe::ChangeSide => None,
+        }
+    }
+
+    // Captures are searched before probing because positions where capturing
+    // is best are stored as "don't care" values, and the tables do not know
+    // about en passant.
+    fn search(&self, game: &mut Game, zeroing_moves: bool) -> Option<(i32, State)> {
+        match game.get_result() {
+            Some(EndResult::Win(_)) => return Some((-2, State::Ok)),
+            Some(EndResult::Tie) => return Some((0, State::Ok)),
+            None => {}
+        }
+
+        let turns = game.legal_turns();
+        let mut searched = 0;
+        let mut best = -2;
+
+        for turn in turns.iter() {
+            if !is_capture(game, turn) && (!zeroing_moves || !is_pawn_move(game, turn)) {
+                continue;
+            }
+            searched += 1;
+
+            game.play(turn);
+            let value = self.search(game, false).map(|(value, _)| -value);
+            game.undo();
+
+            let value = value?;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Some((value, State::ZeroingBestMove));
+                }
+            }
+        }
+
+        let no_more_moves = searched > 0 && searched == turns.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            self.probe_wdl_table(game)?
+        };
+
+        if best >= value {
+            let state = if best > 0 || no_more_moves {
+                State::ZeroingBestMove
+            } else {
+                State::Ok
+            };
+            return Some((best, state));
+        }
+
+        Some((value, State::Ok))
+    }
+
+    fn dtz(&self, game: &mut Game) -> Option<i32> {
+        let (wdl, state) = self.search(game, true)?;
+
+        if wdl == 0 {
+            return Some(0);
+        }
+
+        if state == State::ZeroingBestMove {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.probe_table(game, Format::Dtz, wdl)? {
+            Probe::Value(dtz) => {
+                let cursed = if wdl.abs() == 1 { 100 } else { 0 };
+                return Some((dtz + cursed) * wdl.signum());
+            }
+            Probe::ChangeSide => {}
+        }
+
+        let mut shortest = 0xffff;
+        for turn in game.legal_turns() {
+            let zeroing = is_capture(game, &turn) || is_pawn_move(game, &turn);
+
+            game.play(&turn);
+            let dtz = if zeroing {
+                self.search(game, false)
+                    .map(|(value, _)| -dtz_before_zeroing(value))
+            } else {
+                self.dtz(game).map(|dtz| -dtz)
+            };
+            let mates = matches!(game.get_result(), Some(EndResult::Win(_)));
+            game.undo();
+
+            let mut dtz = dtz?;
+            if dtz == 1 && mates {
+                shortest = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < shortest && dtz.signum() == wdl.signum() {
+                shortest = dtz;
+            }
+        }
+
+        Some(if shortest == 0xffff { -1 } else { shortest })
+    }
+
+    fn can_probe(&self, game: &Game) -> bool {
+        let board = game.get_board();
+        let corners = [*board.width.start(), *board.width.end()];
+
+        board.width == (1..=8)
+            && board.height == (1..=8)
+            && !board.crazyhouse
+            && game.get_variant().is_none()
+            && board.current.iter().flatten().count() <= self.max_pieces.max(2)
+            && [Color::White, Color::Black].iter().all(|color| {
+                corners
+                    .iter()
+                    .all(|corner| !board.can_castle(color, *corner))
+            })
+    }
+}
+
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+fn is_capture(game: &Game, turn: &Turn) -> bool {
+    game.get_board().is_capture(&turn.source, &turn.target)
+}
+
+fn is_pawn_move(game: &Game, turn: &Turn) -> bool {
+    game.get_board()
+        .at_point(&turn.source)
+        .is_some_and(|piece| piece.kind == Kind::Pawn)
+}
+
+impl Game {
+    pub fn probe_wdl(&self, tablebase: &Tablebase) -> Option<Wdl> {
+        if !tablebase.can_probe(self) {
+            return None;
+        }
+
+        let (value, _) = tablebase.search(&mut self.clone(), false)?;
+        Some(Wdl::from_value(value))
+    }
+
+    // Distance to zeroing in plies: positive when the side to move wins, 100
+    // more than the real distance for wins and losses spoiled by the fifty-move
+    // rule, and 0 for draws.
+    pub fn probe_dtz(&self, tablebase: &Tablebase) -> Option<i32> {
+        if !tablebase.can_probe(self) {
+            return None;
+        }
+
+        tablebase.dtz(&mut self.clone())
+    }
+}
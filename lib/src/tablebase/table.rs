@@ -0,0 +1,827 @@
+use std::sync::OnceLock;
+
+pub const MAX_PIECES: usize = 7;
+
+pub const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+pub const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+pub enum Format {
+    Wdl,
+    Dtz,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Probe {
+    Value(i32),
+    ChangeSide,
+}
+
+// Squares are numbered a1 = 0, b1 = 1, ..., h8 = 63 and pieces use the codes
+// of the tablebase files: 1-6 for white pawn to king and 9-14 for black.
+pub struct Position {
+    pub pieces: Vec<(usize, u8)>,
+    pub white_to_move: bool,
+}
+
+pub struct Encoding {
+    pub map_b1h1h7: [u64; 64],
+    pub map_a1d1d4: [u64; 64],
+    pub map_kk: [[u64; 64]; 10],
+    pub binomial: [[u64; 64]; 6],
+    pub map_pawns: [u64; 64],
+    pub lead_pawn_idx: [[u64; 64]; 6],
+    pub lead_pawns_size: [[u64; 4]; 6],
+}
+
+fn file_of(square: usize) -> usize {
+    square & 7
+}
+
+fn rank_of(square: usize) -> usize {
+    square >> 3
+}
+
+fn off_a1h8(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+pub fn encoding() -> &'static Encoding {
+    static ENCODING: OnceLock<Encoding> = OnceLock::new();
+    ENCODING.get_or_init(Encoding::new)
+}
+
+impl Encoding {
+    fn new() -> Self {
+        let mut encoding = Encoding {
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_a1h8(square) < 0 {
+                encoding.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal = vec![];
+        code = 0;
+        for square in 0..28 {
+            if off_a1h8(square) < 0 && file_of(square) <= 3 {
+                encoding.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_a1h8(square) == 0 && file_of(square) <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            encoding.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        let mut both_on_diagonal = vec![];
+        code = 0;
+        for index in 0..10 {
+            for first in 0..28 {
+                if encoding.map_a1d1d4[first] != index as u64 || (index == 0 && first != 1) {
+                    continue;
+                }
+
+                for second in 0..64 {
+                    let adjacent = (file_of(first) as i32 - file_of(second) as i32).abs() <= 1
+                        && (rank_of(first) as i32 - rank_of(second) as i32).abs() <= 1;
+
+                    if adjacent || (off_a1h8(first) == 0 && off_a1h8(second) > 0) {
+                        continue;
+                    } else if off_a1h8(first) == 0 && off_a1h8(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        encoding.map_kk[index][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            encoding.map_kk[index][second] = code;
+            code += 1;
+        }
+
+        encoding.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6.min(n + 1) {
+                encoding.binomial[k][n] = if k > 0 {
+                    encoding.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n {
+                    encoding.binomial[k][n - 1]
+                } else {
+                    0
+                };
+            }
+        }
+
+        let mut available = 47;
+        for lead_pawns in 1..6 {
+            for file in 0..4 {
+                let mut index = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        encoding.map_pawns[square] = available;
+                        encoding.map_pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    encoding.lead_pawn_idx[lead_pawns][square] = index;
+                    index += encoding.binomial[lead_pawns - 1][encoding.map_pawns[square] as usize];
+                }
+                encoding.lead_pawns_size[lead_pawns][file] = index;
+            }
+        }
+
+        encoding
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct PairsData {
+    flags: u8,
+    sizeof_block: usize,
+    span: usize,
+    num_blocks: usize,
+    min_sym_len: usize,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u8>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+}
+
+pub struct Table {
+    format: Format,
+    bytes: Vec<u8>,
+    symmetric: bool,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    pawn_count: [usize; 2],
+    items: Vec<Vec<PairsData>>,
+    map: usize,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]))
+}
+
+fn read_u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let slice = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn read_u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    let slice = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn read_u64_be(bytes: &[u8], at: usize) -> Option<u64> {
+    Some((read_u32_be(bytes, at)? as u64) << 32 | read_u32_be(bytes, at + 4)? as u64)
+}
+
+// Counts of kings, queens, rooks, bishops, knights and pawns for one side of a
+// table name like "KRPvKR".
+pub fn parse_side(side: &str) -> Option<Vec<u8>> {
+    side.chars()
+        .map(|symbol| match symbol {
+            'P' => Some(1),
+            'N' => Some(2),
+            'B' => Some(3),
+            'R' => Some(4),
+            'Q' => Some(5),
+            'K' => Some(6),
+            _ => None,
+        })
+        .collect()
+}
+
+impl Table {
+    pub fn parse(name: &str, format: Format, bytes: Vec<u8>) -> Option<Table> {
+        let mut sides = name.split('v');
+        let white = parse_side(sides.next()?)?;
+        let black = parse_side(sides.next()?)?;
+
+        let magic = match format {
+            Format::Wdl => WDL_MAGIC,
+            Format::Dtz => DTZ_MAGIC,
+        };
+        if bytes.get(..4)? != magic {
+            return None;
+        }
+
+        let count =
+            |pieces: &Vec<u8>, code: u8| pieces.iter().filter(|piece| **piece == code).count();
+        let white_pawns = count(&white, 1);
+        let black_pawns = count(&black, 1);
+        let leading_white = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+
+        let mut table = Table {
+            format,
+            bytes,
+            symmetric: white == black,
+            piece_count: white.len() + black.len(),
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces: (1..6)
+                .any(|code| count(&white, code) == 1 || count(&black, code) == 1),
+            pawn_count: if leading_white {
+                [white_pawns, black_pawns]
+            } else {
+                [black_pawns, white_pawns]
+            },
+            items: vec![],
+            map: 0,
+        };
+
+        if table.piece_count > MAX_PIECES || table.piece_count < 3 {
+            return None;
+        }
+
+        table.setup()?;
+        Some(table)
+    }
+
+    fn get(&self, stm: usize, file: usize) -> &PairsData {
+        &self.items[stm % self.items.len()][if self.has_pawns { file } else { 0 }]
+    }
+
+    fn setup(&mut self) -> Option<()> {
+        let bytes = &self.bytes;
+        let mut at = 4;
+
+        let flags = *bytes.get(at)?;
+        if (flags & 2 != 0) != self.has_pawns || (flags & 1 != 0) == self.symmetric {
+            return None;
+        }
+        at += 1;
+
+        let sides = if self.format == Format::Wdl && !self.symmetric {
+            2
+        } else {
+            1
+        };
+        let files = if self.has_pawns { 4 } else { 1 };
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+
+        let mut items = vec![vec![PairsData::default(); files]; sides];
+
+        for file in 0..files {
+            let first = *bytes.get(at)?;
+            let second = if both_pawns {
+                *bytes.get(at + 1)?
+            } else {
+                0xff
+            };
+            let order = [
+                [(first & 0xf) as usize, (second & 0xf) as usize],
+                [(first >> 4) as usize, (second >> 4) as usize],
+            ];
+            at += 1 + both_pawns as usize;
+
+            for k in 0..self.piece_count {
+                let byte = *bytes.get(at)?;
+                for (side, item) in items.iter_mut().enumerate() {
+                    item[file].pieces[k] = if side == 1 { byte >> 4 } else { byte & 0xf };
+                }
+                at += 1;
+            }
+
+            for (side, item) in items.iter_mut().enumerate() {
+                self.set_groups(&mut item[file], order[side], file)?;
+            }
+        }
+
+        at += at & 1;
+
+        for file in 0..files {
+            for item in items.iter_mut() {
+                at = set_sizes(bytes, &mut item[file], at)?;
+            }
+        }
+
+        if self.format == Format::Dtz {
+            self.map = at;
+            for item in items[0].iter_mut() {
+                if item.flags & FLAG_MAPPED != 0 {
+                    if item.flags & FLAG_WIDE != 0 {
+                        at += at & 1;
+                        for index in 0..4 {
+                            item.map_idx[index] = (at - self.map) / 2 + 1;
+                            at += 2 * read_u16(bytes, at)? as usize + 2;
+                        }
+                    } else {
+                        for index in 0..4 {
+                            item.map_idx[index] = at - self.map + 1;
+                            at += *bytes.get(at)? as usize + 1;
+                        }
+                    }
+                }
+            }
+            at += at & 1;
+        }
+
+        for file in 0..files {
+            for item in items.iter_mut() {
+                item[file].sparse_index = at;
+                at += item[file].sparse_index_size * 6;
+            }
+        }
+
+        for file in 0..files {
+            for item in items.iter_mut() {
+                item[file].block_length = at;
+                at += item[file].block_length_size * 2;
+            }
+        }
+
+        for file in 0..files {
+            for item in items.iter_mut() {
+                at = (at + 0x3f) & !0x3f;
+                item[file].data = at;
+                at += item[file].num_blocks * item[file].sizeof_block;
+            }
+        }
+
+        if at > bytes.len() {
+            return None;
+        }
+
+        self.items = items;
+        Some(())
+    }
+
+    fn set_groups(&self, d: &mut PairsData, order: [usize; 2], file: usize) -> Option<()> {
+        let encoding = encoding();
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns {
+            0
+        } else if self.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        d.group_len[n] = 1;
+
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - d.group_len[0] - if both_pawns { d.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    encoding.lead_pawns_size[d.group_len[0]][file]
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= encoding.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                d.group_idx[next] = idx;
+                idx *= encoding.binomial[d.group_len[next]][free_squares];
+                free_squares = free_squares.checked_sub(d.group_len[next])?;
+                next += 1;
+            }
+            k += 1;
+            if k > MAX_PIECES + 1 {
+                return None;
+            }
+        }
+
+        d.group_idx[n] = idx;
+        Some(())
+    }
+
+    fn check_dtz_stm(&self, stm: usize, file: usize) -> bool {
+        if self.format == Format::Wdl {
+            return true;
+        }
+
+        let flags = self.get(stm, file).flags;
+        (flags & FLAG_STM) as usize == stm || (self.symmetric && !self.has_pawns)
+    }
+
+    fn map_score(&self, file: usize, value: i32, wdl: i32) -> Option<i32> {
+        if self.format == Format::Wdl {
+            return Some(value - 2);
+        }
+
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+        let d = self.get(0, file);
+        let mut value = value;
+        if d.flags & FLAG_MAPPED != 0 {
+            let index = d.map_idx[WDL_MAP[(wdl + 2) as usize]] + value as usize;
+            value = if d.flags & FLAG_WIDE != 0 {
+                read_u16(&self.bytes, self.map + index * 2)? as i32
+            } else {
+                *self.bytes.get(self.map + index)? as i32
+            };
+        }
+
+        if (wdl == 2 && d.flags & FLAG_WIN_PLIES == 0)
+            || (wdl == -2 && d.flags & FLAG_LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1
+        {
+            value *= 2;
+        }
+
+        Some(value + 1)
+    }
+
+    // The key of a position is its material with white first, for example
+    // "KQvK". `black_stronger` is set when the position is the mirror image of
+    // the table, so colors and ranks are swapped before indexing.
+    pub fn probe(&self, position: &Position, black_stronger: bool, wdl: i32) -> Option<Probe> {
+        let (stm, file, idx) = self.index(position, black_stronger)?;
+
+        if !self.check_dtz_stm(stm, file) {
+            return Some(Probe::ChangeSide);
+        }
+
+        let value = decompress_pairs(&self.bytes, self.get(stm, file), idx)?;
+        Some(Probe::Value(self.map_score(file, value, wdl)?))
+    }
+
+    // Side, pawn file and index of a position within the table.
+    pub fn index(&self, position: &Position, black_stronger: bool) -> Option<(usize, usize, u64)> {
+        let encoding = encoding();
+
+        let symmetric_black_to_move = self.symmetric && !position.white_to_move;
+        let flip = symmetric_black_to_move || black_stronger;
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = (flip as usize) ^ (!position.white_to_move as usize);
+
+        let mut squares: Vec<usize> = vec![];
+        let mut pieces: Vec<u8> = vec![];
+        let mut lead_pawns = 0;
+        let mut tb_file = 0;
+        let mut lead_pawn_code = 0;
+
+        if self.has_pawns {
+            lead_pawn_code = self.get(0, 0).pieces[0] ^ flip_color;
+            for (square, code) in position.pieces.iter() {
+                if *code == lead_pawn_code {
+                    squares.push(square ^ flip_squares);
+                    pieces.push(code ^ flip_color);
+                }
+            }
+            lead_pawns = squares.len();
+            if lead_pawns == 0 {
+                return None;
+            }
+
+            let mut lead = 0;
+            for i in 1..lead_pawns {
+                if encoding.map_pawns[squares[i]] > encoding.map_pawns[squares[lead]] {
+                    lead = i;
+                }
+            }
+            squares.swap(0, lead);
+
+            let file = file_of(squares[0]);
+            tb_file = file.min(7 - file);
+        }
+
+        for (square, code) in position.pieces.iter() {
+            if !self.has_pawns || *code != lead_pawn_code {
+                squares.push(square ^ flip_squares);
+                pieces.push(code ^ flip_color);
+            }
+        }
+
+        let size = squares.len();
+        if size != self.piece_count {
+            return None;
+        }
+
+        let d = self.get(stm, tb_file);
+
+        for i in lead_pawns..size - 1 {
+            for j in i + 1..size {
+                if d.pieces[i] == pieces[j] {
+                    pieces.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        if file_of(squares[0]) > 3 {
+            for square in squares.iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx: u64;
+        if self.has_pawns {
+            idx = encoding.lead_pawn_idx[lead_pawns][squares[0]];
+
+            squares[1..lead_pawns].sort_by_key(|square| encoding.map_pawns[*square]);
+
+            for (i, square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += encoding.binomial[i][encoding.map_pawns[*square] as usize];
+            }
+        } else {
+            if rank_of(squares[0]) > 3 {
+                for square in squares.iter_mut() {
+                    *square ^= 56;
+                }
+            }
+
+            for i in 0..d.group_len[0] {
+                if off_a1h8(squares[i]) == 0 {
+                    continue;
+                }
+
+                if off_a1h8(squares[i]) > 0 {
+                    for square in squares[i..].iter_mut() {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            idx = if self.has_unique_pieces {
+                let adjust1 = (squares[1] > squares[0]) as u64;
+                let adjust2 = (squares[2] > squares[0]) as u64 + (squares[2] > squares[1]) as u64;
+
+                if off_a1h8(squares[0]) != 0 {
+                    (encoding.map_a1d1d4[squares[0]] * 63 + (squares[1] as u64 - adjust1)) * 62
+                        + squares[2] as u64
+                        - adjust2
+                } else if off_a1h8(squares[1]) != 0 {
+                    (6 * 63 + rank_of(squares[0]) as u64 * 28 + encoding.map_b1h1h7[squares[1]])
+                        * 62
+                        + squares[2] as u64
+                        - adjust2
+                } else if off_a1h8(squares[2]) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + rank_of(squares[0]) as u64 * 7 * 28
+                        + (rank_of(squares[1]) as u64 - adjust1) * 28
+                        + encoding.map_b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + rank_of(squares[0]) as u64 * 7 * 6
+                        + (rank_of(squares[1]) as u64 - adjust1) * 6
+                        + (rank_of(squares[2]) as u64 - adjust2)
+                }
+            } else {
+                encoding.map_kk[encoding.map_a1d1d4[squares[0]] as usize][squares[1]]
+            };
+        }
+
+        idx *= d.group_idx[0];
+
+        let mut group_start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[group_start..group_start + len].sort_unstable();
+
+            let mut n = 0;
+            for i in 0..len {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start]
+                    .iter()
+                    .filter(|other| square > **other)
+                    .count();
+                let offset = if remaining_pawns { 8 } else { 0 };
+                n += encoding.binomial[i + 1][square.checked_sub(adjust + offset)?];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            group_start += len;
+            next += 1;
+        }
+
+        Some((stm, tb_file, idx))
+    }
+}
+
+fn set_sizes(bytes: &[u8], d: &mut PairsData, mut at: usize) -> Option<usize> {
+    d.flags = *bytes.get(at)?;
+    at += 1;
+
+    if d.flags & FLAG_SINGLE_VALUE != 0 {
+        d.min_sym_len = *bytes.get(at)? as usize;
+        return Some(at + 1);
+    }
+
+    let terminator = d.group_len.iter().position(|len| *len == 0)?;
+    let size = d.group_idx[terminator];
+
+    d.sizeof_block = 1usize.checked_shl(*bytes.get(at)? as u32)?;
+    d.span = 1usize.checked_shl(*bytes.get(at + 1)? as u32)?;
+    d.sparse_index_size = size.div_ceil(d.span as u64) as usize;
+    let padding = *bytes.get(at + 2)? as usize;
+    d.num_blocks = read_u32_le(bytes, at + 3)? as usize;
+    d.block_length_size = d.num_blocks + padding;
+    let max_sym_len = *bytes.get(at + 7)? as usize;
+    d.min_sym_len = *bytes.get(at + 8)? as usize;
+    at += 9;
+
+    if d.min_sym_len == 0 || max_sym_len < d.min_sym_len || max_sym_len > 32 {
+        return None;
+    }
+
+    d.lowest_sym = at;
+    let lengths = max_sym_len - d.min_sym_len + 1;
+    d.base64 = vec![0; lengths];
+
+    for i in (0..lengths - 1).rev() {
+        let lowest = read_u16(bytes, d.lowest_sym + 2 * i)? as u64;
+        let next_lowest = read_u16(bytes, d.lowest_sym + 2 * (i + 1))? as u64;
+        d.base64[i] = (d.base64[i + 1] + lowest).checked_sub(next_lowest)? / 2;
+    }
+
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        *base = base
+            .checked_shl((64 - i - d.min_sym_len) as u32)
+            .unwrap_or(0);
+    }
+
+    at += lengths * 2;
+    let symbols = read_u16(bytes, at)? as usize;
+    at += 2;
+    d.btree = at;
+    d.symlen = vec![0; symbols];
+
+    let mut visited = vec![false; symbols];
+    for symbol in 0..symbols {
+        if !visited[symbol] {
+            d.symlen[symbol] = set_symlen(bytes, d, symbol, &mut visited)?;
+        }
+    }
+
+    Some(at + symbols * 3 + (symbols & 1))
+}
+
+fn btree_entry(bytes: &[u8], d: &PairsData, symbol: usize) -> Option<(usize, usize)> {
+    let lr = bytes.get(d.btree + symbol * 3..d.btree + symbol * 3 + 3)?;
+    let left = ((lr[1] as usize & 0xf) << 8) | lr[0] as usize;
+    let right = ((lr[2] as usize) << 4) | (lr[1] as usize >> 4);
+    Some((left, right))
+}
+
+fn set_symlen(
+    bytes: &[u8],
+    d: &mut PairsData,
+    symbol: usize,
+    visited: &mut Vec<bool>,
+) -> Option<u8> {
+    visited[symbol] = true;
+    let (left, right) = btree_entry(bytes, d, symbol)?;
+
+    if right == 0xfff {
+        return Some(0);
+    }
+
+    if left >= visited.len() || right >= visited.len() {
+        return None;
+    }
+    if !visited[left] {
+        d.symlen[left] = set_symlen(bytes, d, left, visited)?;
+    }
+    if !visited[right] {
+        d.symlen[right] = set_symlen(bytes, d, right, visited)?;
+    }
+
+    d.symlen[left].checked_add(d.symlen[right])?.checked_add(1)
+}
+
+pub fn decompress_pairs(bytes: &[u8], d: &PairsData, idx: u64) -> Option<i32> {
+    if d.flags & FLAG_SINGLE_VALUE != 0 {
+        return Some(d.min_sym_len as i32);
+    }
+
+    let span = d.span as u64;
+    let k = (idx / span) as usize;
+    if k >= d.sparse_index_size {
+        return None;
+    }
+
+    let entry = d.sparse_index + k * 6;
+    let mut block = read_u32_le(bytes, entry)? as usize;
+    let mut offset = read_u16(bytes, entry + 4)? as i64;
+
+    offset += (idx % span) as i64 - (span / 2) as i64;
+
+    let block_length = |block: usize| -> Option<i64> {
+        if block >= d.block_length_size {
+            return None;
+        }
+        Some(read_u16(bytes, d.block_length + block * 2)? as i64)
+    };
+
+    while offset < 0 {
+        block = block.checked_sub(1)?;
+        offset += block_length(block)? + 1;
+    }
+    while offset > block_length(block)? {
+        offset -= block_length(block)? + 1;
+        block += 1;
+    }
+
+    let mut at = d.data + block * d.sizeof_block;
+    let mut buffer = read_u64_be(bytes, at)?;
+    at += 8;
+    let mut buffer_size = 64;
+    let mut symbol;
+
+    loop {
+        let mut len = 0;
+        while buffer < d.base64[len] {
+            len += 1;
+            if len >= d.base64.len() {
+                return None;
+            }
+        }
+
+        symbol = ((buffer - d.base64[len]) >> (64 - len - d.min_sym_len)) as usize;
+        symbol += read_u16(bytes, d.lowest_sym + 2 * len)? as usize;
+
+        if offset < *d.symlen.get(symbol)? as i64 + 1 {
+            break;
+        }
+
+        offset -= d.symlen[symbol] as i64 + 1;
+        len += d.min_sym_len;
+        buffer <<= len;
+        buffer_size -= len as i32;
+
+        if buffer_size <= 32 {
+            buffer_size += 32;
+            buffer |= (read_u32_be(bytes, at)? as u64) << (64 - buffer_size);
+            at += 4;
+        }
+    }
+
+    while d.symlen[symbol] != 0 {
+        let (left, right) = btree_entry(bytes, d, symbol)?;
+
+        if offset < d.symlen[left] as i64 + 1 {
+            symbol = left;
+        } else {
+            offset -= d.symlen[left] as i64 + 1;
+            symbol = right;
+        }
+    }
+
+    Some(btree_entry(bytes, d, symbol)?.0 as i32)
+}
@@ -0,0 +1,260 @@
+use super::table::encoding;
+use super::*;
+use crate::engine::{Engine, Limits, Score};
+use std::collections::hash_map::Entry;
+use std::path::Path;
+
+// The real 3-piece Syzygy tables, as published at
+// https://tablebase.lichess.ovh/tables/standard/3-4-5/.
+const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/syzygy");
+
+const FILES: [&str; 6] = [
+    "KQvK.rtbw",
+    "KQvK.rtbz",
+    "KRvK.rtbw",
+    "KRvK.rtbz",
+    "KPvK.rtbw",
+    "KPvK.rtbz",
+];
+
+// The tables are part of the test data, so a missing one fails the test
+// rather than leaving the decoder unchecked.
+fn tables() -> Tablebase {
+    let missing: Vec<&str> = FILES
+        .iter()
+        .copied()
+        .filter(|file| !Path::new(DATA).join(file).exists())
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "{} missing from {}",
+        missing.join(", "),
+        DATA
+    );
+    Tablebase::open(DATA).unwrap()
+}
+
+fn read(file: &str) -> Vec<u8> {
+    fs::read(Path::new(DATA).join(file))
+        .unwrap_or_else(|error| panic!("{} in {}: {}", file, DATA, error))
+}
+
+fn table(name: &str, format: Format) -> Table {
+    let extension = match format {
+        Format::Wdl => "rtbw",
+        Format::Dtz => "rtbz",
+    };
+    let bytes = read(&format!("{}.{}", name, extension));
+    Table::parse(name, format, bytes).unwrap()
+}
+
+fn probe(tablebase: &Tablebase, fen: &str) -> (Option<Wdl>, Option<i32>) {
+    let game = Game::from_fen(fen).unwrap();
+    (game.probe_wdl(tablebase), game.probe_dtz(tablebase))
+}
+
+fn symmetries(square: usize) -> [usize; 8] {
+    let (x, y) = (square & 7, square >> 3);
+    [
+        (x, y),
+        (7 - x, y),
+        (x, 7 - y),
+        (7 - x, 7 - y),
+        (y, x),
+        (7 - y, x),
+        (y, 7 - x),
+        (7 - y, 7 - x),
+    ]
+    .map(|(x, y)| y * 8 + x)
+}
+
+fn adjacent(first: usize, second: usize) -> bool {
+    ((first & 7) as i32 - (second & 7) as i32).abs() <= 1
+        && ((first >> 3) as i32 - (second >> 3) as i32).abs() <= 1
+}
+
+#[test]
+fn test_encoding_tables() {
+    let encoding = encoding();
+
+    assert_eq!(encoding.binomial[2][5], 10);
+    assert_eq!(encoding.binomial[3][62], 37820);
+
+    let mut kings: Vec<u64> = vec![];
+    for first in [1, 2, 3, 10, 11, 19, 0, 9, 18, 27].iter() {
+        for second in 0..64 {
+            let above = (second >> 3) as i32 > (second & 7) as i32;
+            let on_diagonal = (*first >> 3) == (*first & 7);
+            if !(adjacent(*first, second) || on_diagonal && above) {
+                kings.push(encoding.map_kk[encoding.map_a1d1d4[*first] as usize][second]);
+            }
+        }
+    }
+    kings.sort_unstable();
+    assert_eq!(kings, (0..462).collect::<Vec<u64>>());
+
+    let mut pawns: Vec<u64> = (8..56).map(|square| encoding.map_pawns[square]).collect();
+    pawns.sort_unstable();
+    assert_eq!(pawns, (0..48).collect::<Vec<u64>>());
+    assert_eq!(encoding.map_pawns[8], 47);
+    assert_eq!(encoding.lead_pawns_size[1], [6, 6, 6, 6]);
+}
+
+#[test]
+fn test_index_without_pawns() {
+    let table = table("KQvK", Format::Wdl);
+    let mut classes: HashMap<u64, [usize; 3]> = HashMap::new();
+
+    for king in 0..64 {
+        for queen in (0..64).filter(|queen| *queen != king) {
+            for other in (0..64).filter(|other| *other != queen && !adjacent(king, *other)) {
+                let position = Position {
+                    pieces: vec![(king, 6), (queen, 5), (other, 14)],
+                    white_to_move: true,
+                };
+                let (stm, file, idx) = table.index(&position, false).unwrap();
+                assert_eq!((stm, file), (0, 0));
+
+                let (kings, queens, others) =
+                    (symmetries(king), symmetries(queen), symmetries(other));
+                let class = (0..8)
+                    .map(|i| [kings[i], queens[i], others[i]])
+                    .min()
+                    .unwrap();
+                match classes.entry(idx) {
+                    Entry::Occupied(entry) => assert_eq!(*entry.get(), class, "index {}", idx),
+                    Entry::Vacant(entry) => {
+                        entry.insert(class);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut seen: Vec<[usize; 3]> = classes.values().copied().collect();
+    seen.sort_unstable();
+    seen.dedup();
+    assert_eq!(seen.len(), classes.len());
+}
+
+#[test]
+fn test_index_with_pawn() {
+    let table = table("KPvK", Format::Wdl);
+    let mut classes: HashMap<(usize, u64), [usize; 3]> = HashMap::new();
+
+    for pawn in 8..56 {
+        for king in (0..64).filter(|king| *king != pawn) {
+            for other in (0..64).filter(|other| *other != pawn && !adjacent(king, *other)) {
+                let position = Position {
+                    pieces: vec![(pawn, 1), (king, 6), (other, 14)],
+                    white_to_move: false,
+                };
+                let (stm, file, idx) = table.index(&position, false).unwrap();
+                assert_eq!(stm, 1);
+                assert_eq!(file, (pawn & 7).min(7 - (pawn & 7)));
+
+                let class = [pawn, king, other].min([pawn ^ 7, king ^ 7, other ^ 7]);
+                match classes.entry((file, idx)) {
+                    Entry::Occupied(entry) => assert_eq!(*entry.get(), class),
+                    Entry::Vacant(entry) => {
+                        entry.insert(class);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut seen: Vec<[usize; 3]> = classes.values().copied().collect();
+    seen.sort_unstable();
+    seen.dedup();
+    assert_eq!(seen.len(), classes.len());
+}
+
+#[test]
+fn test_rejects_bad_files() {
+    let bytes = read("KRvK.rtbw");
+
+    assert!(Table::parse("KRvK", Format::Wdl, bytes.clone()).is_some());
+    assert!(Table::parse("KRvK", Format::Dtz, bytes.clone()).is_none());
+    assert!(Table::parse("KRvK", Format::Wdl, bytes[..6].to_vec()).is_none());
+    assert!(Table::parse("KPvK", Format::Wdl, bytes.clone()).is_none());
+    assert!(Table::parse("KXvK", Format::Wdl, bytes).is_none());
+}
+
+#[test]
+fn test_open() {
+    assert!(Tablebase::open("/nonexistent/syzygy").is_err());
+    let tablebase = tables();
+
+    assert_eq!(tablebase.max_pieces(), 3);
+    assert!(!tablebase.is_empty());
+}
+
+#[test]
+fn test_probe() {
+    let tablebase = tables();
+
+    // Qh8 and Rh8 mate at once.
+    assert_eq!(
+        probe(&tablebase, "k7/8/1K6/8/8/8/8/7Q w - - 0 1"),
+        (Some(Wdl::Win), Some(1))
+    );
+    assert_eq!(
+        probe(&tablebase, "k7/8/1K6/8/8/8/8/7R b - - 0 1"),
+        (Some(Wdl::Loss), Some(-2))
+    );
+    // The black king takes the rook.
+    assert_eq!(
+        probe(&tablebase, "8/8/8/8/8/2k5/3R4/7K b - - 0 1"),
+        (Some(Wdl::Draw), Some(0))
+    );
+    assert_eq!(
+        probe(&tablebase, "8/8/8/8/8/8/3kP3/7K b - - 0 1"),
+        (Some(Wdl::Draw), Some(0))
+    );
+    // A king on the sixth rank in front of its pawn wins whoever moves.
+    let (wdl, dtz) = probe(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1");
+    assert_eq!(wdl, Some(Wdl::Win));
+    assert!(dtz.unwrap() > 0);
+    let (wdl, dtz) = probe(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1");
+    assert_eq!(wdl, Some(Wdl::Loss));
+    assert!(dtz.unwrap() < 0);
+    // Colors are swapped for tables with black as the stronger side.
+    let (wdl, dtz) = probe(&tablebase, "q3k3/8/8/8/4K3/8/8/8 w - - 0 1");
+    assert_eq!(wdl, Some(Wdl::Loss));
+    assert!(dtz.unwrap() < 0);
+
+    assert_eq!(
+        probe(&tablebase, "8/8/8/4k3/8/8/8/4K3 w - - 0 1"),
+        (Some(Wdl::Draw), Some(0))
+    );
+    assert_eq!(
+        probe(&tablebase, "8/8/8/4k3/8/8/8/RQ2K3 w - - 0 1"),
+        (None, None)
+    );
+    assert_eq!(
+        probe(&tablebase, "8/8/8/4k3/8/8/8/R3K3 w Q - 0 1"),
+        (None, None)
+    );
+}
+
+#[test]
+fn test_engine_keeps_tablebase_win() {
+    let tablebase = tables();
+    let mut engine = Engine::new();
+    engine.set_tablebase(Some(tables()));
+
+    let mut game = Game::from_fen("8/8/8/8/8/2k5/8/3RK3 w - - 0 1").unwrap();
+    let dtz = game.probe_dtz(&tablebase).unwrap();
+    let mut infos = vec![];
+    let limits = Limits {
+        depth: Some(2),
+        ..Limits::default()
+    };
+    let best = engine.search(&game, &limits, |info| infos.push(info.clone()));
+
+    game.play(&best.unwrap());
+    assert_eq!(game.probe_wdl(&tablebase), Some(Wdl::Loss));
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].score, Score::Centipawns(20_000 - dtz));
+}
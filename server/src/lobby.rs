@@ -0,0 +1,468 @@
+use chess::game::clock::{Clock, TimeControl, TimeSource};
+use chess::game::{EndResult, Game, Turn, TurnResult};
+use chess::Color;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::sync::Arc;
+
+pub const VERSION: u32 = 1;
+
+pub type Output = Box<dyn Write + Send>;
+
+// Someone connected to the server. A session outlives its connection, so a
+// player who drops out can come back with the token and pick up their games.
+struct Session {
+    name: Option<String>,
+    token: String,
+    output: Option<Output>,
+    // The connection the output belongs to, which is the session it started
+    // out as.
+    connection: usize,
+}
+
+struct Table {
+    control: TimeControl,
+    game: Game,
+    players: [Option<usize>; 2],
+    spectators: Vec<usize>,
+    draw_offer: Option<Color>,
+}
+
+impl Table {
+    fn started(&self) -> bool {
+        self.players.iter().all(|player| player.is_some())
+    }
+
+    fn color_of(&self, session: usize) -> Option<Color> {
+        match self.players {
+            [Some(white), _] if white == session => Some(Color::White),
+            [_, Some(black)] if black == session => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    fn audience(&self) -> Vec<usize> {
+        self.players
+            .iter()
+            .flatten()
+            .chain(self.spectators.iter())
+            .copied()
+            .collect()
+    }
+}
+
+// Every game on the server and everyone taking part. Connections feed it
+// lines and it answers through the sessions' outputs:
+//
+//     hello <version> <name>     -> welcome <token>
+//     resume <version> <token>   -> welcome <token>, then each game again
+//     list [control]             -> open <id> <control> <name> ..., listed
+//     create <control> [color]   -> created <id> <color>
+//     join <id>                  -> start <id> <color> <opponent> to both
+//     seek <control>             joins an open game, or creates one
+//     watch <id>                 -> position <id> <fen>
+//     move <id> <move>           -> moved <id> <move> and clock <id> <ms> <ms>
+//     resign <id>
+//     draw <id>                  offers a draw, or accepts one
+//
+// Finished games end with "result <id> 1-0" and the like, and anything that
+// goes wrong is answered with "error <reason>".
+pub struct Lobby {
+    sessions: HashMap<usize, Session>,
+    next_session: usize,
+    tables: Vec<Table>,
+    source: Arc<dyn TimeSource>,
+    tokens: RandomState,
+}
+
+impl Lobby {
+    pub fn new(source: Arc<dyn TimeSource>) -> Self {
+        Lobby {
+            sessions: HashMap::new(),
+            next_session: 0,
+            tables: vec![],
+            source,
+            tokens: RandomState::new(),
+        }
+    }
+
+    // Starts a session for a new connection.
+    pub fn connect(&mut self, output: Output) -> usize {
+        let session = self.next_session;
+        self.next_session += 1;
+        let mut hasher = self.tokens.build_hasher();
+        hasher.write_usize(session);
+        self.sessions.insert(
+            session,
+            Session {
+                name: None,
+                token: format!("{:016x}", hasher.finish()),
+                output: Some(output),
+                connection: session,
+            },
+        );
+        session
+    }
+
+    // The session stays around for the player to come back to. Sessions
+    // that never said hello are dropped. A connection that was replaced by a
+    // newer one no longer has a say.
+    pub fn disconnect(&mut self, session: usize, connection: usize) {
+        let named = match self.sessions.get_mut(&session) {
+            Some(state) if state.connection == connection => {
+                state.output = None;
+                state.name.is_some()
+            }
+            _ => return,
+        };
+        if !named {
+            self.sessions.remove(&session);
+        }
+    }
+
+    // Handles a line from a connection. Resuming moves the connection over
+    // to the old session, which is what the session becomes.
+    pub fn handle(&mut self, session: &mut usize, line: &str) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let named = self
+            .sessions
+            .get(session)
+            .is_some_and(|state| state.name.is_some());
+
+        match tokens.as_slice() {
+            ["hello", version, name] if !named => {
+                if version.parse() != Ok(VERSION) {
+                    return self.send(*session, "error unsupported version");
+                }
+                let state = self.sessions.get_mut(session).unwrap();
+                state.name = Some(name.to_string());
+                let welcome = format!("welcome {}", state.token);
+                self.send(*session, &welcome);
+            }
+            ["resume", version, token] if !named => {
+                if version.parse() != Ok(VERSION) {
+                    return self.send(*session, "error unsupported version");
+                }
+                self.resume(session, token);
+            }
+            _ if !named => self.send(*session, "error say hello first"),
+            ["list"] => self.list(*session, None),
+            ["list", control] => self.list(*session, Some(control)),
+            ["create", control] => self.create(*session, control, Color::White),
+            ["create", control, "white"] => self.create(*session, control, Color::White),
+            ["create", control, "black"] => self.create(*session, control, Color::Black),
+            ["join", id] => match id.parse() {
+                Ok(id) => self.join(*session, id),
+                Err(_) => self.send(*session, "error no such game"),
+            },
+            ["seek", control] => self.seek(*session, control),
+            ["watch", id] => match id.parse() {
+                Ok(id) => self.watch(*session, id),
+                Err(_) => self.send(*session, "error no such game"),
+            },
+            ["move", id, turn] => self.play(*session, id, turn),
+            ["resign", id] => self.act(*session, id, |game, color| game.resign(color)),
+            ["draw", id] => self.draw(*session, id),
+            _ => self.send(*session, "error unknown command"),
+        }
+    }
+
+    // Ends games whose side to move has run out of time.
+    pub fn tick(&mut self) {
+        for id in 0..self.tables.len() {
+            let table = &mut self.tables[id];
+            if table.started() && table.game.check_time().is_some() {
+                self.conclude(id);
+            }
+        }
+    }
+
+    fn resume(&mut self, session: &mut usize, token: &str) {
+        let old = self
+            .sessions
+            .iter()
+            .find(|(_, state)| state.token == token && state.name.is_some())
+            .map(|(old, _)| *old);
+        let old = match old {
+            Some(old) => old,
+            None => return self.send(*session, "error unknown token"),
+        };
+
+        let output = self.sessions.remove(session).and_then(|state| state.output);
+        let state = self.sessions.get_mut(&old).unwrap();
+        state.output = output;
+        state.connection = *session;
+        *session = old;
+        let welcome = format!("welcome {}", token);
+        self.send(old, &welcome);
+
+        for id in 0..self.tables.len() {
+            let table = &self.tables[id];
+            if let Some(color) = table.color_of(old) {
+                let line = format!("game {} {} {}", id, color_name(color), table.game.to_fen());
+                self.send(old, &line);
+                self.send_clock(id, &[old]);
+            }
+        }
+    }
+
+    fn list(&mut self, session: usize, control: Option<&str>) {
+        let mut lines = vec![];
+        for (id, table) in self.tables.iter().enumerate() {
+            let open = !table.started() && table.game.get_result().is_none();
+            let wanted = control.is_none_or(|control| {
+                TimeControl::parse(control).is_some_and(|control| control == table.control)
+            });
+            if open && wanted {
+                let host = table.players.iter().flatten().next().unwrap();
+                lines.push(format!(
+                    "open {} {} {}",
+                    id,
+                    table.control,
+                    self.name(*host)
+                ));
+            }
+        }
+        lines.push("listed".to_string());
+        for line in lines.iter() {
+            self.send(session, line);
+        }
+    }
+
+    fn create(&mut self, session: usize, control: &str, color: Color) {
+        match TimeControl::parse(control) {
+            Some(control) => self.add_table(session, control, color),
+            None => self.send(session, "error bad time control"),
+        }
+    }
+
+    fn add_table(&mut self, session: usize, control: TimeControl, color: Color) {
+        let mut players = [None, None];
+        players[color as usize] = Some(session);
+        self.tables.push(Table {
+            control,
+            game: Game::new(),
+            players,
+            spectators: vec![],
+            draw_offer: None,
+        });
+        let line = format!("created {} {}", self.tables.len() - 1, color_name(color));
+        self.send(session, &line);
+    }
+
+    fn join(&mut self, session: usize, id: usize) {
+        let table = match self.tables.get_mut(id) {
+            Some(table) if !table.started() && table.game.get_result().is_none() => table,
+            _ => return self.send(session, "error no such game"),
+        };
+        if table.color_of(session).is_some() {
+            return self.send(session, "error cannot play yourself");
+        }
+
+        let free = table
+            .players
+            .iter()
+            .position(|player| player.is_none())
+            .unwrap();
+        table.players[free] = Some(session);
+        let clock = Clock::with_source(table.control.clone(), self.source.clone());
+        table.game.set_clock(Some(clock));
+
+        let [white, black] = [table.players[0].unwrap(), table.players[1].unwrap()];
+        let audience = table.audience();
+        for (player, color, opponent) in
+            [(white, Color::White, black), (black, Color::Black, white)].iter()
+        {
+            let line = format!(
+                "start {} {} {}",
+                id,
+                color_name(*color),
+                self.name(*opponent)
+            );
+            self.send(*player, &line);
+        }
+        self.send_clock(id, &audience);
+    }
+
+    fn seek(&mut self, session: usize, control: &str) {
+        let control = match TimeControl::parse(control) {
+            Some(control) => control,
+            None => return self.send(session, "error bad time control"),
+        };
+        let open = self.tables.iter().position(|table| {
+            table.control == control
+                && !table.started()
+                && table.game.get_result().is_none()
+                && table.color_of(session).is_none()
+        });
+        match open {
+            Some(id) => self.join(session, id),
+            None => self.add_table(session, control, Color::White),
+        }
+    }
+
+    fn watch(&mut self, session: usize, id: usize) {
+        let table = match self.tables.get_mut(id) {
+            Some(table) => table,
+            None => return self.send(session, "error no such game"),
+        };
+        if !table.audience().contains(&session) {
+            table.spectators.push(session);
+        }
+        let line = format!("position {} {}", id, table.game.to_fen());
+        self.send(session, &line);
+        self.send_clock(id, &[session]);
+    }
+
+    fn play(&mut self, session: usize, id: &str, notation: &str) {
+        let id = match self.playing(session, id) {
+            Some(id) => id,
+            None => return,
+        };
+        let table = &mut self.tables[id];
+        // A flag that fell before the move came in ends the game instead.
+        if table.game.check_time().is_some() {
+            return self.conclude(id);
+        }
+        let color = table.color_of(session);
+        let played = table.game.get_history().len();
+        let result = match Turn::parse(notation) {
+            Some(turn) if Some(table.game.color) == color => table.game.play(&turn),
+            _ => TurnResult::Failed,
+        };
+        // Only a turn that made it into the history is passed on.
+        let turn = match table.game.get_history().get(played) {
+            Some(turn) if result != TurnResult::Failed => *turn,
+            _ => {
+                let line = format!("illegal {} {}", id, notation);
+                return self.send(session, &line);
+            }
+        };
+
+        table.draw_offer = None;
+        let audience = table.audience();
+        let line = format!("moved {} {}", id, turn);
+        for member in audience.iter() {
+            self.send(*member, &line);
+        }
+        self.send_clock(id, &audience);
+        if let TurnResult::GameEnd(_) = result {
+            self.conclude(id);
+        }
+    }
+
+    fn draw(&mut self, session: usize, id: &str) {
+        let id = match self.playing(session, id) {
+            Some(id) => id,
+            None => return,
+        };
+        let table = &mut self.tables[id];
+        let color = table.color_of(session).unwrap();
+        match table.draw_offer {
+            Some(offer) if offer != color => {
+                table.game.agree_draw();
+                self.conclude(id);
+            }
+            _ => {
+                table.draw_offer = Some(color);
+                let opponent = table.players[color.inverse() as usize].unwrap();
+                let line = format!("drawoffer {}", id);
+                self.send(opponent, &line);
+            }
+        }
+    }
+
+    fn act<F>(&mut self, session: usize, id: &str, action: F)
+    where
+        F: FnOnce(&mut Game, Color) -> TurnResult,
+    {
+        if let Some(id) = self.playing(session, id) {
+            let table = &mut self.tables[id];
+            let color = table.color_of(session).unwrap();
+            if action(&mut table.game, color) != TurnResult::Failed {
+                self.conclude(id);
+            }
+        }
+    }
+
+    // The game, if the session is playing in it and it is still going.
+    fn playing(&mut self, session: usize, id: &str) -> Option<usize> {
+        let table = id.parse().ok().and_then(|id: usize| self.tables.get(id));
+        match table {
+            Some(table)
+                if table.started()
+                    && table.game.get_result().is_none()
+                    && table.color_of(session).is_some() =>
+            {
+                id.parse().ok()
+            }
+            _ => {
+                self.send(session, "error not playing that game");
+                None
+            }
+        }
+    }
+
+    fn conclude(&mut self, id: usize) {
+        let table = &self.tables[id];
+        let result = match table.game.get_result() {
+            Some(EndResult::Win(Color::White)) => "1-0",
+            Some(EndResult::Win(Color::Black)) => "0-1",
+            Some(EndResult::Tie) => "1/2-1/2",
+            None => return,
+        };
+        let line = format!("result {} {}", id, result);
+        for member in table.audience().iter() {
+            self.send(*member, &line);
+        }
+    }
+
+    fn send_clock(&mut self, id: usize, audience: &[usize]) {
+        let clock = match self.tables[id].game.get_clock() {
+            Some(clock) => clock,
+            None => return,
+        };
+        let line = format!(
+            "clock {} {} {}",
+            id,
+            clock.remaining(&Color::White).as_millis(),
+            clock.remaining(&Color::Black).as_millis()
+        );
+        for member in audience.iter() {
+            self.send(*member, &line);
+        }
+    }
+
+    fn name(&self, session: usize) -> &str {
+        self.sessions
+            .get(&session)
+            .and_then(|state| state.name.as_deref())
+            .unwrap_or("?")
+    }
+
+    // Lines to someone who is away are dropped, and they catch up when they
+    // resume.
+    fn send(&mut self, session: usize, line: &str) {
+        let state = match self.sessions.get_mut(&session) {
+            Some(state) => state,
+            None => return,
+        };
+        if let Some(output) = &mut state.output {
+            if writeln!(output, "{}", line)
+                .and_then(|_| output.flush())
+                .is_err()
+            {
+                state.output = None;
+            }
+        }
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
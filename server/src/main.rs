@@ -0,0 +1,82 @@
+use chess::game::clock::WallClock;
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+mod lobby;
+
+#[cfg(test)]
+mod tests;
+
+// Accepts connections until the listener fails, giving each its own thread.
+pub fn serve(listener: TcpListener, lobby: Arc<Mutex<lobby::Lobby>>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let lobby = lobby.clone();
+        thread::spawn(move || client(stream, lobby));
+    }
+}
+
+fn client(stream: TcpStream, lobby: Arc<Mutex<lobby::Lobby>>) {
+    // Replies are short lines that should not wait to be batched up.
+    let _ = stream.set_nodelay(true);
+    let output = match stream.try_clone() {
+        Ok(output) => output,
+        Err(_) => return,
+    };
+    let connection = lock(&lobby).connect(Box::new(output));
+    let mut session = connection;
+    for line in BufReader::new(stream).lines() {
+        match line {
+            Ok(line) => lock(&lobby).handle(&mut session, &line),
+            Err(_) => break,
+        }
+    }
+    lock(&lobby).disconnect(session, connection);
+}
+
+// A panic in one client's thread leaves the lobby poisoned, which should not
+// take every other client down with it.
+fn lock(lobby: &Mutex<lobby::Lobby>) -> MutexGuard<'_, lobby::Lobby> {
+    lobby.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Flags are checked a few times a second, since nobody moves when a clock
+// runs out.
+pub fn tick(lobby: Arc<Mutex<lobby::Lobby>>, every: Duration) {
+    loop {
+        thread::sleep(every);
+        lock(&lobby).tick();
+    }
+}
+
+// Usage: server [--port <port>], listening on port 7878 by default.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let port = args
+        .iter()
+        .position(|arg| arg == "--port")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(7878u16);
+
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on port {}: {}", port, error);
+            return;
+        }
+    };
+    let lobby = Arc::new(Mutex::new(lobby::Lobby::new(
+        Arc::new(WallClock::default()),
+    )));
+    let ticker = lobby.clone();
+    thread::spawn(move || tick(ticker, Duration::from_millis(100)));
+    serve(listener, lobby);
+}
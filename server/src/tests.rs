@@ -0,0 +1,244 @@
+use super::lobby::Lobby;
+use super::serve;
+use chess::game::clock::ManualTime;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+struct Server {
+    address: SocketAddr,
+    lobby: Arc<Mutex<Lobby>>,
+    time: ManualTime,
+}
+
+fn start() -> Server {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let time = ManualTime::default();
+    let lobby = Arc::new(Mutex::new(Lobby::new(Arc::new(time.clone()))));
+    let served = lobby.clone();
+    thread::spawn(move || serve(listener, served));
+    Server {
+        address,
+        lobby,
+        time,
+    }
+}
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(server: &Server) -> Self {
+        let writer = TcpStream::connect(server.address).unwrap();
+        writer.set_nodelay(true).unwrap();
+        writer
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        Client {
+            reader: BufReader::new(writer.try_clone().unwrap()),
+            writer,
+        }
+    }
+
+    // Connects and says hello, returning the token for resuming.
+    fn hello(server: &Server, name: &str) -> (Self, String) {
+        let mut client = Client::connect(server);
+        client.send(&format!("hello 1 {}", name));
+        let welcome = client.read();
+        let token = welcome.strip_prefix("welcome ").unwrap().to_string();
+        (client, token)
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.writer, "{}", line).unwrap();
+    }
+
+    fn read(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+
+    fn expect(&mut self, lines: &[&str]) {
+        for line in lines.iter() {
+            assert_eq!(self.read(), *line);
+        }
+    }
+}
+
+#[test]
+fn test_lobby_play_and_spectators() {
+    let server = start();
+    let (mut alice, _) = Client::hello(&server, "alice");
+    let (mut bob, _) = Client::hello(&server, "bob");
+    let (mut carol, _) = Client::hello(&server, "carol");
+
+    alice.send("create 5+3");
+    alice.expect(&["created 0 white"]);
+    bob.send("create 10 black");
+    bob.expect(&["created 1 black"]);
+    carol.send("list 5+3");
+    carol.expect(&["open 0 5+3 alice", "listed"]);
+
+    bob.send("join 0");
+    bob.expect(&["start 0 black alice", "clock 0 300000 300000"]);
+    alice.expect(&["start 0 white bob", "clock 0 300000 300000"]);
+    carol.send("watch 0");
+    carol.expect(&[&format!("position 0 {}", START), "clock 0 300000 300000"]);
+
+    // Moves are checked on the server, including whose turn it is.
+    alice.send("move 0 e2e5");
+    alice.expect(&["illegal 0 e2e5"]);
+    bob.send("move 0 e7e5");
+    bob.expect(&["illegal 0 e7e5"]);
+    carol.send("move 0 e2e4");
+    carol.expect(&["error not playing that game"]);
+
+    for (mover, notation) in [(0, "f2f3"), (1, "e7e5"), (0, "g2g4"), (1, "d8h4")].iter() {
+        let line = format!("move 0 {}", notation);
+        match mover {
+            0 => alice.send(&line),
+            _ => bob.send(&line),
+        }
+        let moved = format!("moved 0 {}", notation);
+        for client in [&mut alice, &mut bob, &mut carol].iter_mut() {
+            assert_eq!(client.read(), moved);
+            assert!(client.read().starts_with("clock 0 "));
+        }
+    }
+    for client in [&mut alice, &mut bob, &mut carol].iter_mut() {
+        client.expect(&["result 0 0-1"]);
+    }
+}
+
+#[test]
+fn test_seek_and_resume() {
+    let server = start();
+    let (mut alice, _) = Client::hello(&server, "alice");
+    let (mut bob, token) = Client::hello(&server, "bob");
+
+    alice.send("seek 1+0");
+    alice.expect(&["created 0 white"]);
+    bob.send("seek 1+0");
+    bob.expect(&["start 0 black alice", "clock 0 60000 60000"]);
+    alice.expect(&["start 0 white bob", "clock 0 60000 60000"]);
+
+    drop(bob);
+    server.time.advance(Duration::from_secs(2));
+    alice.send("move 0 e2e4");
+    alice.expect(&["moved 0 e2e4", "clock 0 58000 60000"]);
+
+    let mut bob = Client::connect(&server);
+    bob.send(&format!("resume 1 {}", token));
+    bob.expect(&[
+        &format!("welcome {}", token),
+        "game 0 black rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "clock 0 58000 60000",
+    ]);
+    bob.send("move 0 e7e5");
+    bob.expect(&["moved 0 e7e5", "clock 0 58000 60000"]);
+
+    let mut stranger = Client::connect(&server);
+    stranger.send("resume 1 0123456789abcdef");
+    stranger.expect(&["error unknown token"]);
+}
+
+#[test]
+fn test_time_forfeit_and_draws() {
+    let server = start();
+    let (mut alice, _) = Client::hello(&server, "alice");
+    let (mut bob, _) = Client::hello(&server, "bob");
+
+    alice.send("create 1");
+    alice.expect(&["created 0 white"]);
+    alice.send("create 1");
+    alice.expect(&["created 1 white"]);
+    for id in 0..2 {
+        bob.send(&format!("join {}", id));
+        bob.read();
+        bob.read();
+        alice.read();
+        alice.read();
+    }
+
+    alice.send("draw 1");
+    bob.expect(&["drawoffer 1"]);
+    bob.send("draw 1");
+    bob.expect(&["result 1 1/2-1/2"]);
+    alice.expect(&["result 1 1/2-1/2"]);
+
+    server.time.advance(Duration::from_secs(61));
+    server.lobby.lock().unwrap().tick();
+    alice.expect(&["result 0 0-1"]);
+    bob.expect(&["result 0 0-1"]);
+    bob.send("resign 0");
+    bob.expect(&["error not playing that game"]);
+
+    // A move sent after the flag fell ends the game rather than being played,
+    // even before anything has been played.
+    alice.send("create 1");
+    alice.expect(&["created 2 white"]);
+    bob.send("join 2");
+    bob.read();
+    bob.read();
+    alice.read();
+    alice.read();
+    server.time.advance(Duration::from_secs(61));
+    alice.send("move 2 e2e4");
+    alice.expect(&["result 2 0-1"]);
+    bob.expect(&["result 2 0-1"]);
+    alice.send("move 2 e2e4");
+    alice.expect(&["error not playing that game"]);
+}
+
+#[test]
+fn test_errors() {
+    let server = start();
+    let mut client = Client::connect(&server);
+    client.send("list");
+    client.expect(&["error say hello first"]);
+    client.send("hello 2 alice");
+    client.expect(&["error unsupported version"]);
+    client.send("hello 1 alice");
+    assert!(client.read().starts_with("welcome "));
+
+    client.send("create forever");
+    client.expect(&["error bad time control"]);
+    client.send("create 1e300");
+    client.expect(&["error bad time control"]);
+    client.send("seek 5+18446744073709551615");
+    client.expect(&["error bad time control"]);
+    client.send("list");
+    client.expect(&["listed"]);
+    client.send("join 7");
+    client.expect(&["error no such game"]);
+    client.send("create 3+2");
+    client.expect(&["created 0 white"]);
+    client.send("join 0");
+    client.expect(&["error cannot play yourself"]);
+    client.send("castle");
+    client.expect(&["error unknown command"]);
+}
+
+#[test]
+fn test_survives_poisoned_lobby() {
+    let server = start();
+    let lobby = server.lobby.clone();
+    let poisoner = thread::spawn(move || {
+        let _lobby = lobby.lock().unwrap();
+        panic!("a client thread failed");
+    });
+    assert!(poisoner.join().is_err());
+    assert!(server.lobby.is_poisoned());
+
+    let (mut client, _) = Client::hello(&server, "alice");
+    client.send("create 3+2");
+    client.expect(&["created 0 white"]);
+}
@@ -0,0 +1,275 @@
+use chess::book::{Book, Selection};
+use chess::engine::{Engine, Info, Limits, Score};
+use chess::game::{Game, Turn, TurnResult};
+use chess::tablebase::Tablebase;
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub struct Session<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    game: Game,
+    engine: Engine,
+    search: Option<JoinHandle<()>>,
+    // Whether the running search only ends on stop, as with go infinite.
+    unbounded: bool,
+    book: Option<Book>,
+    book_selection: Selection,
+}
+
+impl<W: Write + Send + 'static> Session<W> {
+    pub fn new(output: W) -> Self {
+        Session {
+            output: Arc::new(Mutex::new(output)),
+            game: Game::new(),
+            engine: Engine::new(),
+            search: None,
+            unbounded: false,
+            book: None,
+            book_selection: Selection::Weighted,
+        }
+    }
+
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            match line {
+                Ok(line) => {
+                    if !self.handle(&line) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        // Nobody is left to send stop, so a search without limits would
+        // never end.
+        if self.unbounded {
+            self.engine.stop();
+        }
+        self.wait();
+    }
+
+    pub fn handle(&mut self, line: &str) -> bool {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("uci") => {
+                self.send("id name mvid-chess");
+                self.send("id author Markus Videfors");
+                self.send("option name Hash type spin default 16 min 1 max 1024");
+                self.send("option name Threads type spin default 1 min 1 max 64");
+                self.send("option name BookFile type string default <empty>");
+                self.send("option name BookBestMove type check default false");
+                self.send("option name SyzygyPath type string default <empty>");
+                self.send("uciok");
+            }
+            Some("isready") => self.send("readyok"),
+            Some("ucinewgame") => {
+                self.wait();
+                self.engine.clear();
+                self.game = Game::new();
+            }
+            Some("setoption") => {
+                self.wait();
+                self.set_option(&tokens.collect::<Vec<&str>>());
+            }
+            Some("position") => {
+                self.wait();
+                self.set_position(&tokens.collect::<Vec<&str>>());
+            }
+            Some("go") => {
+                self.wait();
+                let limits = parse_limits(&tokens.collect::<Vec<&str>>());
+                self.go(limits);
+            }
+            // A stop with no search running is ignored.
+            Some("stop") if self.search.is_some() => {
+                self.engine.stop();
+                self.wait();
+            }
+            Some("stop") => (),
+            Some("quit") => {
+                self.engine.stop();
+                return false;
+            }
+            Some(command) => self.send(&format!("info string unknown command {}", command)),
+            None => (),
+        }
+
+        true
+    }
+
+    fn send(&self, message: &str) {
+        send(&self.output, message);
+    }
+
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.join().unwrap();
+        }
+    }
+
+    fn set_option(&mut self, tokens: &[&str]) {
+        let name_end = tokens
+            .iter()
+            .position(|token| *token == "value")
+            .unwrap_or(tokens.len());
+        let name = tokens
+            .get(1..name_end)
+            .unwrap_or_default()
+            .join(" ")
+            .to_lowercase();
+        let value = tokens.get(name_end + 1..).unwrap_or_default().join(" ");
+
+        match (name.as_str(), value.parse::<usize>().ok()) {
+            ("hash", Some(megabytes)) => self.engine.set_hash_size(megabytes),
+            ("threads", Some(threads)) => self.engine.set_threads(threads),
+            ("bookfile", _) if value.is_empty() || value == "<empty>" => self.book = None,
+            ("bookfile", _) => match Book::open(&value) {
+                Ok(book) => self.book = Some(book),
+                Err(error) => {
+                    self.book = None;
+                    self.send(&format!(
+                        "info string could not open book {}: {}",
+                        value, error
+                    ));
+                }
+            },
+            ("bookbestmove", _) => {
+                self.book_selection = if value == "true" {
+                    Selection::Best
+                } else {
+                    Selection::Weighted
+                }
+            }
+            ("syzygypath", _) if value.is_empty() || value == "<empty>" => {
+                self.engine.set_tablebase(None)
+            }
+            ("syzygypath", _) => match Tablebase::open(&value) {
+                Ok(tablebase) => self.engine.set_tablebase(Some(tablebase)),
+                Err(error) => {
+                    self.engine.set_tablebase(None);
+                    self.send(&format!(
+                        "info string could not open tablebases {}: {}",
+                        value, error
+                    ));
+                }
+            },
+            _ => self.send(&format!("info string unsupported option {}", name)),
+        }
+
+        self.engine.set_book(self.book.clone(), self.book_selection);
+    }
+
+    fn set_position(&mut self, tokens: &[&str]) {
+        let moves_start = tokens
+            .iter()
+            .position(|token| *token == "moves")
+            .unwrap_or(tokens.len());
+
+        let game = match tokens.first() {
+            Some(&"startpos") => Some(Game::new()),
+            Some(&"fen") => Game::from_fen(&tokens[1..moves_start].join(" ")),
+            _ => None,
+        };
+
+        self.game = match game {
+            Some(game) => game,
+            None => {
+                self.send("info string invalid position");
+                return;
+            }
+        };
+
+        for notation in tokens.iter().skip(moves_start + 1) {
+            let result = match Turn::parse(notation) {
+                Some(turn) => self.game.play(&turn),
+                None => TurnResult::Failed,
+            };
+
+            if result == TurnResult::Failed {
+                self.send(&format!("info string illegal move {}", notation));
+                return;
+            }
+        }
+    }
+
+    fn go(&mut self, limits: Limits) {
+        self.unbounded = limits.depth.is_none()
+            && limits.nodes.is_none()
+            && limits.movetime.is_none()
+            && limits.white_time.is_none()
+            && limits.black_time.is_none();
+        self.engine.reset_stop();
+        let engine = self.engine.clone();
+        let game = self.game.clone();
+        let output = self.output.clone();
+
+        self.search = Some(thread::spawn(move || {
+            let best = engine.search(&game, &limits, |info| send(&output, &format_info(info)));
+
+            match best {
+                Some(turn) => send(&output, &format!("bestmove {}", turn)),
+                None => send(&output, "bestmove 0000"),
+            }
+        }));
+    }
+}
+
+impl<W: Write + Send + 'static> Drop for Session<W> {
+    fn drop(&mut self) {
+        self.engine.stop();
+        self.wait();
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, message: &str) {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", message).unwrap();
+    output.flush().unwrap();
+}
+
+fn format_info(info: &Info) -> String {
+    let score = match info.score {
+        Score::Centipawns(value) => format!("cp {}", value),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let millis = info.time.as_millis().max(1);
+    let pv: Vec<String> = info.pv.iter().map(|turn| turn.to_string()).collect();
+
+    format!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        info.depth,
+        score,
+        info.nodes,
+        info.nodes as u128 * 1000 / millis,
+        info.time.as_millis(),
+        pv.join(" ")
+    )
+}
+
+pub fn parse_limits(tokens: &[&str]) -> Limits {
+    let mut limits = Limits::default();
+    let mut tokens = tokens.iter();
+
+    while let Some(token) = tokens.next() {
+        let mut value = || tokens.next().and_then(|value| value.parse::<u64>().ok());
+        let millis = |value: Option<u64>| value.map(Duration::from_millis);
+
+        match *token {
+            "depth" => limits.depth = value().map(|depth| depth as u32),
+            "nodes" => limits.nodes = value(),
+            "movetime" => limits.movetime = millis(value()),
+            "wtime" => limits.white_time = millis(value()),
+            "btime" => limits.black_time = millis(value()),
+            "winc" => limits.white_increment = millis(value()),
+            "binc" => limits.black_increment = millis(value()),
+            "movestogo" => limits.moves_to_go = value().map(|moves| moves as u32),
+            _ => (),
+        }
+    }
+
+    limits
+}
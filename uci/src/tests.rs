@@ -0,0 +1,196 @@
+use super::session::{parse_limits, Session};
+use chess::book;
+use chess::engine::Limits;
+use chess::game::Game;
+use std::env;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn exchange(input: &str) -> Vec<String> {
+    let output = Output::default();
+    Session::new(output.clone()).run(Cursor::new(input.to_string()));
+
+    let bytes = output.0.lock().unwrap().clone();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn test_handshake() {
+    let lines = exchange("uci\nisready\nquit\n");
+
+    assert_eq!(lines[0], "id name mvid-chess");
+    assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 1024".to_string()));
+    assert!(lines.contains(&"option name Threads type spin default 1 min 1 max 64".to_string()));
+    assert_eq!(lines[lines.len() - 2], "uciok");
+    assert_eq!(lines[lines.len() - 1], "readyok");
+}
+
+#[test]
+fn test_go_depth_reports_info_and_bestmove() {
+    let lines = exchange("position startpos moves e2e4 e7e5\ngo depth 2\n");
+
+    let infos: Vec<&String> = lines
+        .iter()
+        .filter(|line| line.starts_with("info depth"))
+        .collect();
+    assert_eq!(infos.len(), 2);
+    assert!(infos[1].starts_with("info depth 2 score cp "));
+    assert!(infos[1].contains(" pv "));
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn test_position_fen_finds_mate() {
+    let lines = exchange("position fen k7/8/1K6/8/8/8/8/7R w - - 0 1\ngo depth 3\nquit\n");
+
+    assert!(lines.iter().any(|line| line.contains("score mate 1")));
+    assert_eq!(lines.last().unwrap(), "bestmove h1h8");
+}
+
+#[test]
+fn test_position_with_castling_and_moves() {
+    let lines = exchange(
+        "position fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1 e8c8 f1f8\ngo depth 1\n",
+    );
+
+    assert!(lines.last().unwrap().starts_with("bestmove d8f8"));
+}
+
+#[test]
+fn test_illegal_move_is_reported() {
+    let lines = exchange("position startpos moves e2e5\n");
+
+    assert_eq!(lines, vec!["info string illegal move e2e5"]);
+}
+
+#[test]
+fn test_go_on_finished_game() {
+    let lines = exchange("position fen k7/1Q6/1K6/8/8/8/8/8 b - - 0 1\ngo depth 2\n");
+
+    assert_eq!(lines.last().unwrap(), "bestmove 0000");
+}
+
+#[test]
+fn test_stop_and_isready_during_search() {
+    let lines = exchange(
+        "setoption name Hash value 4\nsetoption name Threads value 2\nposition startpos\ngo infinite\nisready\nstop\n",
+    );
+
+    assert!(lines.contains(&"readyok".to_string()));
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+    assert!(!lines.iter().any(|line| line.contains("unsupported")));
+}
+
+#[test]
+fn test_infinite_search_ends_with_input() {
+    let lines = exchange("position startpos\ngo infinite\n");
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn test_stray_stop_does_not_cut_next_search() {
+    let lines = exchange("position startpos\ngo depth 2\nstop\nposition startpos\ngo depth 4\n");
+    assert!(lines.iter().any(|line| line.starts_with("info depth 4 ")));
+}
+
+#[test]
+fn test_clock_of_other_side_only() {
+    // Black is to move but only White's time is given.
+    let lines = exchange("position startpos moves e2e4\ngo wtime 60000\n");
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+    assert!(!lines.iter().any(|line| line.starts_with("info depth 20 ")));
+}
+
+#[test]
+fn test_go_limits() {
+    assert_eq!(
+        parse_limits(&[
+            "wtime",
+            "60000",
+            "btime",
+            "55000",
+            "winc",
+            "1000",
+            "binc",
+            "1000",
+            "movestogo",
+            "20"
+        ]),
+        Limits {
+            white_time: Some(Duration::from_millis(60000)),
+            black_time: Some(Duration::from_millis(55000)),
+            white_increment: Some(Duration::from_millis(1000)),
+            black_increment: Some(Duration::from_millis(1000)),
+            moves_to_go: Some(20),
+            ..Limits::default()
+        }
+    );
+    assert_eq!(
+        parse_limits(&["nodes", "5000", "movetime", "250", "depth", "4"]),
+        Limits {
+            depth: Some(4),
+            nodes: Some(5000),
+            movetime: Some(Duration::from_millis(250)),
+            ..Limits::default()
+        }
+    );
+}
+
+#[test]
+fn test_movetime_limits_search() {
+    let lines = exchange("position startpos\ngo movetime 100\n");
+
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn test_book_file_option() {
+    let mut bytes = book::key(&Game::new()).to_be_bytes().to_vec();
+    bytes.extend_from_slice(&[0x01, 0x95, 0x00, 0x01, 0, 0, 0, 0]);
+    let path = env::temp_dir().join(format!("mvid-chess-uci-book-{}.bin", std::process::id()));
+    fs::write(&path, bytes).unwrap();
+
+    let lines = exchange(&format!(
+        "setoption name BookFile value {}\nsetoption name BookBestMove value true\nposition startpos\ngo depth 3\nposition startpos moves g1f3\ngo depth 1\n",
+        path.display()
+    ));
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(lines[0], "bestmove g1f3");
+    assert!(lines[1].starts_with("info depth 1"));
+    assert!(lines[2].starts_with("bestmove "));
+}
+
+#[test]
+fn test_missing_book_file() {
+    let lines = exchange("setoption name BookFile value /nonexistent/book.bin\n");
+
+    assert!(lines[0].starts_with("info string could not open book /nonexistent/book.bin"));
+}
+
+#[test]
+fn test_missing_syzygy_path() {
+    let lines = exchange("setoption name SyzygyPath value /nonexistent/syzygy\nsetoption name SyzygyPath value <empty>\n");
+
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("info string could not open tablebases /nonexistent/syzygy"));
+}
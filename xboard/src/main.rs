@@ -0,0 +1,11 @@
+use std::io;
+
+mod session;
+
+#[cfg(test)]
+mod tests;
+
+fn main() {
+    let stdin = io::stdin();
+    session::Session::new(io::stdout()).run(stdin.lock());
+}
@@ -0,0 +1,284 @@
+use chess::engine::{Engine, Info, Limits, Score};
+use chess::game::{EndResult, Game, Turn, TurnResult};
+use chess::tablebase::Tablebase;
+use chess::Color;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Level {
+    pub moves_per_session: u32,
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl Level {
+    pub fn parse(tokens: &[&str]) -> Option<Level> {
+        let moves_per_session = tokens.first()?.parse().ok()?;
+
+        let mut base = tokens.get(1)?.split(':');
+        let minutes: u64 = base.next()?.parse().ok()?;
+        let seconds: u64 = match base.next() {
+            Some(seconds) => seconds.parse().ok()?,
+            None => 0,
+        };
+
+        let increment: f64 = tokens.get(2)?.parse().ok()?;
+
+        Some(Level {
+            moves_per_session,
+            base: Duration::from_secs(minutes * 60 + seconds),
+            increment: Duration::from_secs_f64(increment.max(0.0)),
+        })
+    }
+}
+
+pub struct Session<W: Write> {
+    output: W,
+    game: Game,
+    engine: Engine,
+    engine_color: Option<Color>,
+    post: bool,
+    depth: Option<u32>,
+    move_time: Option<Duration>,
+    level: Option<Level>,
+    engine_time: Option<Duration>,
+    opponent_time: Option<Duration>,
+}
+
+impl<W: Write> Session<W> {
+    pub fn new(output: W) -> Self {
+        Session {
+            output,
+            game: Game::new(),
+            engine: Engine::new(),
+            engine_color: Some(Color::Black),
+            post: false,
+            depth: None,
+            move_time: None,
+            level: None,
+            engine_time: None,
+            opponent_time: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            match line {
+                Ok(line) => {
+                    if !self.handle(&line) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let arguments = tokens.get(1..).unwrap_or_default();
+
+        match tokens.first() {
+            Some(&"xboard") | Some(&"accepted") | Some(&"rejected") | Some(&"random")
+            | Some(&"hard") | Some(&"easy") | Some(&"computer") | Some(&"?") => (),
+            Some(&"protover") => self.send(
+                "feature myname=\"mvid-chess\" ping=1 setboard=1 usermove=1 \
+                 colors=0 sigint=0 sigterm=0 egt=\"syzygy\" done=1",
+            ),
+            Some(&"new") => {
+                self.game = Game::new();
+                self.engine.clear();
+                self.engine_color = Some(Color::Black);
+                self.depth = None;
+                self.engine_time = None;
+                self.opponent_time = None;
+            }
+            Some(&"force") => self.engine_color = None,
+            Some(&"go") => {
+                self.engine_color = Some(self.game.color);
+                self.think();
+            }
+            Some(&"usermove") => match arguments.first() {
+                Some(notation) => self.user_move(notation),
+                None => self.error("usermove", "missing move"),
+            },
+            Some(&"undo") => {
+                self.game.undo();
+            }
+            Some(&"remove") => {
+                self.game.undo();
+                self.game.undo();
+            }
+            Some(&"setboard") => match Game::from_fen(&arguments.join(" ")) {
+                Some(game) => self.game = game,
+                None => self.send("tellusererror Illegal position"),
+            },
+            Some(&"level") => match Level::parse(arguments) {
+                Some(level) => {
+                    self.level = Some(level);
+                    self.move_time = None;
+                }
+                None => self.error(line, "invalid time control"),
+            },
+            Some(&"st") => match arguments.first().and_then(|time| time.parse::<f64>().ok()) {
+                Some(seconds) => {
+                    self.move_time = Some(Duration::from_secs_f64(seconds.max(0.0)));
+                    self.level = None;
+                }
+                None => self.error(line, "invalid time"),
+            },
+            Some(&"sd") => match arguments.first().and_then(|depth| depth.parse().ok()) {
+                Some(depth) => self.depth = Some(depth),
+                None => self.error(line, "invalid depth"),
+            },
+            Some(&"time") => self.engine_time = parse_centiseconds(arguments),
+            Some(&"otim") => self.opponent_time = parse_centiseconds(arguments),
+            Some(&"result") => self.engine_color = None,
+            Some(&"post") => self.post = true,
+            Some(&"nopost") => self.post = false,
+            Some(&"egtpath") => match arguments {
+                ["syzygy", path @ ..] if !path.is_empty() => {
+                    match Tablebase::open(path.join(" ")) {
+                        Ok(tablebase) => self.engine.set_tablebase(Some(tablebase)),
+                        Err(_) => self.error(line, "could not open tablebases"),
+                    }
+                }
+                _ => self.error(line, "unsupported tablebases"),
+            },
+            Some(&"ping") => {
+                let message = format!("pong {}", arguments.join(" "));
+                self.send(&message);
+            }
+            Some(&"quit") => return false,
+            Some(command) => match Turn::parse(command) {
+                Some(_) => self.user_move(command),
+                None => self.error(command, "unknown command"),
+            },
+            None => (),
+        }
+
+        true
+    }
+
+    fn send(&mut self, message: &str) {
+        writeln!(self.output, "{}", message).unwrap();
+        self.output.flush().unwrap();
+    }
+
+    fn error(&mut self, command: &str, reason: &str) {
+        let message = format!("Error ({}): {}", reason, command);
+        self.send(&message);
+    }
+
+    fn user_move(&mut self, notation: &str) {
+        let result = match Turn::parse(notation) {
+            Some(turn) => self.game.play(&turn),
+            None => TurnResult::Failed,
+        };
+
+        if result == TurnResult::Failed {
+            let message = format!("Illegal move: {}", notation);
+            self.send(&message);
+            return;
+        }
+
+        if !self.announce_result() && self.engine_color == Some(self.game.color) {
+            self.think();
+        }
+    }
+
+    fn think(&mut self) {
+        if self.game.get_result().is_some() {
+            return;
+        }
+
+        let limits = self.limits();
+        let engine = self.engine.clone();
+        let mut thinking: Vec<String> = vec![];
+        let best = engine.search(&self.game, &limits, |info| thinking.push(format_info(info)));
+
+        if self.post {
+            for line in thinking {
+                self.send(&line);
+            }
+        }
+
+        if let Some(turn) = best {
+            self.game.play(&turn);
+            let message = format!("move {}", turn);
+            self.send(&message);
+            self.announce_result();
+        }
+    }
+
+    fn limits(&self) -> Limits {
+        let mut limits = Limits {
+            depth: self.depth,
+            movetime: self.move_time,
+            ..Limits::default()
+        };
+
+        if let (Some(level), None) = (&self.level, self.move_time) {
+            let own = self.engine_time.unwrap_or(level.base);
+            let opponent = self.opponent_time.unwrap_or(level.base);
+            let (white, black) = match self.game.color {
+                Color::White => (own, opponent),
+                Color::Black => (opponent, own),
+            };
+
+            limits.white_time = Some(white);
+            limits.black_time = Some(black);
+            limits.white_increment = Some(level.increment);
+            limits.black_increment = Some(level.increment);
+
+            if level.moves_per_session > 0 {
+                let played = (self.game.get_history().len() / 2) as u32;
+                limits.moves_to_go =
+                    Some(level.moves_per_session - played % level.moves_per_session);
+            }
+        }
+
+        limits
+    }
+
+    fn announce_result(&mut self) -> bool {
+        let message = match self.game.get_result() {
+            Some(EndResult::Win(Color::White)) => "1-0 {White mates}",
+            Some(EndResult::Win(Color::Black)) => "0-1 {Black mates}",
+            Some(EndResult::Tie) => "1/2-1/2 {Stalemate}",
+            None => return false,
+        };
+
+        self.send(message);
+        true
+    }
+}
+
+fn parse_centiseconds(arguments: &[&str]) -> Option<Duration> {
+    let centiseconds: u64 = arguments.first()?.parse().ok()?;
+    Some(Duration::from_millis(centiseconds * 10))
+}
+
+fn format_info(info: &Info) -> String {
+    let score = match info.score {
+        Score::Centipawns(value) => value,
+        Score::Mate(moves) if moves > 0 => 100_000 + moves,
+        Score::Mate(moves) => -100_000 + moves,
+    };
+    let pv: Vec<String> = info.pv.iter().map(|turn| turn.to_string()).collect();
+
+    format!(
+        "{} {} {} {} {}",
+        info.depth,
+        score,
+        info.time.as_millis() / 10,
+        info.nodes,
+        pv.join(" ")
+    )
+}
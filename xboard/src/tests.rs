@@ -0,0 +1,85 @@
+use super::session::{Level, Session};
+use std::time::Duration;
+
+fn replay(transcript: &str) {
+    let mut session = Session::new(vec![]);
+    let mut expected: Vec<&str> = vec![];
+
+    for line in transcript.lines() {
+        if let Some(input) = line.strip_prefix("> ") {
+            if !session.handle(input) {
+                break;
+            }
+        } else if let Some(output) = line.strip_prefix("< ") {
+            expected.push(output);
+        }
+    }
+
+    let output = String::from_utf8(session.into_output()).unwrap();
+    let actual: Vec<&str> = output.lines().collect();
+
+    assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        match expected.strip_suffix(" ...") {
+            Some(prefix) => assert!(actual.starts_with(prefix), "{} != {}", actual, expected),
+            None => assert_eq!(actual, expected),
+        }
+    }
+}
+
+#[test]
+fn test_handshake() {
+    replay(include_str!("../transcripts/handshake.txt"));
+}
+
+#[test]
+fn test_engine_replies_as_black() {
+    replay(include_str!("../transcripts/new_game.txt"));
+}
+
+#[test]
+fn test_force_undo_and_go() {
+    replay(include_str!("../transcripts/force_and_undo.txt"));
+}
+
+#[test]
+fn test_setboard_and_mate() {
+    replay(include_str!("../transcripts/setboard_mate.txt"));
+}
+
+#[test]
+fn test_illegal_moves_and_errors() {
+    replay(include_str!("../transcripts/illegal.txt"));
+}
+
+#[test]
+fn test_result_stops_engine() {
+    replay(include_str!("../transcripts/result.txt"));
+}
+
+#[test]
+fn test_thinking_output() {
+    replay(include_str!("../transcripts/post.txt"));
+}
+
+#[test]
+fn test_level_parsing() {
+    assert_eq!(
+        Level::parse(&["40", "90", "30"]),
+        Some(Level {
+            moves_per_session: 40,
+            base: Duration::from_secs(90 * 60),
+            increment: Duration::from_secs(30),
+        })
+    );
+    assert_eq!(
+        Level::parse(&["0", "2:30", "0.5"]),
+        Some(Level {
+            moves_per_session: 0,
+            base: Duration::from_secs(150),
+            increment: Duration::from_millis(500),
+        })
+    );
+    assert_eq!(Level::parse(&["0", "x", "0"]), None);
+    assert_eq!(Level::parse(&["40"]), None);
+}